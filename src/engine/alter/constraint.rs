@@ -0,0 +1,74 @@
+use crate::engine::alter::{
+    altered_field, check_references, check_table, existing_field, invalid, store,
+};
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, table_name, Engine};
+use crate::parser::query::{ConstraintType, Identifier};
+use crate::parser::Parser;
+use std::io;
+
+/// Adds constraints to a field. Every record already in the table has to satisfy them, and
+/// `DEFAULT` and `INC` only apply to records inserted afterwards.
+pub fn add_constraints(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, name) = (db_name(parser), table_name(parser), altered_field(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    existing_field(&table, table_name, name)?;
+
+    let identifier = Identifier::Field(name.to_string());
+    for constraint in &parser.query_data.constraints[&identifier] {
+        if table.has_constraint(&identifier, constraint.constraint_type.clone()) {
+            return Err(invalid(format!(
+                "Field {} is already constrained by {}",
+                name, constraint.constraint_type
+            )));
+        }
+        table
+            .constraints
+            .entry(identifier.clone())
+            .or_default()
+            .push(constraint.clone());
+    }
+
+    check_table(&table)?;
+    check_references(engine, db_name, table_name, &table)?;
+    table.arrange();
+    store(engine, db_name, table_name, table)
+}
+
+/// Removes constraints from a field. Only the type of each constraint is looked at, so
+/// `DROP ON field SUCHTHAT` removes the condition whatever it was.
+pub fn drop_constraints(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, name) = (db_name(parser), table_name(parser), altered_field(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    existing_field(&table, table_name, name)?;
+
+    let identifier = Identifier::Field(name.to_string());
+    for constraint in &parser.query_data.constraints[&identifier] {
+        let constraint_type = constraint.constraint_type.clone();
+        if !table.has_constraint(&identifier, constraint_type.clone()) {
+            return Err(invalid(format!(
+                "Field {} is not constrained by {}",
+                name, constraint_type
+            )));
+        }
+
+        if constraint_type == ConstraintType::PKey {
+            if let Some((other_name, _)) =
+                table_file::referencing(engine, db_name, table_name)?.first()
+            {
+                return Err(invalid(format!(
+                    "The PKEY of {} is referenced by {}",
+                    table_name, other_name
+                )));
+            }
+        }
+
+        table
+            .constraints
+            .get_mut(&identifier)
+            .unwrap()
+            .retain(|existing| existing.constraint_type != constraint_type);
+    }
+
+    store(engine, db_name, table_name, table)
+}
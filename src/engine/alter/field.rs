@@ -0,0 +1,170 @@
+use crate::engine::alter::{
+    altered_field, check_references, check_table, existing_field, invalid, store,
+};
+use crate::engine::insert::generated_value;
+use crate::engine::table_file::{self, Field, TableFile};
+use crate::engine::{db_name, eval, table_name, Engine};
+use crate::parser::constants::*;
+use crate::parser::query::{ConstraintType, Identifier};
+use crate::parser::Parser;
+use std::io;
+
+fn size(options: &[Identifier]) -> Option<i32> {
+    match options.first() {
+        Some(Identifier::IntLiteral(size)) => Some(*size),
+        _ => None,
+    }
+}
+
+/// Whether every value of the `old` datatype also fits the `new` one. Sizes may only grow,
+/// options may only be added, `CHAR` may become a `STRING` and `UINT` may become an `INT` of a
+/// larger size.
+fn widens(old: (&Identifier, &[Identifier]), new: (&Identifier, &[Identifier])) -> bool {
+    let (old_dtype, old_options) = old;
+    let (new_dtype, new_options) = new;
+
+    match (
+        old_dtype.to_string().as_str(),
+        new_dtype.to_string().as_str(),
+    ) {
+        (DT_OPTIONS, DT_OPTIONS) => old_options
+            .iter()
+            .all(|option| new_options.contains(option)),
+        (DT_CHAR, DT_CHAR | DT_STRING) => true,
+        (DT_UINT, DT_INT) => match (size(old_options), size(new_options)) {
+            (Some(old_size), Some(new_size)) => new_size > old_size,
+            (_, None) => true,
+            (None, Some(_)) => false,
+        },
+        (old_dtype, new_dtype) if old_dtype == new_dtype => {
+            match (size(old_options), size(new_options)) {
+                (Some(old_size), Some(new_size)) => new_size >= old_size,
+                (_, None) => true,
+                (None, Some(_)) => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Adds a field to a table. Existing records are given the field's `DEFAULT`, or numbered in
+/// order for `INC` fields, and are otherwise left without a value.
+pub fn add_field(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, name) = (db_name(parser), table_name(parser), altered_field(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    if table.field_index(name).is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Field {} already exists on {}", name, table_name),
+        ));
+    }
+
+    let field: Field = parser.query_data.fields[0].clone();
+    let (_, _, identifier) = &field;
+    table.constraints.insert(
+        identifier.clone(),
+        parser
+            .query_data
+            .constraints
+            .get(identifier)
+            .cloned()
+            .unwrap_or_default(),
+    );
+    table.fields.push(field);
+
+    let index = table.fields.len() - 1;
+    for record in table.records.iter_mut() {
+        record.values.push(None);
+    }
+    for i in 0..table.records.len() {
+        if !table.records[i].deleted {
+            table.records[i].values[index] = generated_value(&table, index);
+        }
+    }
+
+    check_table(&table)?;
+    check_references(engine, db_name, table_name, &table)?;
+    table.arrange();
+    store(engine, db_name, table_name, table)
+}
+
+/// Removes a field and its values from a table. Fields other tables or constraints still depend
+/// on cannot be removed.
+pub fn drop_field(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, name) = (db_name(parser), table_name(parser), altered_field(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    let index = existing_field(&table, table_name, name)?;
+    if table.fields.len() == 1 {
+        return Err(invalid(format!(
+            "Field {} is the only field of {}, drop the table instead",
+            name, table_name
+        )));
+    }
+
+    if table.pkey() == Some(index) {
+        if let Some((other_name, _)) = table_file::referencing(engine, db_name, table_name)?.first()
+        {
+            return Err(invalid(format!(
+                "Field {} is the PKEY of {}, which is referenced by {}",
+                name, table_name, other_name
+            )));
+        }
+    }
+
+    for (other, constraints) in &table.constraints {
+        let depends = constraints
+            .iter()
+            .filter(|constraint| constraint.constraint_type == ConstraintType::Suchthat)
+            .flat_map(|constraint| &constraint.options)
+            .any(|condition| eval::mentions(condition, name));
+        if depends && *other != Identifier::Field(name.to_string()) {
+            return Err(invalid(format!(
+                "Field {} is used by the SUCHTHAT constraint of {}",
+                name, other
+            )));
+        }
+    }
+
+    if let Some(used) = table
+        .indexes
+        .iter()
+        .find(|used| used.fields.contains(&Identifier::Field(name.to_string())))
+    {
+        return Err(invalid(format!(
+            "Field {} is used by index {}, drop the index first",
+            name, used.name
+        )));
+    }
+
+    let (_, _, identifier) = table.fields.remove(index);
+    table.constraints.remove(&identifier);
+    for record in table.records.iter_mut() {
+        record.values.remove(index);
+    }
+
+    store(engine, db_name, table_name, table)
+}
+
+/// Changes the datatype of a field to a wider one, so every stored value keeps fitting it.
+pub fn retype_field(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, name) = (db_name(parser), table_name(parser), altered_field(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    let index = existing_field(&table, table_name, name)?;
+
+    let (new_dtype, new_options, _) = &parser.query_data.fields[0];
+    let (old_dtype, old_options, _) = &table.fields[index];
+    if !widens((old_dtype, old_options), (new_dtype, new_options)) {
+        return Err(invalid(format!(
+            "Field {} of type {} cannot be narrowed to {}",
+            name,
+            table_file::datatype(&table.fields[index]),
+            table_file::datatype(&parser.query_data.fields[0])
+        )));
+    }
+
+    table.fields[index].0 = new_dtype.clone();
+    table.fields[index].1 = new_options.clone();
+
+    check_table(&table)?;
+    store(engine, db_name, table_name, table)
+}
@@ -0,0 +1,90 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::constraints;
+use crate::engine::insert;
+use crate::engine::table_file::TableFile;
+use crate::engine::Engine;
+use crate::parser::query::{Alteration, ConstraintType, Identifier};
+use crate::parser::Parser;
+use std::io;
+
+pub mod constraint;
+pub mod field;
+pub mod mode;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Returns the name of the field an `ALTER` statement is about.
+fn altered_field(parser: &Parser) -> &str {
+    match &parser.query_data.altered_field {
+        Identifier::Field(name) => name,
+        found => panic!("Expected field name, found {:?}", found),
+    }
+}
+
+/// Finds the position of a field that has to exist for the alteration to make sense.
+fn existing_field(table: &TableFile, table_name: &str, name: &str) -> Result<usize, io::Error> {
+    table.field_index(name).ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Field {} not found on {}", name, table_name),
+    ))
+}
+
+/// Checks an altered table as a whole: it holds at most one `PKEY`, and every live record still
+/// satisfies the datatypes and constraints of its fields.
+fn check_table(table: &TableFile) -> Result<(), io::Error> {
+    let pkeys = table
+        .fields
+        .iter()
+        .filter(|(_, _, field)| table.has_constraint(field, ConstraintType::PKey))
+        .count();
+    if pkeys > 1 {
+        return Err(invalid("A table can only have one PKEY".to_string()));
+    }
+
+    for (i, record) in table.records.iter().enumerate() {
+        if !record.deleted {
+            constraints::check_record(table, &record.values, Some(i))?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks the `FKEY`s of an altered table: the tables they refer to exist and have a `PKEY`,
+/// and every live record refers to a live record of them.
+fn check_references(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+) -> Result<(), io::Error> {
+    insert::check_references(engine, db_name, table_name, table)?;
+    for record in table.live_records() {
+        insert::check_foreign_keys(engine, db_name, table_name, table, &record.values)?;
+    }
+    Ok(())
+}
+
+/// Writes an altered table back along with its new schema in the catalog.
+fn store(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    mut table: TableFile,
+) -> Result<(), io::Error> {
+    table.save(engine, db_name, table_name)?;
+    Catalog::set(engine, db_name, table_name, Some(&table))
+}
+
+pub fn alter(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    match parser.query_data.alteration {
+        Alteration::Mode => mode::alter_mode(engine, parser),
+        Alteration::AddField => field::add_field(engine, parser),
+        Alteration::DropField => field::drop_field(engine, parser),
+        Alteration::RetypeField => field::retype_field(engine, parser),
+        Alteration::AddConstraints => constraint::add_constraints(engine, parser),
+        Alteration::DropConstraints => constraint::drop_constraints(engine, parser),
+        Alteration::None => Ok(()),
+    }
+}
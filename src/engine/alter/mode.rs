@@ -0,0 +1,17 @@
+use crate::engine::alter::store;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, table_name, Engine};
+use crate::parser::Parser;
+use std::io;
+
+/// Switches the modes of an existing table and rewrites its file into the matching layout.
+/// Groups not mentioned in the query keep their current mode.
+pub fn alter_mode(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+
+    table.modes = table_file::resolve_modes(&parser.query_data.modes, &table.modes)?;
+    table.arrange();
+
+    store(engine, db_name, table_name, table)
+}
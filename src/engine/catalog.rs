@@ -0,0 +1,182 @@
+use crate::engine::statistics::Statistics;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::Engine;
+use crate::parser::{query::Identifier, Parser};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::PathBuf;
+
+/// The schema of every table of a database, and the statistics of those analyzed, stored in
+/// the `.catalog` file of its directory.
+///
+/// Each line of the file is the `TABLE` statement of one table, the same as the header of its
+/// table file, so it is read back with the regular parser. The schemas held here have no
+/// records. Statements changing a schema keep the catalog in sync through [`Catalog::set`].
+///
+/// The statistics of the tables that were analyzed follow the line of their table, indented.
+/// Writes to an analyzed table keep them up to date through [`Catalog::set_statistics`], under
+/// the lock at [`catalog_path`](crate::engine::lock::catalog_path).
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    pub tables: BTreeMap<String, TableFile>,
+    pub statistics: BTreeMap<String, Statistics>,
+}
+
+/// Starts the lines of the statistics of a table.
+const INDENT: &str = "  ";
+
+fn malformed(db_name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Malformed entry in the catalog of {}", db_name),
+    )
+}
+
+pub fn path(engine: &Engine, db_name: &str) -> PathBuf {
+    engine.path(db_name).join(".catalog")
+}
+
+impl Catalog {
+    /// Loads the catalog of a database. Databases created before catalogs existed get one built
+    /// from their table files.
+    pub fn load(engine: &Engine, db_name: &str) -> Result<Catalog, io::Error> {
+        if !engine.storage().is_dir(&engine.path(db_name)) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Database {} not found", db_name),
+            ));
+        }
+        if !engine.storage().exists(&path(engine, db_name)) {
+            return Catalog::rebuild(engine, db_name);
+        }
+
+        let mut catalog = Catalog::default();
+        let contents = engine.storage().read_to_string(&path(engine, db_name))?;
+        let mut statistics: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        let mut last: Option<String> = None;
+        for line in contents.lines() {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(line) = line.strip_prefix(INDENT) {
+                let table_name = last.clone().ok_or(malformed(db_name))?;
+                statistics.entry(table_name).or_default().push(line);
+                continue;
+            }
+
+            let mut p = Parser::new();
+            p.set_query(line.to_string()).parse();
+            let Identifier::StringLiteral(table_name) = p.query_data.table_name else {
+                return Err(malformed(db_name));
+            };
+
+            last = Some(table_name.clone());
+            catalog.tables.insert(
+                table_name,
+                TableFile {
+                    fields: p.query_data.fields,
+                    constraints: p.query_data.constraints,
+                    modes: p.query_data.modes,
+                    indexes: p.query_data.indexes,
+                    generation: 0,
+                    counters: HashMap::new(),
+                    records: Vec::new(),
+                },
+            );
+        }
+        for (table_name, lines) in statistics {
+            let table_statistics = Statistics::parse(&table_name, lines.into_iter())?;
+            catalog.statistics.insert(table_name, table_statistics);
+        }
+
+        Ok(catalog)
+    }
+
+    /// Builds the catalog of a database again from the headers of its table files, for when
+    /// the tables were replaced as a whole, such as by restoring a snapshot. Tables that were
+    /// analyzed get their statistics gathered again from their records.
+    pub fn rebuild(engine: &Engine, db_name: &str) -> Result<Catalog, io::Error> {
+        let analyzed = match engine.storage().exists(&path(engine, db_name)) {
+            true => Catalog::load(engine, db_name)?.statistics,
+            false => BTreeMap::new(),
+        };
+
+        let mut catalog = Catalog::default();
+        for table_name in table_file::table_names(engine, db_name)? {
+            let table = TableFile::load(engine, db_name, &table_name)?;
+            if analyzed.contains_key(&table_name) {
+                catalog
+                    .statistics
+                    .insert(table_name.clone(), Statistics::build(&table));
+            }
+            catalog.tables.insert(table_name, table.schema());
+        }
+
+        catalog.save(engine, db_name)?;
+        Ok(catalog)
+    }
+
+    pub fn save(&self, engine: &Engine, db_name: &str) -> Result<(), io::Error> {
+        let mut contents = String::new();
+        for (table_name, table) in &self.tables {
+            contents.push_str(&table.header(db_name, table_name));
+            contents.push('\n');
+            if let Some(statistics) = self.statistics.get(table_name) {
+                for line in statistics.serialize().lines() {
+                    contents.push_str(INDENT);
+                    contents.push_str(line);
+                    contents.push('\n');
+                }
+            }
+        }
+
+        engine
+            .storage()
+            .write(&path(engine, db_name), contents.as_bytes())
+    }
+
+    /// Records the schema of a table, or removes the table and its statistics from the catalog
+    /// when `table` is `None`.
+    pub fn set(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        table: Option<&TableFile>,
+    ) -> Result<(), io::Error> {
+        let mut catalog = Catalog::load(engine, db_name)?;
+        match table {
+            Some(table) => catalog
+                .tables
+                .insert(table_name.to_string(), table.schema()),
+            None => {
+                catalog.statistics.remove(table_name);
+                catalog.tables.remove(table_name)
+            }
+        };
+        catalog.save(engine, db_name)
+    }
+
+    /// Records the statistics of a table, or forgets them when `statistics` is `None`.
+    pub fn set_statistics(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        statistics: Option<Statistics>,
+    ) -> Result<(), io::Error> {
+        let mut catalog = Catalog::load(engine, db_name)?;
+        match statistics {
+            Some(statistics) => catalog
+                .statistics
+                .insert(table_name.to_string(), statistics),
+            None => catalog.statistics.remove(table_name),
+        };
+        catalog.save(engine, db_name)
+    }
+
+    pub fn table(&self, db_name: &str, table_name: &str) -> Result<&TableFile, io::Error> {
+        self.tables.get(table_name).ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ))
+    }
+}
@@ -0,0 +1,132 @@
+use crate::engine::eval;
+use crate::engine::table_file::{Field, TableFile};
+use crate::parser::constants::*;
+use crate::parser::query::{ConstraintType, Identifier};
+use std::io;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn size(options: &[Identifier]) -> Option<u32> {
+    match options.first() {
+        Some(Identifier::IntLiteral(size)) => Some(*size as u32),
+        _ => None,
+    }
+}
+
+fn is_option(options: &[Identifier], data: &str) -> bool {
+    options
+        .iter()
+        .any(|option| option.to_string().trim_matches('\'') == data.trim_matches('\''))
+}
+
+/// Checks that a value fits the datatype of its field. The items of an array are checked one by
+/// one, as the datatype of a collection applies to its items.
+pub fn check_datatype(field: &Field, value: &Identifier) -> Result<(), io::Error> {
+    let (dtype, options, identifier) = field;
+    if let Identifier::Array(items) = value {
+        for item in items {
+            check_datatype(field, item)?;
+        }
+        return Ok(());
+    }
+
+    let mismatch = || {
+        invalid(format!(
+            "Value {} does not fit field {} of type {}",
+            value, identifier, dtype
+        ))
+    };
+
+    match (dtype.to_string().as_str(), value) {
+        (DT_UINT, Identifier::IntLiteral(data)) => {
+            let fits = match size(options) {
+                Some(size @ 1..=3) => (*data as i64) < (1i64 << (size * 8)),
+                _ => true,
+            };
+            if *data < 0 || !fits {
+                return Err(mismatch());
+            }
+        }
+        (DT_INT, Identifier::IntLiteral(data)) => {
+            if let Some(size @ 1..=3) = size(options) {
+                let bound = 1i64 << (size * 8 - 1);
+                if (*data as i64) < -bound || (*data as i64) >= bound {
+                    return Err(mismatch());
+                }
+            }
+        }
+        (DT_TIMESTAMP, Identifier::IntLiteral(_)) => {}
+        (DT_STRING | DT_CHAR, Identifier::StringLiteral(data)) => {
+            let limit = if dtype.to_string() == DT_CHAR {
+                Some(1)
+            } else {
+                size(options)
+            };
+            let length = data.trim_matches('\'').chars().count() as u32;
+            if limit.is_some_and(|limit| length > limit) {
+                return Err(mismatch());
+            }
+        }
+        (DT_OPTIONS, Identifier::StringLiteral(data) | Identifier::Field(data))
+            if is_option(options, data) => {}
+        (DT_UINT | DT_INT | DT_TIMESTAMP | DT_STRING | DT_CHAR | DT_OPTIONS, _) => {
+            return Err(mismatch())
+        }
+        // BYTES and FLOAT values have no literal of their own yet
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Checks a record of `table` against the datatypes and the `EXISTS`, `UNIQUE`, `PKEY` and
+/// `SUCHTHAT` constraints of its fields. `position` is the record's own position in the table,
+/// if it is already stored there, so it is not compared against itself.
+pub fn check_record(
+    table: &TableFile,
+    values: &[Option<Identifier>],
+    position: Option<usize>,
+) -> Result<(), io::Error> {
+    for (i, field) in table.fields.iter().enumerate() {
+        let (_, _, identifier) = field;
+
+        let required = table.has_constraint(identifier, ConstraintType::Exists)
+            || table.has_constraint(identifier, ConstraintType::PKey);
+        let Some(value) = &values[i] else {
+            if required {
+                return Err(invalid(format!("Field {} must have a value", identifier)));
+            }
+            continue;
+        };
+
+        check_datatype(field, value)?;
+
+        let unique = table.has_constraint(identifier, ConstraintType::Unique)
+            || table.has_constraint(identifier, ConstraintType::PKey);
+        if unique
+            && table.records.iter().enumerate().any(|(j, record)| {
+                Some(j) != position && !record.deleted && record.values[i] == values[i]
+            })
+        {
+            return Err(invalid(format!(
+                "Field {} already has a record with value {}",
+                identifier, value
+            )));
+        }
+
+        if let Some(constraint) = table.constraint(identifier, ConstraintType::Suchthat) {
+            for condition in &constraint.options {
+                if !eval::matches(condition, table, values)? {
+                    return Err(invalid(format!(
+                        "Value {} of field {} does not satisfy {}",
+                        value, identifier, condition
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
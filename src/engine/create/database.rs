@@ -1,10 +1,11 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::Engine;
 use crate::parser::{query::Identifier, Parser};
-use std::fs;
 
-pub fn create_database(parser: &Parser) -> Result<(), std::io::Error> {
+pub fn create_database(engine: &Engine, parser: &Parser) -> Result<(), std::io::Error> {
     if let Identifier::StringLiteral(db_name) = &parser.query_data.db_name {
-        fs::create_dir(db_name)?;
-        return Ok(());
+        engine.storage().create_dir(&engine.path(db_name))?;
+        return Catalog::default().save(engine, db_name);
     }
     panic!("Database name not found, or it was not in a string literal identifier")
 }
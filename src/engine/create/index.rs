@@ -0,0 +1,31 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::index;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, index_name, table_name, Engine};
+use crate::parser::query::{Identifier, Index};
+use crate::parser::Parser;
+use std::io;
+
+/// Adds an index on some fields of a table, built right away from the records it holds.
+pub fn create_index(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, index_name) =
+        (db_name(parser), table_name(parser), index_name(parser));
+    if !table_file::exists(engine, db_name, table_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ));
+    }
+
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    let index = Index {
+        name: Identifier::StringLiteral(index_name.to_string()),
+        fields: parser.query_data.index_fields.clone(),
+        kind: parser.query_data.index_kind,
+    };
+    index::check(&table, table_name, &index)?;
+
+    table.indexes.push(index);
+    table.save(engine, db_name, table_name)?;
+    Catalog::set(engine, db_name, table_name, Some(&table))
+}
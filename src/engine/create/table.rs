@@ -1,13 +1,25 @@
-use crate::parser::{query::Identifier, Parser};
-use std::fs;
+use crate::engine::catalog::Catalog;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, table_name, Engine};
+use crate::engine::{index, insert};
+use crate::parser::Parser;
+use std::io;
 
-pub fn create_table(parser: &Parser) -> Result<(), std::io::Error> {
-    if let Identifier::StringLiteral(db_name) = &parser.query_data.db_name {
-        if let Identifier::StringLiteral(table_name) = &parser.query_data.table_name {
-            fs::File::create(format!("{}/{}.asql", db_name, table_name))?;
-            return Ok(());
-        }
-        panic!("Table name not found, or it was not in a string literal identifier")
+pub fn create_table(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    if table_file::exists(engine, db_name, table_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Table {} already exists on {}", table_name, db_name),
+        ));
     }
-    panic!("Database name not found, or it was not in a string literal identifier")
+
+    let mut table = TableFile::from_query(&parser.query_data)?;
+    for index in std::mem::take(&mut table.indexes) {
+        index::check(&table, table_name, &index)?;
+        table.indexes.push(index);
+    }
+    insert::check_references(engine, db_name, table_name, &table)?;
+    table.save(engine, db_name, table_name)?;
+    Catalog::set(engine, db_name, table_name, Some(&table))
 }
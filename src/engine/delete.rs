@@ -0,0 +1,83 @@
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, eval, table_name, Engine, Output};
+use crate::parser::{query::Identifier, Parser};
+use std::io;
+
+fn refers_to(value: &Option<Identifier>, key: &Identifier) -> bool {
+    match value {
+        Some(Identifier::Array(items)) => items.contains(key),
+        Some(value) => value == key,
+        None => false,
+    }
+}
+
+/// Refuses to remove records whose `PKEY` is still referenced by an `FKEY` field of any live
+/// record in the database.
+pub fn check_references(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    removed: &[usize],
+) -> Result<(), io::Error> {
+    let Some(pkey) = table.pkey() else {
+        return Ok(());
+    };
+
+    for other_name in table_file::table_names(engine, db_name)? {
+        let other = if other_name == table_name {
+            table.clone()
+        } else {
+            TableFile::load(engine, db_name, &other_name)?
+        };
+
+        for (field, referenced) in other.references() {
+            if referenced != table_name {
+                continue;
+            }
+            for (i, record) in other.records.iter().enumerate() {
+                if record.deleted || (other_name == table_name && removed.contains(&i)) {
+                    continue;
+                }
+                for index in removed {
+                    if let Some(key) = &table.records[*index].values[pkey] {
+                        if refers_to(&record.values[field], key) {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!(
+                                    "Record {} of {} is still referenced by {}",
+                                    key, table_name, other_name
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes every record matching the query's conditions and returns how many were deleted.
+pub fn delete(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+
+    let mut removed: Vec<usize> = Vec::new();
+    for (i, record) in table.records.iter().enumerate() {
+        if !record.deleted && eval::matches(&parser.query_data.conditions, &table, &record.values)?
+        {
+            removed.push(i);
+        }
+    }
+    if removed.is_empty() {
+        return Ok(Output::Count(0));
+    }
+
+    check_references(engine, db_name, table_name, &table, &removed)?;
+
+    table.delete(engine, db_name, table_name, &removed)?;
+
+    Ok(Output::Count(removed.len()))
+}
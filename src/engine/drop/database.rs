@@ -0,0 +1,20 @@
+use crate::engine::db_name;
+use crate::engine::Engine;
+use crate::parser::Parser;
+use std::io;
+
+/// Removes a database along with its tables and snapshots.
+pub fn drop_database(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let db_name = db_name(parser);
+    if !engine.storage().is_dir(&engine.path(db_name)) {
+        if parser.query_data.if_exists {
+            return Ok(());
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Database {} not found", db_name),
+        ));
+    }
+
+    engine.storage().remove_dir_all(&engine.path(db_name))
+}
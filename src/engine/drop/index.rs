@@ -0,0 +1,41 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::index;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, index_name, table_name, Engine};
+use crate::parser::query::Identifier;
+use crate::parser::Parser;
+use std::io;
+
+/// Removes an index from a table along with its file. The records of the table are left as
+/// they are.
+pub fn drop_index(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, index_name) =
+        (db_name(parser), table_name(parser), index_name(parser));
+    if !table_file::exists(engine, db_name, table_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ));
+    }
+
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    if table.index(index_name).is_none() {
+        if parser.query_data.if_exists {
+            return Ok(());
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Index {} not found on {}", index_name, table_name),
+        ));
+    }
+
+    table
+        .indexes
+        .retain(|index| index.name != Identifier::StringLiteral(index_name.to_string()));
+    let file = index::path(engine, db_name, table_name, index_name);
+    if engine.storage().exists(&file) {
+        engine.storage().remove_file(&file)?;
+    }
+    table.save(engine, db_name, table_name)?;
+    Catalog::set(engine, db_name, table_name, Some(&table))
+}
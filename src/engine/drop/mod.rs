@@ -0,0 +1,3 @@
+pub mod database;
+pub mod index;
+pub mod table;
@@ -0,0 +1,67 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::snapshot::table_files;
+use crate::engine::{db_name, table_name, Engine};
+use crate::engine::{lock, table_file};
+use crate::parser::{query::ConstraintType, Parser};
+use std::io;
+
+/// Removes a table and the files stored alongside it. Tables still referred to by an `FKEY` of
+/// another table are only dropped with `CASCADE`, which also removes those `FKEY` constraints.
+pub fn drop_table(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    if !table_file::exists(engine, db_name, table_name) {
+        if parser.query_data.if_exists {
+            return Ok(());
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ));
+    }
+
+    let referencing = table_file::referencing(engine, db_name, table_name)?;
+    if !referencing.is_empty() && !parser.query_data.cascade {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Table {} is referenced by {}, use CASCADE to drop it anyway",
+                table_name,
+                referencing
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        ));
+    }
+
+    for (other_name, mut other) in referencing {
+        for (field, referenced) in other.references() {
+            if referenced == table_name {
+                let (_, _, identifier) = &other.fields[field];
+                let identifier = identifier.clone();
+                other
+                    .constraints
+                    .get_mut(&identifier)
+                    .unwrap()
+                    .retain(|constraint| constraint.constraint_type != ConstraintType::FKey);
+            }
+        }
+        other.save(engine, db_name, &other_name)?;
+        Catalog::set(engine, db_name, &other_name, Some(&other))?;
+    }
+
+    for file in table_files(engine, db_name, table_name)? {
+        engine.storage().remove_file(&file)?;
+    }
+    // The database is held alone, so nobody else holds the locks of the table either
+    for file in [
+        lock::table_path(engine, db_name, table_name),
+        lock::writer_path(engine, db_name, table_name),
+    ] {
+        if engine.storage().exists(&file) {
+            engine.storage().remove_file(&file)?;
+        }
+    }
+    Catalog::set(engine, db_name, table_name, None)
+}
@@ -0,0 +1,143 @@
+use crate::engine::table_file::TableFile;
+use crate::parser::query::{BinaryOperation, Expression, Identifier, UnaryOperation};
+use std::io;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn boolean(value: bool) -> Option<Identifier> {
+    Some(Identifier::IntLiteral(value as i32))
+}
+
+/// Whether a value counts as true in a condition. Missing values, `0` and empty strings or
+/// arrays are false.
+pub fn truthy(value: &Option<Identifier>) -> bool {
+    match value {
+        None => false,
+        Some(Identifier::IntLiteral(value)) => *value != 0,
+        Some(Identifier::StringLiteral(value)) => !value.trim_matches('\'').is_empty(),
+        Some(Identifier::Array(items)) => !items.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn integer(
+    value: &Option<Identifier>,
+    operation: &dyn std::fmt::Display,
+) -> Result<i32, io::Error> {
+    match value {
+        Some(Identifier::IntLiteral(value)) => Ok(*value),
+        Some(value) => Err(invalid(format!(
+            "Operation {} expects a number, found {}",
+            operation, value
+        ))),
+        None => Err(invalid(format!(
+            "Operation {} expects a number, found nothing",
+            operation
+        ))),
+    }
+}
+
+/// Evaluates an expression against a record of `table`. Fields evaluate to their value in the
+/// record, and conditions evaluate to `1` or `0`.
+pub fn evaluate(
+    expression: &Expression,
+    table: &TableFile,
+    values: &[Option<Identifier>],
+) -> Result<Option<Identifier>, io::Error> {
+    match expression {
+        Expression::None => Ok(None),
+        Expression::Identifier(Identifier::Field(name)) => match table.field_index(name) {
+            Some(index) => Ok(values[index].clone()),
+            None => Err(invalid(format!("Field {} not found", name))),
+        },
+        Expression::Identifier(identifier) => Ok(Some(identifier.clone())),
+        Expression::Unary(operator, operand) => {
+            let value = evaluate(operand, table, values)?;
+            Ok(match operator {
+                UnaryOperation::Exists => boolean(value.is_some()),
+                UnaryOperation::LogicalNot => boolean(!truthy(&value)),
+                UnaryOperation::Abs => Some(Identifier::IntLiteral(
+                    integer(&value, operator)?.wrapping_abs(),
+                )),
+                UnaryOperation::Negative => Some(Identifier::IntLiteral(
+                    integer(&value, operator)?.wrapping_neg(),
+                )),
+                UnaryOperation::BitwiseNot => {
+                    Some(Identifier::IntLiteral(!integer(&value, operator)?))
+                }
+            })
+        }
+        Expression::Binary(operator, operands) => {
+            let left = evaluate(&operands.0, table, values)?;
+            match operator {
+                BinaryOperation::And if !truthy(&left) => return Ok(boolean(false)),
+                BinaryOperation::Or if truthy(&left) => return Ok(boolean(true)),
+                _ => {}
+            }
+            let right = evaluate(&operands.1, table, values)?;
+
+            Ok(match operator {
+                BinaryOperation::And | BinaryOperation::Or => boolean(truthy(&right)),
+                BinaryOperation::Equals => boolean(left.is_some() && left == right),
+                BinaryOperation::NotEquals => {
+                    boolean(left.is_some() && right.is_some() && left != right)
+                }
+                BinaryOperation::GreaterThan
+                | BinaryOperation::GreaterThanEqualTo
+                | BinaryOperation::LesserThan
+                | BinaryOperation::LesserThanEqualTo => {
+                    // Comparisons against missing values never hold
+                    let (Some(left), Some(right)) = (left, right) else {
+                        return Ok(boolean(false));
+                    };
+                    boolean(match operator {
+                        BinaryOperation::GreaterThan => left > right,
+                        BinaryOperation::GreaterThanEqualTo => left >= right,
+                        BinaryOperation::LesserThan => left < right,
+                        _ => left <= right,
+                    })
+                }
+                BinaryOperation::Addition
+                | BinaryOperation::Subtraction
+                | BinaryOperation::Multiplication
+                | BinaryOperation::Division => {
+                    let (left, right) = (integer(&left, operator)?, integer(&right, operator)?);
+                    Some(Identifier::IntLiteral(match operator {
+                        BinaryOperation::Addition => left.wrapping_add(right),
+                        BinaryOperation::Subtraction => left.wrapping_sub(right),
+                        BinaryOperation::Multiplication => left.wrapping_mul(right),
+                        _ => left
+                            .checked_div(right)
+                            .ok_or(invalid(format!("Cannot divide {} by {}", left, right)))?,
+                    }))
+                }
+            })
+        }
+    }
+}
+
+/// Whether a record of `table` satisfies a condition. An empty condition matches every record.
+pub fn matches(
+    condition: &Expression,
+    table: &TableFile,
+    values: &[Option<Identifier>],
+) -> Result<bool, io::Error> {
+    if *condition == Expression::None {
+        return Ok(true);
+    }
+    Ok(truthy(&evaluate(condition, table, values)?))
+}
+
+/// Whether an expression refers to the field `name` anywhere within it.
+pub fn mentions(expression: &Expression, name: &str) -> bool {
+    match expression {
+        Expression::Identifier(Identifier::Field(field)) => field == name,
+        Expression::Unary(_, operand) => mentions(operand, name),
+        Expression::Binary(_, operands) => {
+            mentions(&operands.0, name) || mentions(&operands.1, name)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,15 @@
+/// 64 bit FNV-1a, which is stable across platforms and Rust versions unlike the hashers of the
+/// standard library.
+pub fn fnv(contents: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in contents {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The hash of `contents` given by [`fnv`], written as 16 hexadecimal digits.
+pub fn hex(contents: &[u8]) -> String {
+    format!("{:016x}", fnv(contents))
+}
@@ -0,0 +1,590 @@
+use crate::engine::index::{self, Key};
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::Engine;
+use crate::parser::query::Index;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+/// Every number of the header and of the pages is written with this many digits, so the length
+/// of a page is known before the pages it points to are.
+const WIDTH: usize = 20;
+
+/// The length of the header, the generation on its first line and the size of the pages, the
+/// root page and the number of pages on the second.
+const HEADER: usize = 4 * (WIDTH + 1);
+
+/// Indexes are built with pages of at least this many bytes.
+const MIN_PAGE: usize = 1024;
+
+/// A record in an ordered index: its key, then its offset in the table file. Entries are
+/// ordered by key, and records holding the same key by offset, so every entry is unique.
+type Entry = (Key, u64);
+
+/// An ordered index, the default kind.
+///
+/// Its file is made of pages of the same size, the first one holding the generation of the table
+/// and where the others are. Every other page is a node of a B-tree: leaves hold entries in
+/// order along with the page of the next leaf, and branches hold their children in order, each
+/// with the smallest entry it held when it was made, which no entry of the child before it
+/// reaches. Looking a key up reads the pages on the way from the root to the leaf holding the
+/// key, then the leaves following it for as long as they hold keys within range, without
+/// reading the rest of the file.
+///
+/// Records added or removed later are put in or taken out of their leaf, which is split in two
+/// once it outgrows its page, the new half going to a page added to the end of the file. Pages
+/// are made at least four times as large as the longest entry written to them, and the index is
+/// built again should a longer entry come along.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BTreeIndex {
+    pub entries: BTreeMap<Key, Vec<u64>>,
+}
+
+/// A page of an index file other than the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Entries in order, then the page of the next leaf, or 0 for the last leaf.
+    Leaf(Vec<Entry>, u64),
+    /// Children in order, each with the smallest entry it held when it was made.
+    Branch(Vec<(Entry, u64)>),
+}
+
+fn number(value: u64) -> String {
+    format!("{:0width$}", value, width = WIDTH)
+}
+
+/// The line of an entry in a leaf.
+fn leaf_line(entry: &Entry) -> String {
+    let (key, offset) = entry;
+    index::entry(false, &[*offset], key)
+}
+
+/// The line of a child in a branch, the longest line a page can hold for an entry.
+fn branch_line(entry: &Entry, page: u64) -> String {
+    format!("{} {}", number(page), leaf_line(entry))
+}
+
+/// The size of the pages of an index whose longest entry is `longest` bytes long in a branch.
+fn page_size(longest: usize) -> usize {
+    (4 * longest + WIDTH + 3).next_power_of_two().max(MIN_PAGE)
+}
+
+impl Node {
+    fn serialize(&self) -> String {
+        match self {
+            Node::Leaf(entries, next) => {
+                let mut res = format!("L {}\n", number(*next));
+                for entry in entries {
+                    res.push_str(&leaf_line(entry));
+                }
+                res
+            }
+            Node::Branch(children) => {
+                let mut res = "B\n".to_string();
+                for (entry, page) in children {
+                    res.push_str(&branch_line(entry, *page));
+                }
+                res
+            }
+        }
+    }
+
+    fn parse(contents: &str) -> Option<Node> {
+        let mut lines = contents
+            .lines()
+            .map(|line| line.trim_end())
+            .filter(|line| !line.is_empty());
+        let entry = |line: &str| match index::parse_entry(line)? {
+            (false, offsets, key) if offsets.len() == 1 => Some((key, offsets[0])),
+            _ => None,
+        };
+        match lines.next()? {
+            "B" => Some(Node::Branch(
+                lines
+                    .map(|line| {
+                        let (page, line) = line.split_once(' ')?;
+                        Some((entry(line)?, page.parse().ok()?))
+                    })
+                    .collect::<Option<Vec<(Entry, u64)>>>()?,
+            )),
+            leaf => Some(Node::Leaf(
+                lines.map(entry).collect::<Option<Vec<Entry>>>()?,
+                leaf.strip_prefix("L ")?.parse().ok()?,
+            )),
+        }
+    }
+
+    /// The smallest entry of the node when it was made.
+    fn first(&self) -> Option<Entry> {
+        match self {
+            Node::Leaf(entries, _) => entries.first().cloned(),
+            Node::Branch(children) => children.first().map(|(entry, _)| entry.clone()),
+        }
+    }
+
+    /// Splits a node that outgrew its page in two halves of about the same length, the second
+    /// one going to `page`. Returns that half along with the smallest entry it holds.
+    fn split(&mut self, page: u64) -> (Node, Entry) {
+        fn half<T>(items: &mut Vec<T>, len: impl Fn(&T) -> usize) -> Vec<T> {
+            let total: usize = items.iter().map(&len).sum();
+            let mut seen = 0;
+            let at = items
+                .iter()
+                .position(|item| {
+                    seen += len(item);
+                    seen * 2 >= total
+                })
+                .map_or(1, |i| i + 1)
+                .clamp(1, items.len() - 1);
+            items.split_off(at)
+        }
+
+        match self {
+            Node::Leaf(entries, next) => {
+                let right = half(entries, |entry| leaf_line(entry).len());
+                let first = right[0].clone();
+                let res = Node::Leaf(right, *next);
+                *next = page;
+                (res, first)
+            }
+            Node::Branch(children) => {
+                let right = half(children, |(entry, page)| branch_line(entry, *page).len());
+                let first = right[0].0.clone();
+                (Node::Branch(right), first)
+            }
+        }
+    }
+}
+
+/// An index file being walked or brought up to date, with the pages changed or added so far.
+struct Tree<'a> {
+    engine: &'a Engine,
+    path: PathBuf,
+    table_name: &'a str,
+    index_name: &'a str,
+    generation: u64,
+    size: usize,
+    root: u64,
+    pages: u64,
+    changed: BTreeMap<u64, Node>,
+}
+
+impl<'a> Tree<'a> {
+    /// Reads the header of an index file, unless the file is missing.
+    fn open(
+        engine: &'a Engine,
+        db_name: &str,
+        table_name: &'a str,
+        index_name: &'a str,
+    ) -> Result<Option<Tree<'a>>, io::Error> {
+        let path = index::path(engine, db_name, table_name, index_name);
+        if !engine.storage().exists(&path) {
+            return Ok(None);
+        }
+
+        let header = engine.storage().read_at(&path, 0, HEADER)?;
+        let numbers = String::from_utf8_lossy(&header)
+            .split_whitespace()
+            .map(|value| value.parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()
+            .filter(|numbers| numbers.len() == 4 && numbers[1] > 0)
+            .ok_or_else(|| index::malformed(table_name, index_name))?;
+        Ok(Some(Tree {
+            engine,
+            path,
+            table_name,
+            index_name,
+            generation: numbers[0],
+            size: numbers[1] as usize,
+            root: numbers[2],
+            pages: numbers[3],
+            changed: BTreeMap::new(),
+        }))
+    }
+
+    /// Reads the header of an index file, unless the file is missing or not up to date with
+    /// `generation`.
+    fn current(
+        engine: &'a Engine,
+        db_name: &str,
+        table_name: &'a str,
+        index_name: &'a str,
+        generation: u64,
+    ) -> Result<Option<Tree<'a>>, io::Error> {
+        Ok(Tree::open(engine, db_name, table_name, index_name)?
+            .filter(|tree| tree.generation == generation))
+    }
+
+    fn read(&self, page: u64) -> Result<Node, io::Error> {
+        if let Some(node) = self.changed.get(&page) {
+            return Ok(node.clone());
+        }
+        let contents =
+            self.engine
+                .storage()
+                .read_at(&self.path, page * self.size as u64, self.size)?;
+        Node::parse(&String::from_utf8_lossy(&contents))
+            .ok_or_else(|| index::malformed(self.table_name, self.index_name))
+    }
+
+    /// The pages from the root down to the leaf where `entry` belongs, along with their nodes:
+    /// in every branch, the last child starting at or before `entry`, or the first one.
+    fn descend(&self, entry: &Entry) -> Result<Vec<(u64, Node)>, io::Error> {
+        let mut res = Vec::new();
+        let mut page = self.root;
+        loop {
+            let node = self.read(page)?;
+            let next = match &node {
+                Node::Leaf(..) => None,
+                Node::Branch(children) => {
+                    let child = children
+                        .partition_point(|(first, _)| first <= entry)
+                        .saturating_sub(1);
+                    Some(
+                        children
+                            .get(child)
+                            .ok_or_else(|| index::malformed(self.table_name, self.index_name))?
+                            .1,
+                    )
+                }
+            };
+            res.push((page, node));
+            match next {
+                Some(next) => page = next,
+                None => return Ok(res),
+            }
+        }
+    }
+
+    /// Goes through the entries from the first one holding `start` or a key after it, in order,
+    /// for as long as `take` gives `Some`, and keeps the offsets of those it gives `Some(true)`.
+    fn scan(
+        &self,
+        start: &Key,
+        mut take: impl FnMut(&Key) -> Option<bool>,
+    ) -> Result<Vec<u64>, io::Error> {
+        let mut res = Vec::new();
+        let Some((_, mut node)) = self.descend(&(start.clone(), 0))?.pop() else {
+            return Ok(res);
+        };
+        loop {
+            let Node::Leaf(entries, next) = node else {
+                return Err(index::malformed(self.table_name, self.index_name));
+            };
+            for (key, offset) in entries.iter().filter(|(key, _)| key >= start) {
+                match take(key) {
+                    Some(true) => res.push(*offset),
+                    Some(false) => {}
+                    None => return Ok(res),
+                }
+            }
+            if next == 0 {
+                return Ok(res);
+            }
+            node = self.read(next)?;
+        }
+    }
+
+    /// Puts an entry in its leaf, splitting the nodes it outgrows on the way up.
+    fn insert(&mut self, entry: Entry) -> Result<(), io::Error> {
+        let mut path = self.descend(&entry)?;
+        let (mut page, mut node) = path
+            .pop()
+            .ok_or_else(|| index::malformed(self.table_name, self.index_name))?;
+        if let Node::Leaf(entries, _) = &mut node {
+            let at = entries.partition_point(|existing| *existing < entry);
+            entries.insert(at, entry);
+        }
+
+        while node.serialize().len() >= self.size {
+            let new_page = self.pages;
+            self.pages += 1;
+            let (right, first) = node.split(new_page);
+            self.changed.insert(new_page, right);
+
+            let left = node;
+            node = match path.pop() {
+                Some((parent_page, mut parent)) => {
+                    if let Node::Branch(children) = &mut parent {
+                        let at = children
+                            .iter()
+                            .position(|(_, child)| *child == page)
+                            .map_or(children.len(), |i| i + 1);
+                        children.insert(at, (first, new_page));
+                    }
+                    self.changed.insert(page, left);
+                    page = parent_page;
+                    parent
+                }
+                None => {
+                    // The root was split, and a new root above both halves takes its place
+                    let root = Node::Branch(vec![
+                        (left.first().unwrap_or_else(|| first.clone()), page),
+                        (first, new_page),
+                    ]);
+                    self.changed.insert(page, left);
+                    page = self.pages;
+                    self.pages += 1;
+                    self.root = page;
+                    root
+                }
+            };
+        }
+        self.changed.insert(page, node);
+        Ok(())
+    }
+
+    /// Takes an entry out of its leaf. Leaves left empty stay in the tree until it is built
+    /// again.
+    fn remove(&mut self, entry: &Entry) -> Result<(), io::Error> {
+        let (page, mut node) = self
+            .descend(entry)?
+            .pop()
+            .ok_or_else(|| index::malformed(self.table_name, self.index_name))?;
+        if let Node::Leaf(entries, _) = &mut node {
+            entries.retain(|existing| existing != entry);
+        }
+        self.changed.insert(page, node);
+        Ok(())
+    }
+
+    fn header(&self) -> String {
+        format!(
+            "{}\n{} {} {}\n",
+            table_file::generation(self.generation),
+            number(self.size as u64),
+            number(self.root),
+            number(self.pages)
+        )
+    }
+
+    /// A node written out to the full size of a page.
+    fn page(&self, contents: String) -> Vec<u8> {
+        let mut res = contents.into_bytes();
+        res.resize(self.size - 1, b' ');
+        res.push(b'\n');
+        res
+    }
+}
+
+impl BTreeIndex {
+    /// Indexes the live records of a table, stored at `offsets` in its table file.
+    pub fn build(
+        table: &TableFile,
+        index: &Index,
+        offsets: &[u64],
+    ) -> Result<BTreeIndex, io::Error> {
+        Ok(BTreeIndex {
+            entries: index::entries(table, index, offsets)?,
+        })
+    }
+
+    /// Loads every entry of an index of a table, unless it is missing or not up to date with
+    /// `generation`, the generation of the table as it is read.
+    pub fn load(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        index_name: &str,
+        generation: u64,
+    ) -> Result<Option<BTreeIndex>, io::Error> {
+        let Some(tree) = Tree::current(engine, db_name, table_name, index_name, generation)? else {
+            return Ok(None);
+        };
+
+        let mut res = BTreeIndex::default();
+        let mut node = tree.read(tree.root)?;
+        while let Node::Branch(children) = &node {
+            let first = children
+                .first()
+                .ok_or_else(|| index::malformed(table_name, index_name))?;
+            node = tree.read(first.1)?;
+        }
+        loop {
+            let Node::Leaf(entries, next) = node else {
+                return Err(index::malformed(table_name, index_name));
+            };
+            for (key, offset) in entries {
+                res.entries.entry(key).or_default().push(offset);
+            }
+            if next == 0 {
+                return Ok(Some(res));
+            }
+            node = tree.read(next)?;
+        }
+    }
+
+    /// Finds the offsets of the live records in an index of a table, unless it is missing or not
+    /// up to date with `generation`. The entries are gone through in order from the first one
+    /// holding `start` or a key after it, for as long as `take` gives `Some` for their key, and
+    /// the records of those it gives `Some(true)` are kept. Only the pages on the way to the
+    /// first entry and the leaves holding the others are read.
+    pub fn scan(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        index_name: &str,
+        generation: u64,
+        start: &Key,
+        take: impl FnMut(&Key) -> Option<bool>,
+    ) -> Result<Option<Vec<u64>>, io::Error> {
+        match Tree::current(engine, db_name, table_name, index_name, generation)? {
+            Some(tree) => tree.scan(start, take).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the index file, up to date with `generation`. Leaves are filled up in order, then
+    /// every level of branches above them, up to the root.
+    pub fn save(
+        &self,
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        index_name: &str,
+        generation: u64,
+    ) -> Result<(), io::Error> {
+        let entries: Vec<Entry> = self
+            .entries
+            .iter()
+            .flat_map(|(key, offsets)| offsets.iter().map(|offset| (key.clone(), *offset)))
+            .collect();
+        let longest = entries
+            .iter()
+            .map(|entry| branch_line(entry, 0).len())
+            .max()
+            .unwrap_or(0);
+        let mut tree = Tree {
+            engine,
+            path: index::path(engine, db_name, table_name, index_name),
+            table_name,
+            index_name,
+            generation,
+            size: page_size(longest),
+            root: 1,
+            pages: 1,
+            changed: BTreeMap::new(),
+        };
+
+        // Each level is packed into as few pages as it fits in, numbered in order
+        let mut level: Vec<Node> = Vec::new();
+        let mut leaf = Node::Leaf(Vec::new(), 0);
+        for entry in entries {
+            if let Node::Leaf(held, _) = &mut leaf {
+                held.push(entry);
+            }
+            if leaf.serialize().len() >= tree.size {
+                let Node::Leaf(mut held, _) = leaf else {
+                    unreachable!()
+                };
+                let entry = held.pop().unwrap();
+                level.push(Node::Leaf(held, 0));
+                leaf = Node::Leaf(vec![entry], 0);
+            }
+        }
+        level.push(leaf);
+        let leaves = level.len() as u64;
+        for (i, leaf) in level.iter_mut().enumerate() {
+            if let Node::Leaf(_, next) = leaf {
+                *next = if i as u64 + 1 < leaves {
+                    i as u64 + 2
+                } else {
+                    0
+                };
+            }
+        }
+
+        loop {
+            let first = tree.pages;
+            let count = level.len();
+            let mut parent = Node::Branch(Vec::new());
+            let mut parents = Vec::new();
+            for (i, node) in level.into_iter().enumerate() {
+                let child = (node.first().unwrap_or_default(), first + i as u64);
+                tree.changed.insert(first + i as u64, node);
+                if let Node::Branch(children) = &mut parent {
+                    children.push(child);
+                }
+                if parent.serialize().len() >= tree.size {
+                    let Node::Branch(mut children) = parent else {
+                        unreachable!()
+                    };
+                    let child = children.pop().unwrap();
+                    parents.push(Node::Branch(children));
+                    parent = Node::Branch(vec![child]);
+                }
+            }
+            tree.pages += count as u64;
+            if count == 1 {
+                tree.root = first;
+                break;
+            }
+            parents.push(parent);
+            level = parents;
+        }
+
+        let mut res = tree.page(tree.header());
+        for node in tree.changed.values() {
+            res.extend(tree.page(node.serialize()));
+        }
+        engine.storage().write(&tree.path, &res)
+    }
+
+    /// Puts records added to the table in their leaf or, with `removed`, takes records removed
+    /// from it out. New pages are appended to the file first, then the pages changed and the
+    /// header are patched at once, so an index cut short is never taken as up to date.
+    pub(super) fn update(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        table: &TableFile,
+        index: &Index,
+        removed: bool,
+        changes: &[(u64, Key)],
+    ) -> Result<(), io::Error> {
+        let index_name = index.name.to_string();
+        let Some(mut tree) = Tree::open(engine, db_name, table_name, &index_name)? else {
+            return Ok(());
+        };
+        let longest = changes
+            .iter()
+            .map(|(offset, key)| branch_line(&(key.clone(), *offset), 0).len())
+            .max()
+            .unwrap_or(0);
+        if page_size(longest) > tree.size {
+            return index::rebuild_from_file(engine, db_name, table_name, table, index);
+        }
+
+        let pages = tree.pages;
+        for (offset, key) in changes {
+            let entry = (key.clone(), *offset);
+            if removed {
+                tree.remove(&entry)?;
+            } else {
+                tree.insert(entry)?;
+            }
+        }
+        tree.generation = table.generation;
+
+        let mut added = Vec::new();
+        for node in tree.changed.range(pages..).map(|(_, node)| node) {
+            added.extend(tree.page(node.serialize()));
+        }
+        engine.storage().append(&tree.path, &added)?;
+
+        let mut patches: Vec<(u64, Vec<u8>)> = vec![(0, tree.header().into_bytes())];
+        for (page, node) in tree.changed.range(..pages) {
+            patches.push((*page * tree.size as u64, tree.page(node.serialize())));
+        }
+        let patches: Vec<(u64, &[u8])> = patches
+            .iter()
+            .map(|(offset, contents)| (*offset, contents.as_slice()))
+            .collect();
+        engine.storage().patch(&tree.path, &patches)
+    }
+
+    /// The offsets of the live records holding `key`.
+    pub fn get(&self, key: &Key) -> &[u64] {
+        self.entries.get(key).map_or(&[], |offsets| offsets)
+    }
+}
@@ -0,0 +1,261 @@
+use crate::engine::hash;
+use crate::engine::index::{self, Key};
+use crate::engine::storage;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::Engine;
+use crate::parser::query::Index;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+
+/// Every number in a hash index file is written with this many digits, so it can be patched in
+/// place and found at a known offset.
+const WIDTH: usize = 20;
+
+/// Offset of the number of buckets, on the second line of the file after the generation.
+const BUCKETS: u64 = WIDTH as u64 + 1;
+
+/// Offset of the number of entries, following the number of buckets.
+const ENTRIES: u64 = BUCKETS + WIDTH as u64 + 1;
+
+/// The length of the first two lines of the file, which the buckets follow.
+const HEADER: u64 = ENTRIES + WIDTH as u64 + 1;
+
+/// The length of the line of a bucket.
+const SLOT: u64 = WIDTH as u64 + 1;
+
+/// Indexes are built with at least this many buckets.
+const MIN_BUCKETS: usize = 16;
+
+/// An index for looking records up by their exact key, selected with `HASH`.
+///
+/// Its file starts with the generation of the table, then the number of buckets and of
+/// entries. The buckets follow, one line each holding the offset of the last entry written to
+/// it, or 0 when it has none. Then come the entries, each following the offset of the entry
+/// written to the same bucket before it. A key is looked up by reading the bucket it hashes to
+/// and following the entries from there, without reading the rest of the file. Entries are
+/// appended for records added or removed later, and the index is built again, with as many
+/// buckets as its keys then need, once it holds twice as many entries as buckets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HashIndex {
+    pub entries: HashMap<Key, Vec<u64>>,
+}
+
+fn number(value: u64) -> String {
+    format!("{:0width$}", value, width = WIDTH)
+}
+
+/// The bucket `key` goes in, out of `buckets`.
+fn bucket(key: &Key, buckets: u64) -> u64 {
+    hash::fnv(table_file::serialize_values(key).as_bytes()) % buckets
+}
+
+fn slot(bucket: u64) -> u64 {
+    HEADER + bucket * SLOT
+}
+
+/// Reads the number at `offset` of an index file.
+fn read_number(engine: &Engine, path: &Path, offset: u64) -> Result<Option<u64>, io::Error> {
+    Ok(
+        String::from_utf8_lossy(&engine.storage().read_at(path, offset, WIDTH)?)
+            .parse()
+            .ok(),
+    )
+}
+
+impl HashIndex {
+    /// Indexes the live records of a table, stored at `offsets` in its table file.
+    pub fn build(
+        table: &TableFile,
+        index: &Index,
+        offsets: &[u64],
+    ) -> Result<HashIndex, io::Error> {
+        Ok(HashIndex {
+            entries: index::entries(table, index, offsets)?.into_iter().collect(),
+        })
+    }
+
+    /// Loads a whole index of a table, unless it is missing or not up to date with
+    /// `generation`, the generation of the table as it is read.
+    pub fn load(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        index_name: &str,
+        generation: u64,
+    ) -> Result<Option<HashIndex>, io::Error> {
+        let malformed = || index::malformed(table_name, index_name);
+        index::read(
+            engine,
+            db_name,
+            table_name,
+            index_name,
+            generation,
+            |mut lines| {
+                let buckets = lines
+                    .next()
+                    .and_then(|line| line.split(' ').next()?.parse::<usize>().ok())
+                    .ok_or_else(malformed)?;
+                let entries = lines
+                    .skip(buckets)
+                    .map(|line| line.split_once(' ').map(|(_, entry)| entry))
+                    .collect::<Option<Vec<&str>>>()
+                    .ok_or_else(malformed)?;
+                Ok(HashIndex {
+                    entries: index::gather(table_name, index_name, entries.into_iter())?
+                        .into_iter()
+                        .collect(),
+                })
+            },
+        )
+    }
+
+    /// Looks the offsets of the live records holding `key` up in an index of a table, unless
+    /// it is missing or not up to date with `generation`. Only the bucket of the key and its
+    /// entries are read.
+    pub fn lookup(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        index_name: &str,
+        generation: u64,
+        key: &Key,
+    ) -> Result<Option<Vec<u64>>, io::Error> {
+        let path = index::path(engine, db_name, table_name, index_name);
+        if !engine.storage().exists(&path) || read_number(engine, &path, 0)? != Some(generation) {
+            return Ok(None);
+        }
+
+        let malformed = || index::malformed(table_name, index_name);
+        let buckets = read_number(engine, &path, BUCKETS)?
+            .filter(|buckets| *buckets > 0)
+            .ok_or_else(malformed)?;
+        let mut offset =
+            read_number(engine, &path, slot(bucket(key, buckets)))?.ok_or_else(malformed)?;
+        let (mut added, mut removed) = (Vec::new(), Vec::new());
+        while offset != 0 {
+            let line =
+                storage::read_line(engine.storage(), &path, offset)?.ok_or_else(malformed)?;
+            let (previous, entry) = line.split_once(' ').ok_or_else(malformed)?;
+            let (removes, offsets, found) = index::parse_entry(entry).ok_or_else(malformed)?;
+            if found == *key {
+                if removes {
+                    removed.extend(offsets);
+                } else {
+                    added.extend(offsets);
+                }
+            }
+            offset = previous.parse().map_err(|_| malformed())?;
+        }
+
+        added.retain(|offset| !removed.contains(offset));
+        added.sort();
+        Ok(Some(added))
+    }
+
+    /// Writes the index file, up to date with `generation`.
+    pub fn save(
+        &self,
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        index_name: &str,
+        generation: u64,
+    ) -> Result<(), io::Error> {
+        let buckets = (self.entries.len() * 2)
+            .next_power_of_two()
+            .max(MIN_BUCKETS);
+        let start = slot(buckets as u64);
+        let mut keys: Vec<&Key> = self.entries.keys().collect();
+        keys.sort();
+
+        let mut heads = vec![0; buckets];
+        let mut entries = String::new();
+        for key in keys {
+            let bucket = bucket(key, buckets as u64) as usize;
+            let offset = start + entries.len() as u64;
+            entries.push_str(&number(heads[bucket]));
+            entries.push(' ');
+            entries.push_str(&index::entry(false, &self.entries[key], key));
+            heads[bucket] = offset;
+        }
+
+        let mut res = format!(
+            "{}\n{} {}\n",
+            table_file::generation(generation),
+            number(buckets as u64),
+            number(self.entries.len() as u64)
+        );
+        for head in heads {
+            res.push_str(&number(head));
+            res.push('\n');
+        }
+        res.push_str(&entries);
+        engine.storage().write(
+            &index::path(engine, db_name, table_name, index_name),
+            res.as_bytes(),
+        )
+    }
+
+    /// Adds entries for records added to or, with `removed`, removed from the table to its
+    /// index file, each chained to the last entry of its bucket. The buckets, the number of
+    /// entries and the generation are then patched at once.
+    pub(super) fn update(
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        table: &TableFile,
+        index: &Index,
+        removed: bool,
+        changes: &[(u64, Key)],
+    ) -> Result<(), io::Error> {
+        let index_name = index.name.to_string();
+        let path = index::path(engine, db_name, table_name, &index_name);
+        let malformed = || index::malformed(table_name, &index_name);
+        let buckets = read_number(engine, &path, BUCKETS)?
+            .filter(|buckets| *buckets > 0)
+            .ok_or_else(malformed)?;
+        let count =
+            read_number(engine, &path, ENTRIES)?.ok_or_else(malformed)? + changes.len() as u64;
+        if count > 2 * buckets {
+            return index::rebuild_from_file(engine, db_name, table_name, table, index);
+        }
+
+        let start = engine.storage().size(&path)?;
+        let mut heads: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut entries = String::new();
+        for (offset, key) in changes {
+            let bucket = bucket(key, buckets);
+            let previous = match heads.get(&bucket) {
+                Some(previous) => *previous,
+                None => read_number(engine, &path, slot(bucket))?.ok_or_else(malformed)?,
+            };
+            heads.insert(bucket, start + entries.len() as u64);
+            entries.push_str(&number(previous));
+            entries.push(' ');
+            entries.push_str(&index::entry(removed, &[*offset], key));
+        }
+        engine.storage().append(&path, entries.as_bytes())?;
+
+        // The generation is patched along with the buckets, so an index cut short is never
+        // taken as up to date
+        let heads: Vec<(u64, String)> = heads
+            .into_iter()
+            .map(|(bucket, head)| (slot(bucket), number(head)))
+            .chain([
+                (ENTRIES, number(count)),
+                (0, table_file::generation(table.generation)),
+            ])
+            .collect();
+        let patches: Vec<(u64, &[u8])> = heads
+            .iter()
+            .map(|(offset, contents)| (*offset, contents.as_bytes()))
+            .collect();
+        engine.storage().patch(&path, &patches)
+    }
+
+    /// The offsets of the live records holding `key`.
+    pub fn get(&self, key: &Key) -> &[u64] {
+        self.entries.get(key).map_or(&[], |offsets| offsets)
+    }
+}
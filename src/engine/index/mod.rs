@@ -0,0 +1,331 @@
+use crate::engine::table_file::{self, Record, TableFile};
+use crate::engine::Engine;
+use crate::parser::query::{Identifier, Index, IndexKind};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+pub mod btree;
+pub mod hash;
+
+pub use btree::BTreeIndex;
+pub use hash::HashIndex;
+
+/// The values of the indexed fields of a record, in the order the index lists its fields.
+pub type Key = Vec<Option<Identifier>>;
+
+/// Where an index is stored: a `<table>.<index>.idx` file next to the table file, so it is
+/// snapshotted, renamed and dropped along with the table.
+///
+/// The file starts with the generation of the table the index is up to date with, see
+/// [`TableFile`]. Entries of the index hold the offsets of records in the table file, then their
+/// key, and either add the records to the index or, when they start with `-`, remove them.
+/// Writes that only append or delete records add entries to the index, while writes rewriting
+/// the table build the index again. An index whose generation is not the one of its table
+/// missed a change to it, and is not used.
+pub fn path(engine: &Engine, db_name: &str, table_name: &str, index_name: &str) -> PathBuf {
+    engine
+        .path(db_name)
+        .join(format!("{}.{}.idx", table_name, index_name))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// The positions in the table of the fields an index is on.
+pub fn columns(table: &TableFile, index: &Index) -> Result<Vec<usize>, io::Error> {
+    index
+        .fields
+        .iter()
+        .map(|field| {
+            table.field_index(&field.to_string()).ok_or(invalid(format!(
+                "Field {} of index {} not found",
+                field, index.name
+            )))
+        })
+        .collect()
+}
+
+fn key(columns: &[usize], record: &Record) -> Key {
+    columns
+        .iter()
+        .map(|column| record.values[*column].clone())
+        .collect()
+}
+
+/// Checks an index can be added to a table: its name is free, and it lists existing fields,
+/// each of them once.
+pub fn check(table: &TableFile, table_name: &str, index: &Index) -> Result<(), io::Error> {
+    if table.index(&index.name.to_string()).is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Index {} already exists on {}", index.name, table_name),
+        ));
+    }
+
+    for (i, field) in index.fields.iter().enumerate() {
+        if table.field_index(&field.to_string()).is_none() {
+            return Err(invalid(format!(
+                "Field {} not found on {}",
+                field, table_name
+            )));
+        }
+        if index.fields[..i].contains(field) {
+            return Err(invalid(format!(
+                "Field {} is listed twice in index {}",
+                field, index.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn malformed(table_name: &str, index_name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Malformed entry in index {} of {}", index_name, table_name),
+    )
+}
+
+/// Reads an entry of an index file: whether it removes records, their offsets and their key.
+fn parse_entry(line: &str) -> Option<(bool, Vec<u64>, Key)> {
+    let (removed, line) = match line.strip_prefix('-') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let (offsets, key) = line.split_once(';')?;
+    let offsets = offsets
+        .split(' ')
+        .map(|offset| offset.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((removed, offsets, table_file::parse_values(key)))
+}
+
+/// Goes through the entries of an index file in the order they were written, and gathers the
+/// offsets of the records holding each key.
+fn gather<'a>(
+    table_name: &str,
+    index_name: &str,
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<BTreeMap<Key, Vec<u64>>, io::Error> {
+    let mut res: BTreeMap<Key, Vec<u64>> = BTreeMap::new();
+    for line in lines.filter(|line| !line.is_empty()) {
+        let (removed, offsets, key) =
+            parse_entry(line).ok_or_else(|| malformed(table_name, index_name))?;
+        if !removed {
+            res.entry(key).or_default().extend(offsets);
+        } else if let Some(existing) = res.get_mut(&key) {
+            existing.retain(|offset| !offsets.contains(offset));
+            if existing.is_empty() {
+                res.remove(&key);
+            }
+        }
+    }
+    for offsets in res.values_mut() {
+        offsets.sort();
+    }
+    Ok(res)
+}
+
+/// Gathers the offsets of the live records of a table holding each key of an index, `offsets`
+/// being the offsets of all of its records in its table file.
+fn entries(
+    table: &TableFile,
+    index: &Index,
+    offsets: &[u64],
+) -> Result<BTreeMap<Key, Vec<u64>>, io::Error> {
+    let columns = columns(table, index)?;
+    let mut res: BTreeMap<Key, Vec<u64>> = BTreeMap::new();
+    for (record, offset) in table.records.iter().zip(offsets) {
+        if !record.deleted {
+            res.entry(key(&columns, record)).or_default().push(*offset);
+        }
+    }
+    Ok(res)
+}
+
+/// Reads a whole index file, unless it is missing or not up to date with `generation`. The
+/// lines following its generation are given to `entries`, which finds the entries among them.
+fn read<T>(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    index_name: &str,
+    generation: u64,
+    entries: impl FnOnce(std::str::Lines) -> Result<T, io::Error>,
+) -> Result<Option<T>, io::Error> {
+    let path = path(engine, db_name, table_name, index_name);
+    if !engine.storage().exists(&path) {
+        return Ok(None);
+    }
+
+    let stored = engine.storage().read_to_string(&path)?;
+    let mut lines = stored.lines();
+    if lines.next() != Some(table_file::generation(generation).as_str()) {
+        return Ok(None);
+    }
+    entries(lines).map(Some)
+}
+
+/// An entry of an index file, removing the records at `offsets` when `removed` is set and
+/// adding them otherwise.
+fn entry(removed: bool, offsets: &[u64], key: &Key) -> String {
+    format!(
+        "{}{};{}\n",
+        if removed { "-" } else { "" },
+        offsets
+            .iter()
+            .map(|offset| offset.to_string())
+            .collect::<Vec<String>>()
+            .join(" "),
+        table_file::serialize_values(key)
+    )
+}
+
+/// Builds an index of a table again from its records, `offsets` being their offsets in its table
+/// file.
+fn rebuild(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    index: &Index,
+    offsets: &[u64],
+) -> Result<(), io::Error> {
+    let index_name = index.name.to_string();
+    match index.kind {
+        IndexKind::BTree => BTreeIndex::build(table, index, offsets)?.save(
+            engine,
+            db_name,
+            table_name,
+            &index_name,
+            table.generation,
+        ),
+        IndexKind::Hash => HashIndex::build(table, index, offsets)?.save(
+            engine,
+            db_name,
+            table_name,
+            &index_name,
+            table.generation,
+        ),
+    }
+}
+
+/// Builds every index of a table again from its records, once they have been written to its
+/// table file at `offsets`.
+pub fn refresh(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    offsets: &[u64],
+) -> Result<(), io::Error> {
+    for index in &table.indexes {
+        rebuild(engine, db_name, table_name, table, index, offsets)?;
+    }
+    Ok(())
+}
+
+/// Builds an index of a table again from its table file as it now stands, for indexes that
+/// outgrew the layout they were built with.
+fn rebuild_from_file(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    index: &Index,
+) -> Result<(), io::Error> {
+    let contents = engine
+        .storage()
+        .read(&table_file::path(engine, db_name, table_name))?;
+    rebuild(
+        engine,
+        db_name,
+        table_name,
+        table,
+        index,
+        &table_file::record_offsets(&contents),
+    )
+}
+
+/// Adds entries for some records to the file of an index, removing them when `removed` is set,
+/// and moves the index on to the generation of the table, which a single write just bumped.
+/// Records are given by their position in the table and their offset in its file. An index that
+/// was not up to date with the generation before that write is left as is, so it stays
+/// recognizably out of date.
+fn update(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    index: &Index,
+    removed: bool,
+    records: &[(usize, u64)],
+) -> Result<(), io::Error> {
+    let path = path(engine, db_name, table_name, &index.name.to_string());
+    if !engine.storage().exists(&path) {
+        return Ok(());
+    }
+    let previous = table_file::generation(table.generation - 1);
+    if engine.storage().read_at(&path, 0, previous.len())? != previous.as_bytes() {
+        return Ok(());
+    }
+
+    let columns = columns(table, index)?;
+    let changes: Vec<(u64, Key)> = records
+        .iter()
+        .map(|(position, offset)| (*offset, key(&columns, &table.records[*position])))
+        .collect();
+    match index.kind {
+        IndexKind::BTree => {
+            BTreeIndex::update(engine, db_name, table_name, table, index, removed, &changes)
+        }
+        IndexKind::Hash => {
+            HashIndex::update(engine, db_name, table_name, table, index, removed, &changes)
+        }
+    }
+}
+
+/// Adds the last record of a table to its indexes, the record having just been appended to its
+/// table file at `offset`.
+pub fn append(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    offset: u64,
+) -> Result<(), io::Error> {
+    for index in &table.indexes {
+        update(
+            engine,
+            db_name,
+            table_name,
+            table,
+            index,
+            false,
+            &[(table.records.len() - 1, offset)],
+        )?;
+    }
+    Ok(())
+}
+
+/// Removes the records at `positions` from the indexes of a table, the records having just
+/// been marked as deleted in its table file, whose records start at `offsets`.
+pub fn remove(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    positions: &[usize],
+    offsets: &[u64],
+) -> Result<(), io::Error> {
+    let records: Vec<(usize, u64)> = positions
+        .iter()
+        .map(|position| (*position, offsets[*position]))
+        .collect();
+    for index in &table.indexes {
+        update(engine, db_name, table_name, table, index, true, &records)?;
+    }
+    Ok(())
+}
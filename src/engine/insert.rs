@@ -0,0 +1,159 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::constraints;
+use crate::engine::table_file::{self, Record, TableFile};
+use crate::engine::{db_name, table_name, Engine};
+use crate::parser::query::{ConstraintType, Expression, Identifier};
+use crate::parser::Parser;
+use std::io;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// The value a field of `table` takes when a record leaves it out: its `DEFAULT` if it has one,
+/// or the next number of the counter of `INC` fields.
+pub fn generated_value(table: &TableFile, index: usize) -> Option<Identifier> {
+    let (_, _, field) = &table.fields[index];
+    if let Some(constraint) = table.constraint(field, ConstraintType::Default) {
+        match constraint.options.first() {
+            Some(Expression::Identifier(default)) => Some(default.clone()),
+            _ => None,
+        }
+    } else if table.has_constraint(field, ConstraintType::Inc) {
+        let next = i32::try_from(table.counter(index)).unwrap_or(i32::MAX);
+        Some(Identifier::IntLiteral(next))
+    } else {
+        None
+    }
+}
+
+/// Fills in the `DEFAULT` and `INC` values of a record about to be inserted into `table`,
+/// then checks it against the table's constraints.
+pub fn complete_record(
+    table: &TableFile,
+    values: &mut [Option<Identifier>],
+) -> Result<(), io::Error> {
+    for (i, value) in values.iter_mut().enumerate() {
+        if value.is_none() {
+            *value = generated_value(table, i);
+        }
+    }
+
+    constraints::check_record(table, values, None)
+}
+
+/// Checks that every table `table` refers to through an `FKEY` exists and has a `PKEY` to be
+/// referred to. A table may refer to itself.
+pub fn check_references(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+) -> Result<(), io::Error> {
+    for (_, referenced) in table.references() {
+        let other = if referenced == table_name {
+            table.clone()
+        } else if table_file::exists(engine, db_name, &referenced) {
+            TableFile::load(engine, db_name, &referenced)?
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Table {} not found on {}", referenced, db_name),
+            ));
+        };
+        if other.pkey().is_none() {
+            return Err(invalid(format!(
+                "Table {} has no PKEY to be referred to",
+                referenced
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that every value of an `FKEY` field of `table` is the `PKEY` of a live record of the
+/// table it refers to. The items of an array are checked one by one.
+pub fn check_foreign_keys(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    values: &[Option<Identifier>],
+) -> Result<(), io::Error> {
+    for (field, referenced) in table.references() {
+        let keys = match &values[field] {
+            Some(Identifier::Array(items)) => items.clone(),
+            Some(value) => vec![value.clone()],
+            None => continue,
+        };
+
+        let other = if referenced == table_name {
+            table.clone()
+        } else {
+            TableFile::load(engine, db_name, &referenced)?
+        };
+        let Some(pkey) = other.pkey() else {
+            return Err(invalid(format!(
+                "Table {} has no PKEY to be referred to",
+                referenced
+            )));
+        };
+        for key in keys {
+            if !other
+                .live_records()
+                .any(|record| record.values[pkey].as_ref() == Some(&key))
+            {
+                return Err(invalid(format!(
+                    "No record of {} has the key {}",
+                    referenced, key
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn insert(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    let catalog = Catalog::load(engine, db_name)?;
+    let schema = catalog.table(db_name, table_name)?;
+
+    let query = &parser.query_data;
+    if query.inserted_field.len() != query.inserted_value.len() {
+        return Err(invalid(format!(
+            "Expected {} values, found {}",
+            query.inserted_field.len(),
+            query.inserted_value.len()
+        )));
+    }
+
+    let mut values: Vec<Option<Identifier>> = vec![None; schema.fields.len()];
+    for (field, value) in query.inserted_field.iter().zip(&query.inserted_value) {
+        let index = match field {
+            Identifier::Field(name) => schema.field_index(name),
+            _ => None,
+        }
+        .ok_or(invalid(format!(
+            "Field {} not found on {}",
+            field, table_name
+        )))?;
+
+        values[index] = Some(value.clone());
+    }
+
+    let mut table = TableFile::load(engine, db_name, table_name)?;
+    complete_record(&table, &mut values)?;
+    check_foreign_keys(engine, db_name, table_name, &table, &values)?;
+
+    table.insert(
+        engine,
+        db_name,
+        table_name,
+        Record {
+            deleted: false,
+            values,
+        },
+    )
+}
@@ -0,0 +1,351 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::storage::{Lock, LockKind};
+use crate::engine::{db_name, table_name, Engine};
+use crate::parser::{query::QueryType, Parser};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait between two attempts at taking a lock held elsewhere.
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The file locked by every statement using a database.
+pub fn database_path(engine: &Engine, db_name: &str) -> PathBuf {
+    engine.path(db_name).join(".lock")
+}
+
+/// The file locked by statements reading or writing the records of a table. It is hidden, so
+/// it is left out of snapshots and stays in place when the table is renamed.
+pub fn table_path(engine: &Engine, db_name: &str, table_name: &str) -> PathBuf {
+    engine.path(db_name).join(format!(".{}.lock", table_name))
+}
+
+/// The file locked by statements writing to the records of a table, for as long as their
+/// write lasts: until the statement is done, or until its transaction ends. Reads never take
+/// it, so a transaction keeping it does not hold them up.
+pub fn writer_path(engine: &Engine, db_name: &str, table_name: &str) -> PathBuf {
+    engine
+        .path(db_name)
+        .join(format!(".{}.writer.lock", table_name))
+}
+
+/// The file locked by writes to an analyzed table, which update its statistics in the catalog,
+/// see [`Catalog`]. Transactions keep it until they end, since they write the catalog whole.
+pub fn catalog_path(engine: &Engine, db_name: &str) -> PathBuf {
+    engine.path(db_name).join(".catalog.lock")
+}
+
+fn locked(db_name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::WouldBlock,
+        format!("Database {} is locked", db_name),
+    )
+}
+
+/// The locks held by the statements of an engine and of its clones. Threads sharing an engine
+/// wait on these, since the locks of a storage only keep out other engines, and memory storage
+/// has none at all.
+#[derive(Debug, Default)]
+pub struct Locks {
+    held: Mutex<HashMap<PathBuf, Held>>,
+    released: Condvar,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Held {
+    Shared(usize),
+    Exclusive,
+}
+
+/// A lock in [`Locks`], given back when dropped.
+struct LocalLock {
+    locks: Arc<Locks>,
+    path: PathBuf,
+}
+
+impl Locks {
+    /// Takes a lock, waiting until `deadline` while another thread holds it.
+    fn take(
+        locks: &Arc<Locks>,
+        path: &Path,
+        kind: LockKind,
+        deadline: Instant,
+    ) -> Option<LocalLock> {
+        let mut held = locks.held.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            match (held.get(path).copied(), kind) {
+                (None, LockKind::Shared) => {
+                    held.insert(path.to_path_buf(), Held::Shared(1));
+                }
+                (Some(Held::Shared(count)), LockKind::Shared) => {
+                    held.insert(path.to_path_buf(), Held::Shared(count + 1));
+                }
+                (None, LockKind::Exclusive) => {
+                    held.insert(path.to_path_buf(), Held::Exclusive);
+                }
+                _ => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    held = locks
+                        .released
+                        .wait_timeout(held, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                    continue;
+                }
+            }
+            return Some(LocalLock {
+                locks: Arc::clone(locks),
+                path: path.to_path_buf(),
+            });
+        }
+    }
+}
+
+impl Drop for LocalLock {
+    fn drop(&mut self) {
+        let mut held = self
+            .locks
+            .held
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match held.get(&self.path).copied() {
+            Some(Held::Shared(count)) if count > 1 => {
+                held.insert(self.path.clone(), Held::Shared(count - 1));
+            }
+            _ => {
+                held.remove(&self.path);
+            }
+        }
+        self.locks.released.notify_all();
+    }
+}
+
+/// Takes a lock, waiting until `deadline` while it is held elsewhere: by other threads sharing
+/// the engine first, then by other engines.
+fn take(
+    engine: &Engine,
+    path: &Path,
+    kind: LockKind,
+    deadline: Instant,
+) -> Result<Option<Lock>, io::Error> {
+    let Some(local) = Locks::take(&engine.locks, path, kind, deadline) else {
+        return Ok(None);
+    };
+    loop {
+        if let Some(lock) = engine.storage().try_lock(path, kind)? {
+            return Ok(Some(Lock::new((lock, local))));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(RETRY_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Takes a lock, waiting up to the lock timeout of the engine while it is held elsewhere.
+pub fn acquire(
+    engine: &Engine,
+    db_name: &str,
+    path: &Path,
+    kind: LockKind,
+) -> Result<Lock, io::Error> {
+    take(engine, path, kind, Instant::now() + engine.lock_timeout())?.ok_or_else(|| locked(db_name))
+}
+
+/// Takes a lock only if nobody holds it at the moment.
+pub fn try_acquire(
+    engine: &Engine,
+    path: &Path,
+    kind: LockKind,
+) -> Result<Option<Lock>, io::Error> {
+    take(engine, path, kind, Instant::now())
+}
+
+/// The locks an open transaction keeps from its first write until it ends: its database,
+/// shared so the tables it writes to are not changed as a whole under it, and the writer lock
+/// of every table it wrote to, so no other write to those tables is lost when it commits. Once
+/// it writes to an analyzed table, it keeps the catalog lock as well.
+#[derive(Debug, Default)]
+pub struct TransactionLocks {
+    database: Option<Lock>,
+    tables: BTreeMap<String, Lock>,
+    catalog: Option<Lock>,
+}
+
+impl TransactionLocks {
+    /// The tables the transaction wrote to.
+    pub fn tables(&self) -> impl Iterator<Item = &String> {
+        self.tables.keys()
+    }
+}
+
+/// The database a statement uses, if it uses an existing one.
+pub fn database(engine: &Engine, parser: &Parser) -> Option<String> {
+    let db_name = match parser.query_data._type {
+        QueryType::None
+        | QueryType::DatabaseCreation
+        | QueryType::ShowDatabases
+        | QueryType::Begin
+        | QueryType::Rollback
+        | QueryType::Savepoint
+        | QueryType::RollbackTo
+        | QueryType::Release => return None,
+        QueryType::Commit => engine
+            .transaction_database()?
+            .file_name()?
+            .to_string_lossy()
+            .to_string(),
+        _ => db_name(parser).to_string(),
+    };
+    engine
+        .storage()
+        .is_dir(&engine.path(&db_name))
+        .then_some(db_name)
+}
+
+/// The tables whose records are checked when writing to `table_name`: those it refers to, and
+/// those referring to it.
+fn related(engine: &Engine, db_name: &str, table_name: &str) -> Vec<String> {
+    let Ok(catalog) = Catalog::load(engine, db_name) else {
+        return Vec::new();
+    };
+    let refers_to = |from: &str, to: &str| {
+        catalog
+            .tables
+            .get(from)
+            .is_some_and(|table| table.references().iter().any(|(_, other)| other == to))
+    };
+
+    catalog
+        .tables
+        .keys()
+        .filter(|other| {
+            *other != table_name && (refers_to(table_name, other) || refers_to(other, table_name))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether `table_name` has statistics in the catalog, which writes to it update.
+fn analyzed(engine: &Engine, db_name: &str, table_name: &str) -> bool {
+    Catalog::load(engine, db_name).is_ok_and(|catalog| catalog.statistics.contains_key(table_name))
+}
+
+/// Takes the locks a statement needs, which are released once they are dropped.
+///
+/// Every statement on a database holds its database lock: changes to the records of a table
+/// share it, while changes to the database as a whole, such as to its tables or snapshots,
+/// hold it exclusively. Writes then hold the writer lock of their table, which transactions
+/// keep until they end, then the lock of their table exclusively along with a shared lock on
+/// the tables their constraints are checked against. Writes to an analyzed table then hold the
+/// catalog lock, which transactions also keep until they end. `COMMIT` holds the tables its
+/// transaction wrote to exclusively while it writes them. `READ`s only share the lock of their
+/// table while they pin a version of it, see [`versions`](crate::engine::versions).
+///
+/// Locks are always taken in the same order, the database first, then the writer lock, then
+/// tables by name and then the catalog, so two statements can never each wait on the other.
+/// Two transactions each waiting on a lock the other keeps fail once the lock timeout has
+/// passed.
+pub fn statement_locks(engine: &Engine, parser: &Parser) -> Result<Vec<Lock>, io::Error> {
+    let Some(db_name) = database(engine, parser) else {
+        return Ok(Vec::new());
+    };
+
+    let mut tables: BTreeMap<String, LockKind> = BTreeMap::new();
+    let mut catalog = false;
+    let db_kind = match parser.query_data._type {
+        QueryType::Read
+        | QueryType::Insert
+        | QueryType::Update
+        | QueryType::Delete
+        | QueryType::SnapshotList
+        | QueryType::Diff
+        | QueryType::ShowTables
+        | QueryType::Describe
+        | QueryType::Commit => LockKind::Shared,
+        _ => LockKind::Exclusive,
+    };
+
+    let mut locks = vec![acquire(
+        engine,
+        &db_name,
+        &database_path(engine, &db_name),
+        db_kind,
+    )?];
+
+    match parser.query_data._type {
+        QueryType::Insert | QueryType::Update | QueryType::Delete => {
+            let table_name = table_name(parser);
+            let mut held = engine.transaction_locks();
+            if !held.tables.contains_key(table_name) {
+                let writer = acquire(
+                    engine,
+                    &db_name,
+                    &writer_path(engine, &db_name, table_name),
+                    LockKind::Exclusive,
+                )?;
+                if engine.in_transaction() {
+                    if held.database.is_none() {
+                        held.database = Some(acquire(
+                            engine,
+                            &db_name,
+                            &database_path(engine, &db_name),
+                            LockKind::Shared,
+                        )?);
+                    }
+                    held.tables.insert(table_name.to_string(), writer);
+                } else {
+                    locks.push(writer);
+                }
+            }
+
+            for other in related(engine, &db_name, table_name) {
+                tables.insert(other, LockKind::Shared);
+            }
+            tables.insert(table_name.to_string(), LockKind::Exclusive);
+            catalog = analyzed(engine, &db_name, table_name);
+        }
+        QueryType::Commit => {
+            for table_name in engine.transaction_locks().tables() {
+                tables.insert(table_name.clone(), LockKind::Exclusive);
+            }
+        }
+        _ => {}
+    }
+
+    for (table_name, kind) in tables {
+        locks.push(acquire(
+            engine,
+            &db_name,
+            &table_path(engine, &db_name, &table_name),
+            kind,
+        )?);
+    }
+
+    if catalog {
+        let mut held = engine.transaction_locks();
+        if !engine.in_transaction() {
+            locks.push(acquire(
+                engine,
+                &db_name,
+                &catalog_path(engine, &db_name),
+                LockKind::Exclusive,
+            )?);
+        } else if held.catalog.is_none() {
+            held.catalog = Some(acquire(
+                engine,
+                &db_name,
+                &catalog_path(engine, &db_name),
+                LockKind::Exclusive,
+            )?);
+        }
+    }
+    Ok(locks)
+}
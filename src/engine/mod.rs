@@ -1,12 +1,393 @@
-use super::parser::{query::QueryType, Parser};
+use super::parser::{
+    query::{Identifier, QueryType},
+    Parser,
+};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use storage::{FileStorage, LockKind, MemoryStorage, Storage};
+use wal::{Session, WalStorage};
 
+pub mod alter;
+pub mod catalog;
+pub mod constraints;
 pub mod create;
+pub mod delete;
+pub mod drop;
+pub mod eval;
+pub mod hash;
+pub mod index;
+pub mod insert;
+pub mod lock;
+pub mod plan;
+pub mod read;
+pub mod rename;
+pub mod show;
+pub mod snapshot;
+pub mod statistics;
+pub mod storage;
+pub mod table_file;
+pub mod update;
+pub mod versions;
+pub mod wal;
 
-pub fn engine(parser: &Parser) -> Result<(), std::io::Error> {
-    match parser.query_data._type {
-        QueryType::None => panic!("No query type in query data."),
-        QueryType::DatabaseCreation => create::database::create_database(parser),
-        QueryType::TableCreation => create::table::create_table(parser),
-        _ => Ok(()),
+#[derive(Debug, Default, PartialEq)]
+pub enum Output {
+    #[default]
+    None,
+    //        COLUMNS                 ROWS
+    ResultSet(Vec<String>, Vec<Vec<Option<Identifier>>>),
+    Count(usize),
+}
+
+/// An instance of the engine. Every database named by a query is a directory resolved against
+/// the root the engine was opened on, so engines opened on different roots never see each
+/// other's databases. The directories themselves are kept by the [`Storage`] of the engine,
+/// and every change made to them is written to the log of their database first.
+///
+/// Statements lock the databases and tables they use, so several engines, in this process or
+/// in others, can share the same root. A statement finding its locks held elsewhere waits for
+/// them up to the lock timeout of the engine.
+///
+/// An engine can also be shared by many threads, by reference or by cloning it, since clones
+/// share their storage and locks: `READ`s of a table run side by side, while writes to it run
+/// one at a time, without waiting for those reads. Each clone is a session of its own, with
+/// its own transaction, while threads sharing an engine by reference share its transaction, so
+/// threads running transactions of their own should each use a clone.
+#[derive(Debug)]
+pub struct Engine {
+    root: PathBuf,
+    storage: Session,
+    lock_timeout: Duration,
+    locks: Arc<lock::Locks>,
+    transaction_locks: Mutex<lock::TransactionLocks>,
+    versions: Arc<versions::Versions>,
+}
+
+impl Clone for Engine {
+    fn clone(&self) -> Engine {
+        Engine {
+            root: self.root.clone(),
+            storage: Session::new(self.storage.wal().clone()),
+            lock_timeout: self.lock_timeout,
+            locks: self.locks.clone(),
+            transaction_locks: Mutex::default(),
+            versions: self.versions.clone(),
+        }
+    }
+}
+
+/// How long statements wait for a lock unless [`Engine::with_lock_timeout`] says otherwise.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl Engine {
+    /// Opens an engine keeping its databases in `storage`, creating the root if it does not
+    /// exist yet. Changes left in the log of a database by an engine which stopped midway are
+    /// redone first, unless another engine is using that database at the moment.
+    pub fn new(
+        root: impl AsRef<Path>,
+        storage: impl Storage + 'static,
+    ) -> Result<Engine, io::Error> {
+        let root = root.as_ref();
+        storage.create_dir_all(root)?;
+        let engine = Engine {
+            root: root.to_path_buf(),
+            storage: Session::new(Arc::new(WalStorage::new(root, Box::new(storage)))),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            locks: Arc::default(),
+            transaction_locks: Mutex::default(),
+            versions: Arc::default(),
+        };
+
+        for entry in engine.storage().list(root)? {
+            if !entry.is_dir || !engine.storage().exists(&entry.path.join(wal::WAL)) {
+                continue;
+            }
+            let lock = engine
+                .storage()
+                .try_lock(&entry.path.join(".lock"), LockKind::Exclusive)?;
+            if lock.is_some() {
+                engine.storage.wal().replay(&entry.path)?;
+            }
+        }
+        Ok(engine)
+    }
+
+    /// Sets how long statements wait for locks held by other engines before failing.
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Engine {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
+    pub fn lock_timeout(&self) -> Duration {
+        self.lock_timeout
+    }
+
+    /// Opens an engine on the directory `root` of the filesystem.
+    pub fn open(root: impl AsRef<Path>) -> Result<Engine, io::Error> {
+        Engine::new(root, FileStorage)
+    }
+
+    /// Opens an engine whose databases only live in memory, and are gone once every clone of
+    /// the engine is dropped.
+    pub fn in_memory() -> Engine {
+        Engine::new("/", MemoryStorage::new()).expect("Memory storage cannot fail to create a root")
+    }
+
+    /// The versions of tables pinned by the `READ`s running at the moment.
+    pub fn versions(&self) -> &versions::Versions {
+        &self.versions
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn storage(&self) -> &dyn Storage {
+        &self.storage
+    }
+
+    /// Empties the log of a database, once everything in it is safely applied.
+    pub fn checkpoint(&self, db_name: &str) -> Result<(), io::Error> {
+        self.storage.wal().checkpoint(&self.path(db_name))
+    }
+
+    /// Starts a transaction. Until it is committed, the records it writes are only seen by the
+    /// statements run on this engine, and not by its clones.
+    pub fn begin(&self) -> Result<(), io::Error> {
+        self.storage.begin()
+    }
+
+    /// Makes every write of the open transaction durable at once.
+    pub fn commit(&self) -> Result<(), io::Error> {
+        let res = self.storage.commit();
+        *self.transaction_locks() = lock::TransactionLocks::default();
+        res
+    }
+
+    /// Discards every write of the open transaction.
+    pub fn rollback(&self) -> Result<(), io::Error> {
+        let res = self.storage.rollback();
+        *self.transaction_locks() = lock::TransactionLocks::default();
+        res
+    }
+
+    /// Runs `change` in a transaction of its own, so the changes it makes to the files of a
+    /// database are logged as a single entry, and either all of them are made or none is.
+    pub(crate) fn atomically<T>(
+        &self,
+        change: impl FnOnce() -> Result<T, io::Error>,
+    ) -> Result<T, io::Error> {
+        self.storage.begin()?;
+        match change() {
+            Ok(res) => {
+                self.storage.commit()?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.storage.rollback()?;
+                Err(err)
+            }
+        }
+    }
+
+    /// The locks kept by the open transaction until it ends.
+    pub(crate) fn transaction_locks(&self) -> MutexGuard<'_, lock::TransactionLocks> {
+        self.transaction_locks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sets a savepoint in the open transaction, which it can later be rolled back to.
+    pub fn savepoint(&self, name: &str) -> Result<(), io::Error> {
+        self.storage.savepoint(name)
+    }
+
+    /// Discards the writes made by the open transaction since the savepoint `name`.
+    pub fn rollback_to(&self, name: &str) -> Result<(), io::Error> {
+        self.storage.rollback_to(name)
+    }
+
+    /// Forgets the savepoint `name`, keeping the writes made since.
+    pub fn release(&self, name: &str) -> Result<(), io::Error> {
+        self.storage.release(name)
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.storage.in_transaction()
+    }
+
+    /// The contents the open transaction gives to the file at `path`, if it has written to it.
+    pub(crate) fn staged(&self, path: &Path) -> Option<Vec<u8>> {
+        self.storage.staged(path)
+    }
+
+    /// The directory of the database the open transaction writes to, once it has written.
+    pub fn transaction_database(&self) -> Option<PathBuf> {
+        self.storage.transaction_database()
+    }
+
+    /// Checkpoints the log of a database once it has grown too large, as long as no other
+    /// engine is using the database at the moment.
+    fn trim(&self, db_name: &str) -> Result<(), io::Error> {
+        if !self.storage.wal().needs_checkpoint(&self.path(db_name)) {
+            return Ok(());
+        }
+        let lock = lock::try_acquire(
+            self,
+            &lock::database_path(self, db_name),
+            LockKind::Exclusive,
+        )?;
+        if lock.is_some() {
+            self.checkpoint(db_name)?;
+        }
+        Ok(())
+    }
+
+    /// The directory holding a database.
+    pub fn path(&self, db_name: &str) -> PathBuf {
+        self.root.join(db_name)
+    }
+
+    pub fn execute(&self, parser: &Parser) -> Result<Output, io::Error> {
+        if self.in_transaction() && !transactional(&parser.query_data._type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{:?} statements cannot be run inside a transaction",
+                    parser.query_data._type
+                ),
+            ));
+        }
+
+        let database = lock::database(self, parser);
+        let locks = lock::statement_locks(self, parser)?;
+        let result = self.dispatch(parser);
+        drop(locks);
+
+        if let Some(db_name) = database {
+            self.trim(&db_name)?;
+        }
+        result
+    }
+
+    fn dispatch(&self, parser: &Parser) -> Result<Output, io::Error> {
+        match parser.query_data._type {
+            QueryType::None => panic!("No query type in query data."),
+            QueryType::DatabaseCreation => {
+                create::database::create_database(self, parser).map(|_| Output::None)
+            }
+            QueryType::TableCreation => {
+                create::table::create_table(self, parser).map(|_| Output::None)
+            }
+            QueryType::Insert => insert::insert(self, parser).map(|_| Output::None),
+            QueryType::Read if parser.query_data.explain => read::explain(self, parser),
+            QueryType::Read => read::read(self, parser),
+            QueryType::Delete => delete::delete(self, parser),
+            QueryType::Update => update::update(self, parser),
+            QueryType::DatabaseDrop => {
+                drop::database::drop_database(self, parser).map(|_| Output::None)
+            }
+            QueryType::TableDrop => drop::table::drop_table(self, parser).map(|_| Output::None),
+            QueryType::DatabaseRename => {
+                rename::database::rename_database(self, parser).map(|_| Output::None)
+            }
+            QueryType::TableRename => {
+                rename::table::rename_table(self, parser).map(|_| Output::None)
+            }
+            QueryType::Alter => alter::alter(self, parser).map(|_| Output::None),
+            QueryType::Snapshot => snapshot::snapshot(self, parser).map(|_| Output::None),
+            QueryType::SnapshotList => snapshot::list_snapshots(self, parser),
+            QueryType::Restore => snapshot::restore(self, parser).map(|_| Output::None),
+            QueryType::Diff => snapshot::diff::diff(self, parser),
+            QueryType::ShowDatabases => show::show_databases(self, parser),
+            QueryType::ShowTables => show::show_tables(self, parser),
+            QueryType::Describe => show::describe(self, parser),
+            QueryType::Checkpoint => wal::checkpoint(self, parser).map(|_| Output::None),
+            QueryType::Begin => self.begin().map(|_| Output::None),
+            QueryType::Commit => self.commit().map(|_| Output::None),
+            QueryType::Rollback => self.rollback().map(|_| Output::None),
+            QueryType::Savepoint => self.savepoint(savepoint_name(parser)).map(|_| Output::None),
+            QueryType::RollbackTo => self
+                .rollback_to(savepoint_name(parser))
+                .map(|_| Output::None),
+            QueryType::Release => self.release(savepoint_name(parser)).map(|_| Output::None),
+            QueryType::IndexCreation => {
+                create::index::create_index(self, parser).map(|_| Output::None)
+            }
+            QueryType::IndexDrop => drop::index::drop_index(self, parser).map(|_| Output::None),
+            QueryType::Analyze => statistics::analyze(self, parser).map(|_| Output::None),
+        }
+    }
+}
+
+/// Whether a statement can be run inside a transaction. Transactions only hold changes to the
+/// records of existing tables, so statements changing tables, databases or snapshots are left
+/// out.
+fn transactional(query_type: &QueryType) -> bool {
+    matches!(
+        query_type,
+        QueryType::Insert
+            | QueryType::Read
+            | QueryType::Delete
+            | QueryType::Update
+            | QueryType::SnapshotList
+            | QueryType::Diff
+            | QueryType::ShowDatabases
+            | QueryType::ShowTables
+            | QueryType::Describe
+            | QueryType::Commit
+            | QueryType::Rollback
+            | QueryType::Savepoint
+            | QueryType::RollbackTo
+            | QueryType::Release
+    )
+}
+
+/// Runs a query with an engine opened on the working directory.
+pub fn engine(parser: &Parser) -> Result<Output, io::Error> {
+    Engine::open(".")?.execute(parser)
+}
+
+pub fn db_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(db_name) = &parser.query_data.db_name {
+        return db_name;
+    }
+    panic!("Database name not found, or it was not in a string literal identifier")
+}
+
+pub fn new_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(new_name) = &parser.query_data.new_name {
+        return new_name;
+    }
+    panic!("New name not found, or it was not in a string literal identifier")
+}
+
+pub fn table_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(table_name) = &parser.query_data.table_name {
+        return table_name;
+    }
+    panic!("Table name not found, or it was not in a string literal identifier")
+}
+
+pub fn snapshot_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(snapshot_name) = &parser.query_data.snapshot_name {
+        return snapshot_name;
+    }
+    panic!("Snapshot name not found, or it was not in a string literal identifier")
+}
+
+pub fn index_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(index_name) = &parser.query_data.index_name {
+        return index_name;
+    }
+    panic!("Index name not found, or it was not in a string literal identifier")
+}
+
+pub fn savepoint_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(savepoint_name) = &parser.query_data.savepoint_name {
+        return savepoint_name;
     }
+    panic!("Savepoint name not found, or it was not in a string literal identifier")
 }
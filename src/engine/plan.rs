@@ -0,0 +1,551 @@
+use crate::engine::index::{BTreeIndex, HashIndex, Key};
+use crate::engine::statistics::{FieldStatistics, Statistics};
+use crate::engine::table_file::TableFile;
+use crate::engine::Engine;
+use crate::parser::query::{BinaryOperation, Expression, Identifier, Index, IndexKind, Mode};
+use std::cmp::Reverse;
+use std::fmt;
+use std::io;
+use std::ops::Bound;
+
+/// A comparison of a field against a value, found among the conditions of a query. The field
+/// is always on the left, so `1 < id` is read as `id > 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Predicate {
+    pub field: String,
+    pub operation: BinaryOperation,
+    pub value: Identifier,
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.operation, self.value)
+    }
+}
+
+/// How the records of a table are found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// Every record is read.
+    Scan,
+    /// Records with their primary key within bounds. `FREAD` tables keep their records ordered
+    /// by primary key, so these are found by binary search.
+    Pkey(Bound<Identifier>, Bound<Identifier>),
+    /// Records holding an exact key, looked up in an index.
+    IndexLookup(Index, Key),
+    /// Records whose key in an ordered index starts with a prefix, and whose next field is
+    /// within bounds.
+    IndexRange(Index, Key, Bound<Identifier>, Bound<Identifier>),
+}
+
+impl Access {
+    /// The name of the access method, as shown by `EXPLAIN`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Access::Scan => "SCAN",
+            Access::Pkey(lower, upper) if is_point(lower, upper) => "PKEY LOOKUP",
+            Access::Pkey(..) => "PKEY RANGE",
+            Access::IndexLookup(..) => "INDEX LOOKUP",
+            Access::IndexRange(..) => "INDEX RANGE",
+        }
+    }
+}
+
+/// How a `READ` finds the records matching its conditions: an access method narrowing the
+/// records down by some predicates, after which the whole conditions are checked against every
+/// record it found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub access: Access,
+    /// The predicates the access method narrows the records down by.
+    pub pushed: Vec<Predicate>,
+    /// How many records the access method is expected to find.
+    pub rows: usize,
+}
+
+fn flip(operation: &BinaryOperation) -> BinaryOperation {
+    match operation {
+        BinaryOperation::GreaterThan => BinaryOperation::LesserThan,
+        BinaryOperation::GreaterThanEqualTo => BinaryOperation::LesserThanEqualTo,
+        BinaryOperation::LesserThan => BinaryOperation::GreaterThan,
+        BinaryOperation::LesserThanEqualTo => BinaryOperation::GreaterThanEqualTo,
+        operation => operation.clone(),
+    }
+}
+
+/// Reads an expression as a comparison of a field against a value, if it is one.
+fn predicate(expression: &Expression) -> Option<Predicate> {
+    let Expression::Binary(operation, operands) = expression else {
+        return None;
+    };
+    if !matches!(
+        operation,
+        BinaryOperation::Equals
+            | BinaryOperation::GreaterThan
+            | BinaryOperation::GreaterThanEqualTo
+            | BinaryOperation::LesserThan
+            | BinaryOperation::LesserThanEqualTo
+    ) {
+        return None;
+    }
+
+    let (field, value, operation) = match &**operands {
+        (Expression::Identifier(Identifier::Field(field)), Expression::Identifier(value)) => {
+            (field, value, operation.clone())
+        }
+        (Expression::Identifier(value), Expression::Identifier(Identifier::Field(field))) => {
+            (field, value, flip(operation))
+        }
+        _ => return None,
+    };
+    if let Identifier::Field(_) = value {
+        return None;
+    }
+
+    Some(Predicate {
+        field: field.clone(),
+        operation,
+        value: value.clone(),
+    })
+}
+
+/// The conditions that must all hold for a record to match: the operands of the `AND`s the
+/// conditions are made of.
+fn conjuncts(conditions: &Expression) -> Vec<&Expression> {
+    match conditions {
+        Expression::None => Vec::new(),
+        Expression::Binary(BinaryOperation::And, operands) => {
+            let mut res = conjuncts(&operands.0);
+            res.extend(conjuncts(&operands.1));
+            res
+        }
+        condition => vec![condition],
+    }
+}
+
+/// The predicates every record matching the conditions satisfies: the comparisons of a field
+/// against a value combined by `AND`. Anything under an `OR` or a `NOT` is left out.
+pub fn predicates(conditions: &Expression) -> Vec<Predicate> {
+    predicates_of(&conjuncts(conditions))
+}
+
+fn predicates_of(conditions: &[&Expression]) -> Vec<Predicate> {
+    conditions
+        .iter()
+        .filter_map(|condition| predicate(condition))
+        .collect()
+}
+
+/// The conditions left to check once the access method found records satisfying the `pushed`
+/// predicates.
+pub fn residual(conditions: &Expression, pushed: &[Predicate]) -> Expression {
+    conjuncts(conditions)
+        .into_iter()
+        .filter(|condition| !predicate(condition).is_some_and(|found| pushed.contains(&found)))
+        .rev()
+        .fold(Expression::None, |res, condition| match res {
+            Expression::None => condition.clone(),
+            res => Expression::Binary(BinaryOperation::And, Box::new((condition.clone(), res))),
+        })
+}
+
+fn value(bound: &Bound<Identifier>) -> Option<&Identifier> {
+    match bound {
+        Bound::Included(value) | Bound::Excluded(value) => Some(value),
+        Bound::Unbounded => None,
+    }
+}
+
+/// Whether a value sorts before everything a lower bound lets through.
+fn below(value: &Option<Identifier>, lower: &Bound<Identifier>) -> bool {
+    match (value, lower) {
+        (_, Bound::Unbounded) => false,
+        (None, _) => true,
+        (Some(value), Bound::Included(lower)) => value < lower,
+        (Some(value), Bound::Excluded(lower)) => value <= lower,
+    }
+}
+
+/// Whether a value sorts after everything an upper bound lets through.
+fn above(value: &Option<Identifier>, upper: &Bound<Identifier>) -> bool {
+    match (value, upper) {
+        (_, Bound::Unbounded) => false,
+        (None, _) => false,
+        (Some(value), Bound::Included(upper)) => value > upper,
+        (Some(value), Bound::Excluded(upper)) => value >= upper,
+    }
+}
+
+/// The stricter of two lower bounds.
+fn tighter_lower(a: Bound<Identifier>, b: Bound<Identifier>) -> Bound<Identifier> {
+    match (value(&a), value(&b)) {
+        (None, _) => b,
+        (_, None) => a,
+        (Some(x), Some(y)) if x > y || (x == y && matches!(a, Bound::Excluded(_))) => a,
+        _ => b,
+    }
+}
+
+/// The stricter of two upper bounds.
+fn tighter_upper(a: Bound<Identifier>, b: Bound<Identifier>) -> Bound<Identifier> {
+    match (value(&a), value(&b)) {
+        (None, _) => b,
+        (_, None) => a,
+        (Some(x), Some(y)) if x < y || (x == y && matches!(a, Bound::Excluded(_))) => a,
+        _ => b,
+    }
+}
+
+/// The tightest bounds the predicates on a single field put on it.
+fn bounds(predicates: &[Predicate]) -> (Bound<Identifier>, Bound<Identifier>) {
+    let (mut lower, mut upper) = (Bound::Unbounded, Bound::Unbounded);
+    for predicate in predicates {
+        let value = predicate.value.clone();
+        let (new_lower, new_upper) = match predicate.operation {
+            BinaryOperation::Equals => (Bound::Included(value.clone()), Bound::Included(value)),
+            BinaryOperation::GreaterThan => (Bound::Excluded(value), Bound::Unbounded),
+            BinaryOperation::GreaterThanEqualTo => (Bound::Included(value), Bound::Unbounded),
+            BinaryOperation::LesserThan => (Bound::Unbounded, Bound::Excluded(value)),
+            _ => (Bound::Unbounded, Bound::Included(value)),
+        };
+        lower = tighter_lower(lower, new_lower);
+        upper = tighter_upper(upper, new_upper);
+    }
+    (lower, upper)
+}
+
+/// Whether bounds only let a single value through.
+pub fn is_point(lower: &Bound<Identifier>, upper: &Bound<Identifier>) -> bool {
+    matches!((lower, upper), (Bound::Included(a), Bound::Included(b)) if a == b)
+}
+
+/// The share of records a predicate is expected to let through when nothing is known about the
+/// values of the table: a tenth of them for an equality, and a third of them for a bound.
+fn default_share(predicate: &Predicate) -> f64 {
+    match predicate.operation {
+        BinaryOperation::Equals => 0.1,
+        _ => 1.0 / 3.0,
+    }
+}
+
+/// The share of records whose value of a field is within bounds, according to the statistics
+/// of the field: values are spread evenly among the distinct values for an equality, and
+/// ranges count the buckets of the histogram they cover, half of those they only overlap.
+fn field_share(
+    statistics: &FieldStatistics,
+    rows: usize,
+    lower: &Bound<Identifier>,
+    upper: &Bound<Identifier>,
+) -> f64 {
+    if rows == 0 || below(&statistics.max, lower) || above(&statistics.min, upper) {
+        return 0.0;
+    }
+    let present = rows.saturating_sub(statistics.nulls) as f64 / rows as f64;
+    if is_point(lower, upper) {
+        return present / statistics.distinct.max(1) as f64;
+    }
+    if let (Some(lower), Some(upper)) = (value(lower), value(upper)) {
+        if lower > upper {
+            return 0.0;
+        }
+    }
+
+    let total: usize = statistics.histogram.iter().map(|(_, count)| count).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let (mut lowest, mut within) = (statistics.min.clone(), 0.0);
+    for (highest, count) in &statistics.histogram {
+        let highest = Some(highest.clone());
+        if below(&highest, lower) || above(&lowest, upper) {
+            // The bucket is out of bounds
+        } else if !below(&lowest, lower) && !above(&highest, upper) {
+            within += *count as f64;
+        } else {
+            within += *count as f64 / 2.0;
+        }
+        lowest = highest;
+    }
+    present * within / total as f64
+}
+
+/// The share of records expected to satisfy all of some predicates. Predicates on different
+/// fields are taken to be independent of each other.
+fn share(predicates: &[Predicate], statistics: Option<&Statistics>) -> f64 {
+    let mut fields: Vec<&str> = predicates
+        .iter()
+        .map(|predicate| predicate.field.as_str())
+        .collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .map(|field| {
+            let on: Vec<Predicate> = predicates
+                .iter()
+                .filter(|predicate| predicate.field == field)
+                .cloned()
+                .collect();
+            match statistics
+                .and_then(|statistics| Some((statistics.rows, statistics.fields.get(field)?)))
+            {
+                Some((rows, field)) => {
+                    let (lower, upper) = bounds(&on);
+                    field_share(field, rows, &lower, &upper)
+                }
+                None => on.iter().map(default_share).product(),
+            }
+        })
+        .product()
+}
+
+fn scale(rows: usize, share: f64) -> usize {
+    ((rows as f64 * share).ceil() as usize).min(rows)
+}
+
+/// How many of `rows` records are expected to satisfy some conditions. Conditions other than
+/// predicates are taken to hold for half of the records.
+pub fn estimate(rows: usize, conditions: &Expression, statistics: Option<&Statistics>) -> usize {
+    let conditions = conjuncts(conditions);
+    let others = conditions
+        .iter()
+        .filter(|condition| predicate(condition).is_none())
+        .count();
+    scale(
+        rows,
+        share(&predicates_of(&conditions), statistics) * 0.5_f64.powi(others as i32),
+    )
+}
+
+/// The cost of going over a key, such as an entry of an index file or a primary key compared
+/// during a binary search, relative to checking the conditions against a record.
+const KEY_COST: f64 = 0.25;
+
+/// The cost of reading a record along with the rest of its table file and parsing it, relative
+/// to checking the conditions against a record.
+const LOAD_COST: f64 = 1.0;
+
+/// The cost of reading a single record from its offset in the table file and parsing it,
+/// relative to checking the conditions against a record.
+const FETCH_COST: f64 = 2.0;
+
+/// How much work an access method is expected to take for a table of `count` records.
+///
+/// Scans and primary key lookups read the whole table file, then check every record or only
+/// those a binary search finds. Index lookups read the records they find one by one from their
+/// offset, without reading the rest of the table: a hash index only reads the bucket the key
+/// falls in, which holds two entries at most on average, while an ordered index reads the pages
+/// on the way from its root to the first key found, which take about as many keys to go through
+/// as a binary search, then an entry for every record found.
+fn cost(plan: &Plan, count: usize) -> f64 {
+    let (count, rows) = (count as f64, plan.rows as f64);
+    let search = KEY_COST * count.log2().max(0.0);
+    match &plan.access {
+        Access::Scan => (LOAD_COST + 1.0) * count,
+        Access::Pkey(..) => LOAD_COST * count + search + rows,
+        Access::IndexLookup(index, _) if index.kind == IndexKind::Hash => {
+            2.0 * KEY_COST + (FETCH_COST + 1.0) * rows
+        }
+        Access::IndexLookup(..) | Access::IndexRange(..) => {
+            search + (KEY_COST + FETCH_COST + 1.0) * rows
+        }
+    }
+}
+
+/// Breaks ties between plans expected to cost as much, lower being better: looking a single
+/// record up by its primary key first, then exact index lookups, then ranges, and scans last.
+/// Between plans of the same kind, the one narrowing the records down by more predicates wins.
+fn rank(plan: &Plan) -> (u8, Reverse<usize>) {
+    let kind = match &plan.access {
+        Access::Pkey(lower, upper) if is_point(lower, upper) => 0,
+        Access::IndexLookup(..) => 1,
+        Access::Pkey(..) => 2,
+        Access::IndexRange(..) => 3,
+        Access::Scan => 4,
+    };
+    (kind, Reverse(plan.pushed.len()))
+}
+
+/// The plans the predicates make possible on a table of `count` live records, a scan always
+/// being one of them.
+fn candidates(
+    table: &TableFile,
+    count: usize,
+    predicates: &[Predicate],
+    statistics: Option<&Statistics>,
+) -> Vec<Plan> {
+    let on = |field: &str| -> Vec<Predicate> {
+        predicates
+            .iter()
+            .filter(|predicate| predicate.field == field)
+            .cloned()
+            .collect()
+    };
+
+    let rows = |pushed: &[Predicate]| scale(count, share(pushed, statistics));
+
+    let mut res = vec![Plan {
+        access: Access::Scan,
+        pushed: Vec::new(),
+        rows: count,
+    }];
+
+    if let (true, Some(pkey)) = (table.modes.contains(&Mode::Fread), table.pkey()) {
+        let pushed = on(&table.fields[pkey].2.to_string());
+        if !pushed.is_empty() {
+            let (lower, upper) = bounds(&pushed);
+            // Primary keys are unique
+            let rows = match is_point(&lower, &upper) {
+                true => count.min(1),
+                false => rows(&pushed),
+            };
+            res.push(Plan {
+                access: Access::Pkey(lower, upper),
+                pushed,
+                rows,
+            });
+        }
+    }
+
+    for index in &table.indexes {
+        // Equalities on the leading fields of the index make up a prefix of the keys looked up
+        let (mut key, mut pushed) = (Vec::new(), Vec::new());
+        for field in &index.fields {
+            let Some(equality) = predicates.iter().find(|predicate| {
+                predicate.field == field.to_string()
+                    && predicate.operation == BinaryOperation::Equals
+            }) else {
+                break;
+            };
+            key.push(Some(equality.value.clone()));
+            pushed.push(equality.clone());
+        }
+
+        if key.len() == index.fields.len() {
+            res.push(Plan {
+                access: Access::IndexLookup(index.clone(), key),
+                rows: rows(&pushed),
+                pushed,
+            });
+            continue;
+        }
+        if index.kind == IndexKind::Hash {
+            continue;
+        }
+
+        let ranged = on(&index.fields[key.len()].to_string());
+        if pushed.is_empty() && ranged.is_empty() {
+            continue;
+        }
+        let (lower, upper) = bounds(&ranged);
+        pushed.extend(ranged);
+        res.push(Plan {
+            access: Access::IndexRange(index.clone(), key, lower, upper),
+            rows: rows(&pushed),
+            pushed,
+        });
+    }
+
+    res
+}
+
+/// Chooses how to find the records of a table of `count` live records matching some conditions,
+/// `table` only being needed for its schema. Primary keys of `FREAD` tables and indexes can be
+/// used for the comparisons of their fields against values that the conditions combine by
+/// `AND`, and the plan expected to cost the least is chosen, going by the statistics of the
+/// table when it was analyzed. Scanning the table is always possible.
+pub fn plan(
+    table: &TableFile,
+    count: usize,
+    conditions: &Expression,
+    statistics: Option<&Statistics>,
+) -> Plan {
+    candidates(table, count, &predicates(conditions), statistics)
+        .into_iter()
+        .min_by(|a, b| {
+            cost(a, count)
+                .total_cmp(&cost(b, count))
+                .then_with(|| rank(a).cmp(&rank(b)))
+        })
+        .unwrap()
+}
+
+/// The positions of the records the access method of a plan finds in a table read whole, in the
+/// order they are stored in, deleted records included. Index lookups are left to [`lookup`], so
+/// the whole table is scanned for them.
+pub fn positions(table: &TableFile, access: &Access) -> Vec<usize> {
+    let scan = || (0..table.records.len()).collect();
+    let Access::Pkey(lower, upper) = access else {
+        return scan();
+    };
+    let Some(pkey) = table.pkey() else {
+        return scan();
+    };
+    let start = table
+        .records
+        .partition_point(|record| below(&record.values[pkey], lower));
+    let end = table
+        .records
+        .partition_point(|record| !above(&record.values[pkey], upper));
+    (start..end.max(start)).collect()
+}
+
+/// The offsets in the table file of the records an index lookup finds, in the order they are
+/// stored in, deleted records left out. There are none for other access methods, or when the
+/// index is not up to date with `generation`, the generation of the table as it is read.
+///
+/// Only the parts of the index file leading to the records are read, so the index must not
+/// change in the meantime: the caller holds a shared lock on the table.
+pub fn lookup(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    generation: u64,
+    access: &Access,
+) -> Result<Option<Vec<u64>>, io::Error> {
+    let index = match access {
+        Access::IndexLookup(index, _) | Access::IndexRange(index, ..) => index,
+        Access::Scan | Access::Pkey(..) => return Ok(None),
+    };
+    let index_name = index.name.to_string();
+    let found = match (access, &index.kind) {
+        (Access::IndexLookup(_, key), IndexKind::Hash) => {
+            HashIndex::lookup(engine, db_name, table_name, &index_name, generation, key)?
+        }
+        (Access::IndexLookup(_, key), IndexKind::BTree) => BTreeIndex::scan(
+            engine,
+            db_name,
+            table_name,
+            &index_name,
+            generation,
+            key,
+            |found| (found == key).then_some(true),
+        )?,
+        (Access::IndexRange(_, prefix, lower, upper), IndexKind::BTree) => {
+            let mut start = prefix.clone();
+            if let Some(value) = value(lower) {
+                start.push(Some(value.clone()));
+            }
+            BTreeIndex::scan(
+                engine,
+                db_name,
+                table_name,
+                &index_name,
+                generation,
+                &start,
+                |key| {
+                    if !key.starts_with(prefix) || above(&key[prefix.len()], upper) {
+                        return None;
+                    }
+                    Some(key[prefix.len()].is_some() && !below(&key[prefix.len()], lower))
+                },
+            )?
+        }
+        _ => None,
+    };
+    Ok(found.map(|mut offsets| {
+        offsets.sort();
+        offsets
+    }))
+}
@@ -0,0 +1,254 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::lock;
+use crate::engine::plan::{self, Access};
+use crate::engine::show;
+use crate::engine::snapshot;
+use crate::engine::statistics::Statistics;
+use crate::engine::storage::{Lock, LockKind};
+use crate::engine::table_file::{self, Record, TableFile};
+use crate::engine::versions::{self, Version};
+use crate::engine::{db_name, eval, snapshot_name, table_name, Engine, Output};
+use crate::parser::{
+    query::{Expression, Identifier},
+    Parser,
+};
+use std::io;
+use std::sync::Arc;
+
+/// The table a `READ` goes through: a live table, of which only the schema and the number of
+/// live records are known until its records are found, or a table as it was stored in a
+/// snapshot.
+enum Source {
+    Live(TableFile, usize, Option<Statistics>),
+    Snapshot(TableFile),
+}
+
+impl Source {
+    /// The schema of the table, along with its records for snapshots.
+    fn table(&self) -> &TableFile {
+        match self {
+            Source::Live(schema, ..) => schema,
+            Source::Snapshot(table) => table,
+        }
+    }
+
+    /// The number of live records in the table.
+    fn count(&self) -> usize {
+        match self {
+            Source::Live(_, count, _) => *count,
+            Source::Snapshot(table) => table.live_records().count(),
+        }
+    }
+
+    /// The statistics of the table, which are only kept for live tables.
+    fn statistics(&self) -> Option<&Statistics> {
+        match self {
+            Source::Live(.., statistics) => statistics.as_ref(),
+            Source::Snapshot(_) => None,
+        }
+    }
+}
+
+/// Finds the table a `READ` goes through, along with the positions of the fields it reads.
+///
+/// Live tables are looked up in the catalog first, so reading a missing table or field fails
+/// before any record is read. Snapshots carry their own schema.
+fn open(engine: &Engine, parser: &Parser) -> Result<(Source, Vec<usize>), io::Error> {
+    let (db_name, table_name, snapshot_name) =
+        (db_name(parser), table_name(parser), snapshot_name(parser));
+    let (schema, statistics) = if snapshot_name.is_empty() {
+        let mut catalog = Catalog::load(engine, db_name)?;
+        (
+            catalog.table(db_name, table_name)?.clone(),
+            catalog.statistics.remove(table_name),
+        )
+    } else {
+        (
+            snapshot::load_table(engine, db_name, snapshot_name, table_name)?,
+            None,
+        )
+    };
+
+    let mut columns: Vec<usize> = Vec::new();
+    for field in &parser.query_data.read_fields {
+        let name = field.to_string();
+        columns.push(schema.field_index(&name).ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Field {} not found on {}", name, table_name),
+        ))?);
+    }
+
+    let source = if snapshot_name.is_empty() {
+        let (_, count) = {
+            let _lock = shared(engine, db_name, table_name)?;
+            table_file::head(engine, db_name, table_name)?
+        };
+        Source::Live(schema, count, statistics)
+    } else {
+        Source::Snapshot(schema)
+    };
+    Ok((source, columns))
+}
+
+fn shared(engine: &Engine, db_name: &str, table_name: &str) -> Result<Lock, io::Error> {
+    lock::acquire(
+        engine,
+        db_name,
+        &lock::table_path(engine, db_name, table_name),
+        LockKind::Shared,
+    )
+}
+
+/// Reads the records an index lookup finds in a live table one by one from their offset,
+/// without reading the rest of the table file. The table is held for as long as it takes, so
+/// the records are the ones the index points to. There are none when the plan does not go
+/// through an index, or when the index is not up to date.
+fn fetch(
+    engine: &Engine,
+    parser: &Parser,
+    source: &Source,
+    access: &Access,
+) -> Result<Option<Vec<Record>>, io::Error> {
+    let Source::Live(..) = source else {
+        return Ok(None);
+    };
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    let _lock = shared(engine, db_name, table_name)?;
+    let (generation, _) = table_file::head(engine, db_name, table_name)?;
+    let Some(offsets) = plan::lookup(engine, db_name, table_name, generation, access)? else {
+        return Ok(None);
+    };
+    offsets
+        .into_iter()
+        .map(|offset| table_file::fetch(engine, db_name, table_name, offset))
+        .collect::<Result<Vec<Record>, io::Error>>()
+        .map(Some)
+}
+
+/// Finds the records of a table matching the query's conditions, through the access method the
+/// planner chose for them, and checks the conditions against each of them. With `AT snapshot`,
+/// the table is read as it was stored in that snapshot instead of its live state.
+///
+/// Index lookups on live tables only read the records they find. Other reads of live tables
+/// pin the whole table as it was when the read started, whatever is written to it in the
+/// meantime.
+pub fn read(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let (source, columns) = open(engine, parser)?;
+    let plan = plan::plan(
+        source.table(),
+        source.count(),
+        &parser.query_data.conditions,
+        source.statistics(),
+    );
+
+    let mut rows: Vec<Vec<Option<Identifier>>> = Vec::new();
+    let mut keep = |record: &Record| -> Result<(), io::Error> {
+        if !record.deleted
+            && eval::matches(
+                &parser.query_data.conditions,
+                source.table(),
+                &record.values,
+            )?
+        {
+            rows.push(
+                columns
+                    .iter()
+                    .map(|column| record.values[*column].clone())
+                    .collect(),
+            );
+        }
+        Ok(())
+    };
+    if let Some(records) = fetch(engine, parser, &source, &plan.access)? {
+        for record in &records {
+            keep(record)?;
+        }
+    } else {
+        let version: Arc<Version>;
+        let table = match &source {
+            Source::Live(..) => {
+                version = versions::pin(engine, db_name(parser), table_name(parser))?;
+                &version.table
+            }
+            Source::Snapshot(table) => table,
+        };
+        for position in plan::positions(table, &plan.access) {
+            keep(&table.records[position])?;
+        }
+    }
+
+    Ok(Output::ResultSet(
+        parser
+            .query_data
+            .read_fields
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+        rows,
+    ))
+}
+
+/// Lists the operators an `EXPLAIN READ` would run, in the order they run, without reading any
+/// record: how the records are found and by which predicates, the conditions then left to
+/// check, and the fields kept. Each operator comes with the number of records it is expected to
+/// pass on.
+pub fn explain(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let (source, _) = open(engine, parser)?;
+    let plan = plan::plan(
+        source.table(),
+        source.count(),
+        &parser.query_data.conditions,
+        source.statistics(),
+    );
+
+    let pushed = plan
+        .pushed
+        .iter()
+        .map(|predicate| predicate.to_string())
+        .collect::<Vec<String>>()
+        .join(" AND ");
+    let detail = match &plan.access {
+        Access::Scan => table_name(parser).to_string(),
+        Access::Pkey(..) => pushed,
+        Access::IndexLookup(index, _) | Access::IndexRange(index, ..) => {
+            format!("{} ON {}", index, pushed)
+        }
+    };
+    let mut operators = vec![(plan.access.name(), detail, plan.rows)];
+
+    let residual = plan::residual(&parser.query_data.conditions, &plan.pushed);
+    let mut rows = plan.rows;
+    if residual != Expression::None {
+        rows = plan::estimate(rows, &residual, source.statistics());
+        operators.push(("FILTER", residual.to_string(), rows));
+    }
+    operators.push((
+        "PROJECT",
+        parser
+            .query_data
+            .read_fields
+            .iter()
+            .map(|field| field.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+        rows,
+    ));
+
+    Ok(Output::ResultSet(
+        vec![
+            "operator".to_string(),
+            "detail".to_string(),
+            "rows".to_string(),
+        ],
+        operators
+            .into_iter()
+            .map(|(operator, detail, rows)| {
+                vec![
+                    show::text(operator.to_string()),
+                    show::text(detail),
+                    Some(Identifier::IntLiteral(rows as i32)),
+                ]
+            })
+            .collect(),
+    ))
+}
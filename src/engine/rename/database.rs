@@ -0,0 +1,34 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, new_name, Engine};
+use crate::parser::Parser;
+use std::io;
+
+/// Moves a database to a new directory in a single rename, then rewrites the header of each of
+/// its tables to name the new database.
+pub fn rename_database(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, new_name) = (db_name(parser), new_name(parser));
+    if !engine.storage().is_dir(&engine.path(db_name)) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Database {} not found", db_name),
+        ));
+    }
+    if engine.storage().exists(&engine.path(new_name)) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Database {} already exists", new_name),
+        ));
+    }
+
+    engine
+        .storage()
+        .rename(&engine.path(db_name), &engine.path(new_name))?;
+
+    // Table files and the catalog are read back without looking at the names in their headers,
+    // so the database is usable even if this is interrupted
+    for table_name in table_file::table_names(engine, new_name)? {
+        TableFile::load(engine, new_name, &table_name)?.save(engine, new_name, &table_name)?;
+    }
+    Catalog::rebuild(engine, new_name).map(|_| ())
+}
@@ -0,0 +1,71 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::snapshot::table_files;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, new_name, table_name, Engine};
+use crate::parser::Parser;
+use std::io;
+
+/// Renames a table along with the files stored alongside it, and points every `FKEY` referring
+/// to it at its new name. Snapshots keep the name the table had when they were taken.
+pub fn rename_table(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, new_name) = (db_name(parser), table_name(parser), new_name(parser));
+    if !table_file::exists(engine, db_name, table_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ));
+    }
+    if table_file::exists(engine, db_name, new_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Table {} already exists on {}", new_name, db_name),
+        ));
+    }
+
+    // Every file is moved, and every reference rewritten, in a single transaction, so the log
+    // holds the whole rename as one entry, which is either redone entirely or not at all
+    engine.atomically(|| move_table(engine, db_name, table_name, new_name))
+}
+
+fn move_table(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    new_name: &str,
+) -> Result<(), io::Error> {
+    let referencing = table_file::referencing(engine, db_name, table_name)?;
+
+    for file in table_files(engine, db_name, table_name)? {
+        let suffix = &file.file_name().unwrap().to_string_lossy()[table_name.len()..];
+        engine.storage().rename(
+            &file,
+            &engine.path(db_name).join(format!("{}{}", new_name, suffix)),
+        )?;
+    }
+
+    // The table is moved in the catalog first, so saving it under its new name keeps its
+    // statistics up to date
+    let mut catalog = Catalog::load(engine, db_name)?;
+    if let Some(schema) = catalog.tables.remove(table_name) {
+        catalog.tables.insert(new_name.to_string(), schema);
+    }
+    if let Some(statistics) = catalog.statistics.remove(table_name) {
+        catalog.statistics.insert(new_name.to_string(), statistics);
+    }
+    catalog.save(engine, db_name)?;
+
+    let mut table = TableFile::load(engine, db_name, new_name)?;
+    table.rename_references(table_name, new_name);
+    table.save(engine, db_name, new_name)?;
+
+    let mut schemas = vec![(new_name.to_string(), table.schema())];
+    for (other_name, mut other) in referencing {
+        other.rename_references(table_name, new_name);
+        other.save(engine, db_name, &other_name)?;
+        schemas.push((other_name, other.schema()));
+    }
+
+    let mut catalog = Catalog::load(engine, db_name)?;
+    catalog.tables.extend(schemas);
+    catalog.save(engine, db_name)
+}
@@ -0,0 +1,107 @@
+use crate::engine::catalog::{self, Catalog};
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, table_name, Engine, Output};
+use crate::parser::{query::Identifier, Parser};
+use std::io;
+
+/// A value of a result set, quoted the way `READ` returns the string values of records.
+pub(crate) fn text(value: String) -> Option<Identifier> {
+    Some(Identifier::StringLiteral(format!("'{}'", value)))
+}
+
+fn modes(table: &TableFile) -> String {
+    table
+        .modes
+        .iter()
+        .map(|mode| mode.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Lists the databases found in the root of the engine, which are the directories holding a
+/// catalog.
+pub fn show_databases(engine: &Engine, _parser: &Parser) -> Result<Output, io::Error> {
+    let mut databases: Vec<String> = Vec::new();
+    for entry in engine.storage().list(engine.root())? {
+        let name = entry.name();
+        if entry.is_dir && engine.storage().exists(&catalog::path(engine, &name)) {
+            databases.push(name);
+        }
+    }
+    databases.sort();
+
+    Ok(Output::ResultSet(
+        vec!["database".to_string()],
+        databases.into_iter().map(|name| vec![text(name)]).collect(),
+    ))
+}
+
+/// Lists the tables of a database along with their modes.
+pub fn show_tables(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let catalog = Catalog::load(engine, db_name(parser))?;
+
+    Ok(Output::ResultSet(
+        vec!["table".to_string(), "modes".to_string()],
+        catalog
+            .tables
+            .iter()
+            .map(|(table_name, table)| vec![text(table_name.clone()), text(modes(table))])
+            .collect(),
+    ))
+}
+
+/// Lists the fields of a table in order, with their datatype, constraints and the indexes on
+/// them written the same way as in a `TABLE` statement, along with the modes of the table.
+/// Fields without constraints or indexes have no value in those columns.
+pub fn describe(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    let catalog = Catalog::load(engine, db_name)?;
+    let table = catalog.table(db_name, table_name)?;
+
+    Ok(Output::ResultSet(
+        vec![
+            "field".to_string(),
+            "datatype".to_string(),
+            "constraints".to_string(),
+            "indexes".to_string(),
+            "modes".to_string(),
+        ],
+        table
+            .fields
+            .iter()
+            .map(|field| {
+                let (_, _, identifier) = field;
+                let constraints = table
+                    .constraints
+                    .get(identifier)
+                    .map(|constraints| {
+                        constraints
+                            .iter()
+                            .map(|constraint| constraint.to_string())
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    })
+                    .filter(|constraints| !constraints.is_empty());
+                let indexes = table
+                    .indexes
+                    .iter()
+                    .filter(|index| index.fields.contains(identifier))
+                    .map(|index| index.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                vec![
+                    text(identifier.to_string()),
+                    text(table_file::datatype(field)),
+                    constraints.and_then(text),
+                    if indexes.is_empty() {
+                        None
+                    } else {
+                        text(indexes)
+                    },
+                    text(modes(table)),
+                ]
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,47 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::snapshot::{database_files, read_object, set_head, write_snapshot, Manifest};
+use crate::engine::{db_name, snapshot_name, Engine};
+use crate::parser::Parser;
+use std::io;
+
+/// Records every table of a database in a new snapshot. Snapshots are never overwritten.
+pub fn create_snapshot(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    write_snapshot(
+        engine,
+        parser,
+        None,
+        database_files(engine, db_name(parser))?,
+    )
+}
+
+/// Replaces the live tables of a database with the ones stored in a snapshot.
+pub fn restore_snapshot(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, snapshot_name) = (db_name(parser), snapshot_name(parser));
+    let manifest = Manifest::load(engine, db_name, snapshot_name)?;
+    if let Some(table_name) = manifest.table {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Snapshot {} only holds table {}, restore it ON {} ON {}",
+                snapshot_name, table_name, table_name, db_name
+            ),
+        ));
+    }
+
+    // Every file is replaced in a single transaction, so the log holds the whole restore as one
+    // entry, which is either redone entirely or not at all
+    engine.atomically(|| {
+        for file in database_files(engine, db_name)? {
+            engine.storage().remove_file(&file)?;
+        }
+        for (file, hash) in &manifest.files {
+            engine.storage().write(
+                &engine.path(db_name).join(file),
+                &read_object(engine, db_name, hash)?,
+            )?;
+        }
+        Catalog::rebuild(engine, db_name)?;
+
+        set_head(engine, db_name, snapshot_name)
+    })
+}
@@ -0,0 +1,122 @@
+use crate::engine::show;
+use crate::engine::snapshot::{read_object, Manifest};
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, snapshot_name, Engine, Output};
+use crate::parser::{query::Identifier, Parser};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+/// The live records of a table, each with the key `DIFF` reports it under and how many times the
+/// table holds it.
+type Records =
+    BTreeMap<Vec<Option<Identifier>>, (Option<Identifier>, Vec<Option<Identifier>>, usize)>;
+
+fn other_snapshot_name(parser: &Parser) -> &str {
+    if let Identifier::StringLiteral(snapshot_name) = &parser.query_data.other_snapshot_name {
+        return snapshot_name;
+    }
+    panic!("Snapshot name not found, or it was not in a string literal identifier")
+}
+
+fn tables(
+    engine: &Engine,
+    db_name: &str,
+    manifest: &Manifest,
+) -> Result<BTreeMap<String, TableFile>, io::Error> {
+    let mut res = BTreeMap::new();
+    for (file, hash) in &manifest.files {
+        if let Some(table_name) = file.strip_suffix(".asql") {
+            let contents = read_object(engine, db_name, hash)?;
+            res.insert(
+                table_name.to_string(),
+                TableFile::parse(&String::from_utf8_lossy(&contents))?,
+            );
+        }
+    }
+    Ok(res)
+}
+
+/// Keys the live records of a table by their `PKEY`. Tables without one are keyed by the whole
+/// record, nulls included, so their records can only ever show up as added or removed, once for
+/// every copy of them.
+fn keyed_records(table: Option<&TableFile>) -> Records {
+    let mut res = Records::new();
+    let Some(table) = table else {
+        return res;
+    };
+
+    for record in table.live_records() {
+        let (key, shown) = match table.pkey() {
+            Some(pkey) => match &record.values[pkey] {
+                Some(key) => (vec![Some(key.clone())], Some(key.clone())),
+                None => continue,
+            },
+            None => (
+                record.values.clone(),
+                Some(Identifier::StringLiteral(format!(
+                    "[{}]",
+                    table_file::serialize_values(&record.values)
+                ))),
+            ),
+        };
+        res.entry(key)
+            .or_insert((shown, record.values.clone(), 0))
+            .2 += 1;
+    }
+    res
+}
+
+/// Compares two snapshots table by table, reporting every record that was added, removed or
+/// changed on the way from the first snapshot to the second.
+pub fn diff(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let db_name = db_name(parser);
+    let old = tables(
+        engine,
+        db_name,
+        &Manifest::load(engine, db_name, snapshot_name(parser))?,
+    )?;
+    let new = tables(
+        engine,
+        db_name,
+        &Manifest::load(engine, db_name, other_snapshot_name(parser))?,
+    )?;
+
+    let mut rows = Vec::new();
+    for table_name in old.keys().chain(new.keys()).collect::<BTreeSet<&String>>() {
+        let old_records = keyed_records(old.get(table_name));
+        let new_records = keyed_records(new.get(table_name));
+
+        for key in old_records
+            .keys()
+            .chain(new_records.keys())
+            .collect::<BTreeSet<&Vec<Option<Identifier>>>>()
+        {
+            let (shown, change, times) = match (old_records.get(key), new_records.get(key)) {
+                (Some((shown, old_values, _)), Some((_, new_values, _)))
+                    if old_values != new_values =>
+                {
+                    (shown, "changed", 1)
+                }
+                (Some((shown, _, old)), Some((_, _, new))) if old > new => {
+                    (shown, "removed", old - new)
+                }
+                (Some((_, _, old)), Some((shown, _, new))) => (shown, "added", new - old),
+                (Some((shown, _, old)), None) => (shown, "removed", *old),
+                (None, Some((shown, _, new))) => (shown, "added", *new),
+                (None, None) => continue,
+            };
+            for _ in 0..times {
+                rows.push(vec![
+                    show::text(table_name.clone()),
+                    show::text(change.to_string()),
+                    shown.clone(),
+                ]);
+            }
+        }
+    }
+
+    Ok(Output::ResultSet(
+        vec!["table".to_string(), "change".to_string(), "key".to_string()],
+        rows,
+    ))
+}
@@ -0,0 +1,381 @@
+use crate::engine::hash;
+use crate::engine::show;
+use crate::engine::table_file::TableFile;
+use crate::engine::{db_name, snapshot_name, table_name, Engine, Output};
+use crate::parser::{query::Identifier, Parser};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod database;
+pub mod diff;
+pub mod table;
+
+/// A snapshot as recorded in the snapshot log of a database.
+///
+/// The files of a snapshot are not copied into it directly. Each file is stored once in the
+/// object store under the hash of its contents, and the manifest only refers to those hashes,
+/// so a table left unchanged between two snapshots is stored a single time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub parent: Option<String>,
+    pub created: u64,
+    pub table: Option<String>,
+    pub message: String,
+    //              FILE    HASH
+    pub files: Vec<(String, String)>,
+}
+
+pub fn snapshots_path(engine: &Engine, db_name: &str) -> PathBuf {
+    engine.path(db_name).join(".snapshots")
+}
+
+pub fn manifest_path(engine: &Engine, db_name: &str, snapshot_name: &str) -> PathBuf {
+    snapshots_path(engine, db_name)
+        .join("manifests")
+        .join(snapshot_name)
+}
+
+pub fn object_path(engine: &Engine, db_name: &str, hash: &str) -> PathBuf {
+    snapshots_path(engine, db_name).join("objects").join(hash)
+}
+
+fn head_path(engine: &Engine, db_name: &str) -> PathBuf {
+    snapshots_path(engine, db_name).join("HEAD")
+}
+
+/// Stores `contents` in the object store unless an identical object is already there, and
+/// returns the name it is stored under. Objects are named after the hash of their contents,
+/// and the rare object whose hash is already taken by different contents gets a numbered name
+/// after it instead.
+pub fn store_object(engine: &Engine, db_name: &str, contents: &[u8]) -> Result<String, io::Error> {
+    let hash = hash::hex(contents);
+    for attempt in 0.. {
+        let name = match attempt {
+            0 => hash.clone(),
+            attempt => format!("{}-{}", hash, attempt),
+        };
+        let path = object_path(engine, db_name, &name);
+        if !engine.storage().exists(&path) {
+            engine.storage().create_dir_all(path.parent().unwrap())?;
+            engine.storage().write(&path, contents)?;
+            return Ok(name);
+        }
+        if engine.storage().read(&path)? == contents {
+            return Ok(name);
+        }
+    }
+    unreachable!("Ran out of names for an object")
+}
+
+pub fn read_object(engine: &Engine, db_name: &str, hash: &str) -> Result<Vec<u8>, io::Error> {
+    engine.storage().read(&object_path(engine, db_name, hash))
+}
+
+/// The snapshot the live database was last snapshotted as, or restored from.
+pub fn head(engine: &Engine, db_name: &str) -> Result<Option<String>, io::Error> {
+    if !engine.storage().exists(&head_path(engine, db_name)) {
+        return Ok(None);
+    }
+    Ok(Some(
+        engine
+            .storage()
+            .read_to_string(&head_path(engine, db_name))?
+            .trim()
+            .to_string(),
+    ))
+}
+
+pub fn set_head(engine: &Engine, db_name: &str, snapshot_name: &str) -> Result<(), io::Error> {
+    engine
+        .storage()
+        .write(&head_path(engine, db_name), snapshot_name.as_bytes())
+}
+
+/// Keeps a message on a single line of its manifest, escaping backslashes first so those
+/// already in the message are told apart from the escaped line breaks.
+fn escape(message: &str) -> String {
+    message.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(message: &str) -> String {
+    let mut res = String::new();
+    let mut chars = message.chars();
+    while let Some(character) = chars.next() {
+        res.push(match character {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some(escaped) => escaped,
+                None => character,
+            },
+            character => character,
+        });
+    }
+    res
+}
+
+impl Manifest {
+    pub fn exists(engine: &Engine, db_name: &str, snapshot_name: &str) -> bool {
+        engine
+            .storage()
+            .exists(&manifest_path(engine, db_name, snapshot_name))
+    }
+
+    pub fn load(
+        engine: &Engine,
+        db_name: &str,
+        snapshot_name: &str,
+    ) -> Result<Manifest, io::Error> {
+        let path = manifest_path(engine, db_name, snapshot_name);
+        let contents = match engine.storage().read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Snapshot {} not found on {}", snapshot_name, db_name),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut manifest = Manifest {
+            parent: None,
+            created: 0,
+            table: None,
+            message: String::new(),
+            files: Vec::new(),
+        };
+        for line in contents.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let optional = || (!value.is_empty()).then(|| value.to_string());
+            match key {
+                "parent" => manifest.parent = optional(),
+                "created" => manifest.created = value.parse().unwrap_or(0),
+                "table" => manifest.table = optional(),
+                "message" => manifest.message = unescape(value),
+                "file" => {
+                    let (file, hash) = value.split_once(' ').ok_or(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Malformed file entry in snapshot {}", snapshot_name),
+                    ))?;
+                    manifest.files.push((file.to_string(), hash.to_string()));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn save(
+        &self,
+        engine: &Engine,
+        db_name: &str,
+        snapshot_name: &str,
+    ) -> Result<(), io::Error> {
+        let mut contents = format!(
+            "parent {}\ncreated {}\ntable {}\nmessage {}\n",
+            self.parent.clone().unwrap_or_default(),
+            self.created,
+            self.table.clone().unwrap_or_default(),
+            escape(&self.message),
+        );
+        for (file, hash) in &self.files {
+            contents.push_str(&format!("file {} {}\n", file, hash));
+        }
+
+        let path = manifest_path(engine, db_name, snapshot_name);
+        engine.storage().create_dir_all(path.parent().unwrap())?;
+        engine.storage().write(&path, contents.as_bytes())
+    }
+}
+
+/// Records a new snapshot made of `files`, with the current head as its parent.
+pub fn write_snapshot(
+    engine: &Engine,
+    parser: &Parser,
+    table: Option<String>,
+    files: Vec<PathBuf>,
+) -> Result<(), io::Error> {
+    let (db_name, snapshot_name) = (db_name(parser), snapshot_name(parser));
+    if Manifest::exists(engine, db_name, snapshot_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Snapshot {} already exists on {}", snapshot_name, db_name),
+        ));
+    }
+
+    let mut stored = Vec::new();
+    for file in files {
+        let hash = store_object(engine, db_name, &engine.storage().read(&file)?)?;
+        stored.push((
+            file.file_name().unwrap().to_string_lossy().to_string(),
+            hash,
+        ));
+    }
+
+    let message = match &parser.query_data.snapshot_message {
+        Identifier::StringLiteral(message) => message.trim_matches('\'').to_string(),
+        _ => String::new(),
+    };
+
+    Manifest {
+        parent: head(engine, db_name)?,
+        created: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs()),
+        table,
+        message,
+        files: stored,
+    }
+    .save(engine, db_name, snapshot_name)?;
+
+    set_head(engine, db_name, snapshot_name)
+}
+
+/// Reads a table as it was stored in a snapshot.
+pub fn load_table(
+    engine: &Engine,
+    db_name: &str,
+    snapshot_name: &str,
+    table_name: &str,
+) -> Result<TableFile, io::Error> {
+    let manifest = Manifest::load(engine, db_name, snapshot_name)?;
+    let file_name = format!("{}.asql", table_name);
+    let (_, hash) = manifest
+        .files
+        .iter()
+        .find(|(file, _)| *file == file_name)
+        .ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "Table {} not found in snapshot {} on {}",
+                table_name, snapshot_name, db_name
+            ),
+        ))?;
+
+    TableFile::parse(&String::from_utf8_lossy(&read_object(
+        engine, db_name, hash,
+    )?))
+}
+
+/// Lists the files making up the live state of a database. Hidden entries, such as the
+/// snapshots themselves, and leftover temporary files are skipped.
+pub fn database_files(engine: &Engine, db_name: &str) -> Result<Vec<PathBuf>, io::Error> {
+    let mut res: Vec<PathBuf> = Vec::new();
+    for entry in engine.storage().list(&engine.path(db_name))? {
+        let name = entry.name();
+        if !entry.is_dir && !name.starts_with('.') && !name.ends_with(".tmp") {
+            res.push(entry.path);
+        }
+    }
+    res.sort();
+
+    Ok(res)
+}
+
+/// Lists the files belonging to a single table: its table file and any file stored alongside
+/// it under the same name.
+pub fn table_files(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+) -> Result<Vec<PathBuf>, io::Error> {
+    Ok(database_files(engine, db_name)?
+        .into_iter()
+        .filter(|file| {
+            file.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(&format!("{}.", table_name))
+        })
+        .collect())
+}
+
+pub fn snapshot(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    if table_name(parser).is_empty() {
+        database::create_snapshot(engine, parser)
+    } else {
+        table::create_snapshot(engine, parser)
+    }
+}
+
+pub fn restore(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    if table_name(parser).is_empty() {
+        database::restore_snapshot(engine, parser)
+    } else {
+        table::restore_snapshot(engine, parser)
+    }
+}
+
+/// Puts snapshots in the order they were taken. Every snapshot follows its parent, so the
+/// chain of parents leading to the head is kept in order whenever the snapshots on it were
+/// taken, and only snapshots on different branches are ordered by the time they were taken.
+fn taken_order(snapshots: Vec<(String, Manifest)>) -> Vec<(String, Manifest)> {
+    let mut remaining: BTreeMap<String, Manifest> = snapshots.into_iter().collect();
+    let mut res = Vec::new();
+    while !remaining.is_empty() {
+        // Snapshots whose parent is still to be listed wait for it, unless their parents go
+        // round in circles
+        let waits = |manifest: &Manifest| {
+            manifest
+                .parent
+                .as_ref()
+                .is_some_and(|parent| remaining.contains_key(parent))
+        };
+        let next = remaining
+            .iter()
+            .min_by_key(|(name, manifest)| (waits(manifest), manifest.created, *name))
+            .map(|(name, _)| name.clone())
+            .unwrap();
+        let manifest = remaining.remove(&next).unwrap();
+        res.push((next, manifest));
+    }
+    res
+}
+
+/// Lists the snapshot log of a database, oldest first.
+pub fn list_snapshots(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let db_name = db_name(parser);
+    if !engine.storage().is_dir(&engine.path(db_name)) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Database {} not found", db_name),
+        ));
+    }
+
+    let mut snapshots: Vec<(String, Manifest)> = Vec::new();
+    let manifests = snapshots_path(engine, db_name).join("manifests");
+    if engine.storage().is_dir(&manifests) {
+        for entry in engine.storage().list(&manifests)? {
+            let name = entry.name();
+            if !name.ends_with(".tmp") {
+                let manifest = Manifest::load(engine, db_name, &name)?;
+                snapshots.push((name, manifest));
+            }
+        }
+    }
+
+    Ok(Output::ResultSet(
+        vec![
+            "snapshot".to_string(),
+            "table".to_string(),
+            "parent".to_string(),
+            "created".to_string(),
+            "message".to_string(),
+        ],
+        taken_order(snapshots)
+            .into_iter()
+            .map(|(name, manifest)| {
+                vec![
+                    show::text(name),
+                    manifest.table.and_then(show::text),
+                    manifest.parent.and_then(show::text),
+                    show::text(manifest.created.to_string()),
+                    show::text(manifest.message),
+                ]
+            })
+            .collect(),
+    ))
+}
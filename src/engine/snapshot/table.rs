@@ -0,0 +1,106 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::snapshot::{read_object, table_files, write_snapshot, Manifest};
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::{db_name, snapshot_name, table_name, Engine};
+use crate::parser::{query::Identifier, Parser};
+use std::io;
+
+/// Records the files of a single table in a new snapshot. The record counting `INC` fields
+/// live in the table file itself, so they are captured along with the data.
+pub fn create_snapshot(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    if !table_file::exists(engine, db_name, table_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ));
+    }
+
+    write_snapshot(
+        engine,
+        parser,
+        Some(table_name.to_string()),
+        table_files(engine, db_name, table_name)?,
+    )
+}
+
+/// Restores a table snapshot, either over the table it was taken of or, with `INTO`, as a new
+/// table. No other table of the database is touched.
+pub fn restore_snapshot(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name, snapshot_name) =
+        (db_name(parser), table_name(parser), snapshot_name(parser));
+    let manifest = Manifest::load(engine, db_name, snapshot_name)?;
+
+    match &manifest.table {
+        Some(snapshot_table) if snapshot_table == table_name => {}
+        Some(snapshot_table) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Snapshot {} holds table {}, not {}",
+                    snapshot_name, snapshot_table, table_name
+                ),
+            ))
+        }
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Snapshot {} holds the whole of {}, restore it ON {}",
+                    snapshot_name, db_name, db_name
+                ),
+            ))
+        }
+    }
+
+    let target = match &parser.query_data.new_name {
+        Identifier::StringLiteral(new_name) if !new_name.is_empty() => new_name.as_str(),
+        _ => table_name,
+    };
+    if target != table_name && table_file::exists(engine, db_name, target) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Table {} already exists on {}", target, db_name),
+        ));
+    }
+
+    // The table file, its other files and the catalog are replaced in a single transaction, so
+    // the log holds the whole restore as one entry, which is either redone entirely or not at
+    // all
+    engine.atomically(|| {
+        let previous_files = table_files(engine, db_name, target)?;
+        let mut restored_files = Vec::new();
+        for (file, hash) in &manifest.files {
+            let suffix = &file[table_name.len()..];
+            let destination = engine.path(db_name).join(format!("{}{}", target, suffix));
+            let contents = read_object(engine, db_name, hash)?;
+
+            if suffix == ".asql" {
+                // The table file names its own table, so it is rewritten rather than copied,
+                // and it carries on from the generation of the table it replaces, if any, so
+                // indexes up to date with that table are never taken for up to date with this
+                // one
+                let mut table = TableFile::parse(&String::from_utf8_lossy(&contents))?;
+                if table_file::exists(engine, db_name, target) {
+                    let replaced = TableFile::load(engine, db_name, target)?;
+                    table.generation = table.generation.max(replaced.generation);
+                }
+                table.save(engine, db_name, target)?;
+                Catalog::set(engine, db_name, target, Some(&table))?;
+            } else if suffix.ends_with(".idx") {
+                // Saving the table built its indexes again, and the stored ones would be stale
+            } else {
+                engine.storage().write(&destination, &contents)?;
+            }
+            restored_files.push(destination);
+        }
+
+        for file in previous_files {
+            if !restored_files.contains(&file) {
+                engine.storage().remove_file(&file)?;
+            }
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,264 @@
+use crate::engine::catalog::Catalog;
+use crate::engine::table_file::{self, Record, TableFile};
+use crate::engine::{db_name, table_name, Engine};
+use crate::parser::{query::Identifier, Parser};
+use std::collections::BTreeMap;
+use std::io;
+
+/// How many buckets the histogram of a field is split into at most.
+const BUCKETS: usize = 8;
+
+/// What the planner knows about the records of a table, gathered by `ANALYZE` and kept in the
+/// catalog of the database. Tables never analyzed have none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    pub rows: usize,
+    pub fields: BTreeMap<String, FieldStatistics>,
+}
+
+/// What is known about the values of one field. Only `ANALYZE` and writes rewriting the whole
+/// table make these exact: records added or removed one at a time update the counts, but the
+/// distinct values, the bounds and the histogram are only estimated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldStatistics {
+    pub distinct: usize,
+    pub nulls: usize,
+    pub min: Option<Identifier>,
+    pub max: Option<Identifier>,
+    /// Buckets holding about as many values each, ordered: the largest value of each, and how
+    /// many values it holds.
+    pub histogram: Vec<(Identifier, usize)>,
+}
+
+fn malformed(table_name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Malformed statistics for {}", table_name),
+    )
+}
+
+fn parse_value(value: &str) -> Option<Identifier> {
+    table_file::parse_values(value).pop().flatten()
+}
+
+fn serialize_value(value: &Option<Identifier>) -> String {
+    table_file::serialize_values(std::slice::from_ref(value))
+}
+
+impl FieldStatistics {
+    /// Gathers statistics on some values of a field.
+    pub fn build<'a>(values: impl Iterator<Item = &'a Option<Identifier>>) -> FieldStatistics {
+        let mut res = FieldStatistics::default();
+        let mut present: Vec<&Identifier> = Vec::new();
+        for value in values {
+            match value {
+                Some(value) => present.push(value),
+                None => res.nulls += 1,
+            }
+        }
+        present.sort();
+
+        res.min = present.first().map(|value| (*value).clone());
+        res.max = present.last().map(|value| (*value).clone());
+        res.distinct = present
+            .iter()
+            .enumerate()
+            .filter(|(i, value)| *i == 0 || present[i - 1] != **value)
+            .count();
+
+        // Equal values end up in the same bucket, so buckets are merged when they end alike
+        for bucket in present.chunks(present.len().div_ceil(BUCKETS).max(1)) {
+            let last = bucket[bucket.len() - 1];
+            match res.histogram.last_mut() {
+                Some((upper, count)) if upper == last => *count += bucket.len(),
+                _ => res.histogram.push((last.clone(), bucket.len())),
+            }
+        }
+        res
+    }
+
+    fn add(&mut self, value: &Option<Identifier>) {
+        let Some(value) = value else {
+            self.nulls += 1;
+            return;
+        };
+
+        // A value outside of the bounds cannot have been seen before
+        if !(self.min.as_ref() <= Some(value) && Some(value) <= self.max.as_ref()) {
+            self.distinct += 1;
+        }
+        if self.min.as_ref().is_none_or(|min| value < min) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().is_none_or(|max| value > max) {
+            self.max = Some(value.clone());
+        }
+
+        match self.histogram.iter().position(|(upper, _)| value <= upper) {
+            Some(bucket) => self.histogram[bucket].1 += 1,
+            None => match self.histogram.last_mut() {
+                Some((upper, count)) => {
+                    *upper = value.clone();
+                    *count += 1;
+                }
+                None => self.histogram.push((value.clone(), 1)),
+            },
+        }
+    }
+
+    fn remove(&mut self, value: &Option<Identifier>) {
+        let Some(value) = value else {
+            self.nulls = self.nulls.saturating_sub(1);
+            return;
+        };
+        if let Some(bucket) = self.histogram.iter().position(|(upper, _)| value <= upper) {
+            self.histogram[bucket].1 = self.histogram[bucket].1.saturating_sub(1);
+        }
+    }
+}
+
+impl Statistics {
+    /// Gathers statistics on the live records of a table.
+    pub fn build(table: &TableFile) -> Statistics {
+        let mut res = Statistics {
+            rows: table.live_records().count(),
+            fields: BTreeMap::new(),
+        };
+        for (i, (_, _, field)) in table.fields.iter().enumerate() {
+            res.fields.insert(
+                field.to_string(),
+                FieldStatistics::build(table.live_records().map(|record| &record.values[i])),
+            );
+        }
+        res
+    }
+
+    /// Reads the statistics of a table back from the lines written by [`Statistics::serialize`].
+    pub(crate) fn parse<'a>(
+        table_name: &str,
+        lines: impl Iterator<Item = &'a str>,
+    ) -> Result<Statistics, io::Error> {
+        let mut res = Statistics::default();
+        let mut field: Option<&mut FieldStatistics> = None;
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let count = || value.parse::<usize>().map_err(|_| malformed(table_name));
+            match (key, field.as_deref_mut()) {
+                ("rows", _) => res.rows = count()?,
+                ("field", _) => {
+                    field = Some(res.fields.entry(value.to_string()).or_default());
+                }
+                ("distinct", Some(field)) => field.distinct = count()?,
+                ("nulls", Some(field)) => field.nulls = count()?,
+                ("min", Some(field)) => field.min = parse_value(value),
+                ("max", Some(field)) => field.max = parse_value(value),
+                ("bucket", Some(field)) => {
+                    let (count, upper) = value.split_once(' ').ok_or(malformed(table_name))?;
+                    field.histogram.push((
+                        parse_value(upper).ok_or(malformed(table_name))?,
+                        count.parse().map_err(|_| malformed(table_name))?,
+                    ));
+                }
+                _ => return Err(malformed(table_name)),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Writes the statistics out as lines of text.
+    pub(crate) fn serialize(&self) -> String {
+        let mut contents = format!("rows {}\n", self.rows);
+        for (name, field) in &self.fields {
+            contents.push_str(&format!(
+                "field {}\ndistinct {}\nnulls {}\nmin {}\nmax {}\n",
+                name,
+                field.distinct,
+                field.nulls,
+                serialize_value(&field.min),
+                serialize_value(&field.max),
+            ));
+            for (upper, count) in &field.histogram {
+                contents.push_str(&format!("bucket {} {}\n", count, upper));
+            }
+        }
+        contents
+    }
+
+    /// Counts a record added to the table.
+    pub fn add(&mut self, table: &TableFile, record: &Record) {
+        self.rows += 1;
+        for (i, (_, _, field)) in table.fields.iter().enumerate() {
+            self.fields
+                .entry(field.to_string())
+                .or_default()
+                .add(&record.values[i]);
+        }
+    }
+
+    /// Counts a record removed from the table.
+    pub fn remove(&mut self, table: &TableFile, record: &Record) {
+        self.rows = self.rows.saturating_sub(1);
+        for (i, (_, _, field)) in table.fields.iter().enumerate() {
+            if let Some(statistics) = self.fields.get_mut(&field.to_string()) {
+                statistics.remove(&record.values[i]);
+            }
+        }
+    }
+}
+
+/// Gathers the statistics of a table again once the whole table file was written, if the table
+/// was analyzed.
+pub fn refresh(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+) -> Result<(), io::Error> {
+    if !Catalog::load(engine, db_name)?
+        .statistics
+        .contains_key(table_name)
+    {
+        return Ok(());
+    }
+    Catalog::set_statistics(engine, db_name, table_name, Some(Statistics::build(table)))
+}
+
+/// Updates the statistics of a table, if it was analyzed, for records added to it or removed
+/// from it without writing the whole table file.
+pub fn update(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    table: &TableFile,
+    added: &[&Record],
+    removed: &[&Record],
+) -> Result<(), io::Error> {
+    let Some(mut statistics) = Catalog::load(engine, db_name)?
+        .statistics
+        .remove(table_name)
+    else {
+        return Ok(());
+    };
+    for record in added {
+        statistics.add(table, record);
+    }
+    for record in removed {
+        statistics.remove(table, record);
+    }
+    Catalog::set_statistics(engine, db_name, table_name, Some(statistics))
+}
+
+/// Gathers statistics on the records of a table, for the planner to estimate how many records
+/// the conditions of a `READ` let through.
+pub fn analyze(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    if !table_file::exists(engine, db_name, table_name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Table {} not found on {}", table_name, db_name),
+        ));
+    }
+
+    let table = TableFile::load(engine, db_name, table_name)?;
+    Catalog::set_statistics(engine, db_name, table_name, Some(Statistics::build(&table)))
+}
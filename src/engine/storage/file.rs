@@ -0,0 +1,121 @@
+use crate::engine::storage::{Entry, Lock, LockKind, Storage};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Keeps databases as directories on disk. Every write is synced before it returns.
+///
+/// Locks are taken on the files themselves, so they are seen by every process using the same
+/// directory, as well as by other engines in the same process.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileStorage;
+
+impl Storage for FileStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        fs::read(path)
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, io::Error> {
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut res = Vec::with_capacity(len);
+        file.take(len as u64).read_to_end(&mut res)?;
+        Ok(res)
+    }
+
+    fn size(&self, path: &Path) -> Result<u64, io::Error> {
+        Ok(fs::metadata(path)?.len())
+    }
+
+    /// Writes the contents next to their final location and renames them into place, so a
+    /// crash midway never leaves a half written file behind.
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        fs::rename(temp_path, path)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        let mut file = fs::OpenOptions::new().append(true).open(path)?;
+        file.write_all(contents)?;
+        file.sync_all()
+    }
+
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error> {
+        let mut file = fs::OpenOptions::new().write(true).open(path)?;
+        for (offset, contents) in patches {
+            file.seek(SeekFrom::Start(*offset))?;
+            file.write_all(contents)?;
+        }
+        file.sync_all()
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        fs::remove_dir_all(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        fs::create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        fs::create_dir_all(path)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<Entry>, io::Error> {
+        let mut res = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            res.push(Entry {
+                path: entry.path(),
+                is_dir: entry.file_type()?.is_dir(),
+            });
+        }
+        Ok(res)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn sync_dir(&self, path: &Path) -> Result<(), io::Error> {
+        fs::File::open(path)?.sync_all()
+    }
+
+    fn try_lock(&self, path: &Path, kind: LockKind) -> Result<Option<Lock>, io::Error> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let res = match kind {
+            LockKind::Shared => file.try_lock_shared(),
+            LockKind::Exclusive => file.try_lock(),
+        };
+
+        match res {
+            Ok(()) => Ok(Some(Lock::new(file))),
+            Err(fs::TryLockError::WouldBlock) => Ok(None),
+            Err(fs::TryLockError::Error(err)) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::engine::storage::{self, Entry, Lock, LockKind, Storage};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Clone)]
+enum Node {
+    Dir,
+    File(Vec<u8>),
+}
+
+/// Keeps databases in memory only, so they are gone once the storage is dropped. Useful for
+/// tests and for throwaway databases, as nothing is ever written to disk. No other process can
+/// see these databases, so locks are always granted.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found", path.display()),
+    )
+}
+
+/// Ensures the directory a new entry goes into exists.
+fn check_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> Result<(), io::Error> {
+    match path.parent() {
+        Some(parent) if !matches!(nodes.get(parent), Some(Node::Dir)) => Err(not_found(parent)),
+        _ => Ok(()),
+    }
+}
+
+/// `path` and every entry below it. Paths are ordered component by component, so these are all
+/// next to each other in the map.
+fn below(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> Vec<PathBuf> {
+    nodes
+        .range(path.to_path_buf()..)
+        .map(|(key, _)| key)
+        .take_while(|key| key.starts_with(path))
+        .cloned()
+        .collect()
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    fn nodes(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        self.nodes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn file_mut<'a>(
+        nodes: &'a mut BTreeMap<PathBuf, Node>,
+        path: &Path,
+    ) -> Result<&'a mut Vec<u8>, io::Error> {
+        match nodes.get_mut(path) {
+            Some(Node::File(contents)) => Ok(contents),
+            Some(Node::Dir) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is a directory", path.display()),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        let mut nodes = self.nodes();
+        MemoryStorage::file_mut(&mut nodes, path).map(|contents| contents.clone())
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, io::Error> {
+        let mut nodes = self.nodes();
+        MemoryStorage::file_mut(&mut nodes, path)
+            .map(|contents| storage::slice(contents, offset, len))
+    }
+
+    fn size(&self, path: &Path) -> Result<u64, io::Error> {
+        let mut nodes = self.nodes();
+        MemoryStorage::file_mut(&mut nodes, path).map(|contents| contents.len() as u64)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        check_parent(&nodes, path)?;
+        if let Some(Node::Dir) = nodes.get(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is a directory", path.display()),
+            ));
+        }
+        nodes.insert(path.to_path_buf(), Node::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        MemoryStorage::file_mut(&mut nodes, path)?.extend_from_slice(contents);
+        Ok(())
+    }
+
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        let file = MemoryStorage::file_mut(&mut nodes, path)?;
+        for (offset, contents) in patches {
+            let (start, end) = (*offset as usize, *offset as usize + contents.len());
+            if file.len() < end {
+                file.resize(end, 0);
+            }
+            file[start..end].copy_from_slice(contents);
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        check_parent(&nodes, to)?;
+        match (nodes.get(from), nodes.get(to)) {
+            (None, _) => return Err(not_found(from)),
+            (Some(Node::Dir), Some(_)) | (Some(Node::File(_)), Some(Node::Dir)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", to.display()),
+                ))
+            }
+            _ => {}
+        }
+        if to.starts_with(from) && to != from {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot move {} into itself", from.display()),
+            ));
+        }
+
+        for key in below(&nodes, from) {
+            let node = nodes.remove(&key).unwrap();
+            nodes.insert(to.join(key.strip_prefix(from).unwrap()), node);
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        MemoryStorage::file_mut(&mut nodes, path)?;
+        nodes.remove(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        if !matches!(nodes.get(path), Some(Node::Dir)) {
+            return Err(not_found(path));
+        }
+        for key in below(&nodes, path) {
+            nodes.remove(&key);
+        }
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        check_parent(&nodes, path)?;
+        if nodes.contains_key(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        nodes.insert(path.to_path_buf(), Node::Dir);
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        let mut nodes = self.nodes();
+        for ancestor in path.ancestors() {
+            match nodes.get(ancestor) {
+                Some(Node::File(_)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} is a file", ancestor.display()),
+                    ))
+                }
+                Some(Node::Dir) => {}
+                None => {
+                    nodes.insert(ancestor.to_path_buf(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<Entry>, io::Error> {
+        let nodes = self.nodes();
+        if !matches!(nodes.get(path), Some(Node::Dir)) {
+            return Err(not_found(path));
+        }
+        Ok(below(&nodes, path)
+            .into_iter()
+            .filter(|key| key.parent() == Some(path))
+            .map(|key| Entry {
+                is_dir: matches!(nodes.get(&key), Some(Node::Dir)),
+                path: key,
+            })
+            .collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.nodes().contains_key(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        matches!(self.nodes().get(path), Some(Node::Dir))
+    }
+
+    fn try_lock(&self, _path: &Path, _kind: LockKind) -> Result<Option<Lock>, io::Error> {
+        Ok(Some(Lock::new(())))
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub mod file;
+pub mod memory;
+
+pub use file::FileStorage;
+pub use memory::MemoryStorage;
+
+/// An entry of a directory, as listed by [`Storage::list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+impl Entry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockKind {
+    /// Held by any number of readers at once.
+    Shared,
+    /// Held by a single writer, while nobody else holds the lock.
+    Exclusive,
+}
+
+/// A lock taken with [`Storage::try_lock`], held until it is dropped.
+pub struct Lock {
+    _guard: Box<dyn Send + Sync>,
+}
+
+impl Lock {
+    pub fn new(guard: impl Send + Sync + 'static) -> Lock {
+        Lock {
+            _guard: Box::new(guard),
+        }
+    }
+}
+
+impl Debug for Lock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Lock")
+    }
+}
+
+/// The bytes [`Storage::read_at`] reads from `contents`, the whole of a file.
+pub(crate) fn slice(contents: &[u8], offset: u64, len: usize) -> Vec<u8> {
+    let start = contents.len().min(offset as usize);
+    contents[start..contents.len().min(start + len)].to_vec()
+}
+
+/// Reads the line of a file starting at `offset`, without the line break ending it, and only as
+/// much of the file as the line takes. A line the file ends in the middle of is not read.
+pub(crate) fn read_line(
+    storage: &dyn Storage,
+    path: &Path,
+    offset: u64,
+) -> Result<Option<String>, io::Error> {
+    let mut len = 256;
+    loop {
+        let bytes = storage.read_at(path, offset, len)?;
+        if let Some(end) = bytes.iter().position(|byte| *byte == b'\n') {
+            return Ok(String::from_utf8(bytes[..end].to_vec()).ok());
+        }
+        if bytes.len() < len {
+            return Ok(None);
+        }
+        len *= 2;
+    }
+}
+
+/// Where the engine keeps its databases. Every file and directory the engine touches goes
+/// through this, so databases can live on disk or only in memory.
+///
+/// Paths are the ones built by [`crate::engine::Engine::path`]. Backends report failures with
+/// the same error kinds as `std::fs`, such as `NotFound` for a missing file or parent directory
+/// and `AlreadyExists` for a directory created twice.
+pub trait Storage: Debug + Send + Sync {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error>;
+
+    /// Reads up to `len` bytes of a file from `offset` on, fewer when the file ends before.
+    /// Backends that can seek into a file do so instead of reading it whole.
+    fn read_at(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, io::Error> {
+        Ok(slice(&self.read(path)?, offset, len))
+    }
+
+    /// The length of a file in bytes.
+    fn size(&self, path: &Path) -> Result<u64, io::Error>;
+
+    /// Replaces the contents of a file, creating it if needed. The file either keeps its old
+    /// contents or holds all of the new ones, even if the write is interrupted.
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error>;
+
+    /// Adds `contents` to the end of an existing file.
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error>;
+
+    /// Overwrites bytes of an existing file in place, each patch starting at its offset.
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error>;
+
+    /// Moves a file or a whole directory.
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error>;
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error>;
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error>;
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error>;
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error>;
+
+    /// Lists the entries of a directory, in no particular order.
+    fn list(&self, path: &Path) -> Result<Vec<Entry>, io::Error>;
+
+    fn exists(&self, path: &Path) -> bool;
+
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Makes the entries of a directory durable, such as those of files renamed, created or
+    /// removed in it. Backends with nothing to sync leave this as is.
+    fn sync_dir(&self, _path: &Path) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    /// Takes an advisory lock on the file at `path`, creating it if needed, without waiting.
+    /// Returns `None` when the lock is held elsewhere in a way that conflicts with `kind`.
+    fn try_lock(&self, path: &Path, kind: LockKind) -> Result<Option<Lock>, io::Error>;
+
+    fn read_to_string(&self, path: &Path) -> Result<String, io::Error> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
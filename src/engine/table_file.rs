@@ -0,0 +1,651 @@
+use crate::engine::{index, statistics, storage, Engine};
+use crate::parser::constants::ON;
+use crate::parser::query::{
+    Constraint, ConstraintType, Expression, Identifier, Index, Mode, Query,
+};
+use crate::parser::query_types::type_checker;
+use crate::parser::Parser;
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+//                 DTYPE        OPTIONS          IDTFR
+pub type Field = (Identifier, Vec<Identifier>, Identifier);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Record {
+    pub deleted: bool,
+    pub values: Vec<Option<Identifier>>,
+}
+
+/// In-memory form of a `.asql` table file.
+///
+/// The first line of the file is the generation of the table, a counter bumped by every write
+/// to it, which its indexes record to tell whether they missed one, followed by the number of
+/// live records and the next number of each `INC` field, written as `field=number`. The second
+/// line is the `TABLE` statement describing the table, so the schema is read back with the
+/// regular parser. Every following line is a record, prefixed with `+` when it is live and `-`
+/// when it has been deleted but not yet compacted away. Records stay at the same offset until
+/// the whole file is written again, so indexes find them by their offset.
+#[derive(Debug, Clone)]
+pub struct TableFile {
+    pub fields: Vec<Field>,
+    pub constraints: HashMap<Identifier, Vec<Constraint>>,
+    pub modes: Vec<Mode>,
+    pub indexes: Vec<Index>,
+    pub generation: u64,
+    /// The next number of the `INC` fields, as last written. It only ever grows, so numbers
+    /// freed by deleted records are never handed out again.
+    pub counters: HashMap<Identifier, u64>,
+    pub records: Vec<Record>,
+}
+
+/// Generations are written with a fixed width, so bumping one patches it in place.
+pub fn generation(generation: u64) -> String {
+    format!("{:020}", generation)
+}
+
+/// The length of the generation and of the number of live records starting the first line of a
+/// table file, along with the space between them.
+const HEAD: usize = 41;
+
+pub fn path(engine: &Engine, db_name: &str, table_name: &str) -> PathBuf {
+    engine.path(db_name).join(format!("{}.asql", table_name))
+}
+
+pub fn exists(engine: &Engine, db_name: &str, table_name: &str) -> bool {
+    engine.storage().exists(&path(engine, db_name, table_name))
+}
+
+/// Reads the generation of a table and its number of live records from the start of its file,
+/// without reading any record.
+pub fn head(engine: &Engine, db_name: &str, table_name: &str) -> Result<(u64, usize), io::Error> {
+    let head = engine
+        .storage()
+        .read_at(&path(engine, db_name, table_name), 0, HEAD)?;
+    let head = String::from_utf8_lossy(&head);
+    head.split_once(' ')
+        .and_then(|(generation, rows)| Some((generation.parse().ok()?, rows.parse().ok()?)))
+        .ok_or(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Table file of {} is missing its generation", table_name),
+        ))
+}
+
+/// The offsets of the records in the contents of a table file, which start on its third line.
+pub fn record_offsets(contents: &[u8]) -> Vec<u64> {
+    contents
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .map(|(i, _)| i as u64 + 1)
+        .filter(|offset| *offset < contents.len() as u64)
+        .skip(1)
+        .collect()
+}
+
+/// Reads the record at `offset` of a table file, and only that record.
+pub fn fetch(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+    offset: u64,
+) -> Result<Record, io::Error> {
+    storage::read_line(engine.storage(), &path(engine, db_name, table_name), offset)?
+        .filter(|line| line.starts_with(['+', '-']))
+        .map(|line| Record::parse(&line))
+        .ok_or(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("No record at offset {} of {}", offset, table_name),
+        ))
+}
+
+/// Writes the datatype of a field along with its options, such as `UINT(2)`.
+pub fn datatype(field: &Field) -> String {
+    let (dtype, options, _) = field;
+    format!(
+        "{}({})",
+        dtype,
+        options
+            .iter()
+            .map(|option| option.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+/// Lists the names of the tables stored in a database.
+pub fn table_names(engine: &Engine, db_name: &str) -> Result<Vec<String>, io::Error> {
+    let mut res: Vec<String> = Vec::new();
+    for entry in engine.storage().list(&engine.path(db_name))? {
+        if let Some(table_name) = entry.name().strip_suffix(".asql") {
+            res.push(table_name.to_string());
+        }
+    }
+    res.sort();
+
+    Ok(res)
+}
+
+/// Lists the tables of a database holding an `FKEY` that refers to `table_name`.
+pub fn referencing(
+    engine: &Engine,
+    db_name: &str,
+    table_name: &str,
+) -> Result<Vec<(String, TableFile)>, io::Error> {
+    let mut res = Vec::new();
+    for other_name in table_names(engine, db_name)? {
+        if other_name == table_name {
+            continue;
+        }
+        let other = TableFile::load(engine, db_name, &other_name)?;
+        if other
+            .references()
+            .iter()
+            .any(|(_, referenced)| referenced == table_name)
+        {
+            res.push((other_name, other));
+        }
+    }
+
+    Ok(res)
+}
+
+/// Resolves the modes given to a table into exactly one mode from each group, in the order
+/// `[group 1, group 2]`. Groups left unspecified fall back to `current`.
+pub fn resolve_modes(modes: &[Mode], current: &[Mode]) -> Result<Vec<Mode>, io::Error> {
+    let mut resolved = vec![Mode::Fadd, Mode::Lmem];
+    for mode in current {
+        match mode {
+            Mode::Fadd | Mode::Fread => resolved[0] = *mode,
+            Mode::Fdelete | Mode::Lmem => resolved[1] = *mode,
+        }
+    }
+
+    let (mut first_group, mut second_group) = (None, None);
+    for mode in modes {
+        let (group, slot) = match mode {
+            Mode::Fadd | Mode::Fread => (&mut first_group, 0),
+            Mode::Fdelete | Mode::Lmem => (&mut second_group, 1),
+        };
+        if let Some(previous) = group {
+            if previous != mode {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Modes {} and {} cannot be used together", previous, mode),
+                ));
+            }
+        }
+        *group = Some(*mode);
+        resolved[slot] = *mode;
+    }
+
+    Ok(resolved)
+}
+
+fn split_values(line: &str) -> Vec<String> {
+    let mut res: Vec<String> = Vec::new();
+    let mut curr = String::new();
+    let mut quotes = false;
+    let mut depth = 0;
+    let mut previous = ' ';
+
+    for character in line.chars() {
+        match character {
+            '\'' if previous != '\\' => quotes = !quotes,
+            '[' if !quotes => depth += 1,
+            ']' if !quotes => depth -= 1,
+            ',' if !quotes && depth == 0 => {
+                res.push(curr.trim().to_string());
+                curr = String::new();
+                previous = character;
+                continue;
+            }
+            _ => {}
+        }
+        curr.push(character);
+        previous = character;
+    }
+    res.push(curr.trim().to_string());
+
+    res
+}
+
+/// Reads back values written by [`serialize_values`], an empty value being a missing one.
+pub fn parse_values(line: &str) -> Vec<Option<Identifier>> {
+    split_values(line)
+        .iter()
+        .map(|value| {
+            if value.is_empty() {
+                None
+            } else {
+                Some(type_checker::as_identifier(value))
+            }
+        })
+        .collect()
+}
+
+pub fn serialize_values(values: &[Option<Identifier>]) -> String {
+    values
+        .iter()
+        .map(|value| match value {
+            Some(value) => value.to_string(),
+            None => String::new(),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl Record {
+    fn parse(line: &str) -> Record {
+        let (deleted, values) = match line.split_at(1) {
+            ("+", values) => (false, values),
+            ("-", values) => (true, values),
+            _ => panic!("Malformed record in table file: {:?}", line),
+        };
+
+        Record {
+            deleted,
+            values: parse_values(values),
+        }
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "{} {}",
+            if self.deleted { "-" } else { "+" },
+            serialize_values(&self.values)
+        )
+    }
+}
+
+impl TableFile {
+    pub fn from_query(query: &Query) -> Result<TableFile, io::Error> {
+        Ok(TableFile {
+            fields: query.fields.clone(),
+            constraints: query.constraints.clone(),
+            modes: resolve_modes(&query.modes, &[])?,
+            indexes: query.indexes.clone(),
+            generation: 0,
+            counters: HashMap::new(),
+            records: Vec::new(),
+        })
+    }
+
+    pub fn load(engine: &Engine, db_name: &str, table_name: &str) -> Result<TableFile, io::Error> {
+        TableFile::parse(
+            &engine
+                .storage()
+                .read_to_string(&path(engine, db_name, table_name))?,
+        )
+    }
+
+    pub fn parse(contents: &str) -> Result<TableFile, io::Error> {
+        let mut lines = contents.lines();
+        let mut first_line = lines.next().unwrap_or_default().split(' ');
+        let generation = first_line
+            .next()
+            .and_then(|generation| generation.parse().ok())
+            .ok_or(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Table file is missing its generation",
+            ))?;
+        // The number of live records is counted again from the records
+        first_line.next();
+        let mut counters = HashMap::new();
+        for counter in first_line {
+            let (field, next) = counter
+                .split_once('=')
+                .and_then(|(field, next)| Some((field, next.parse().ok()?)))
+                .ok_or(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Malformed counter in table file: {:?}", counter),
+                ))?;
+            counters.insert(Identifier::Field(field.to_string()), next);
+        }
+        let header = lines.next().ok_or(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Table file is missing its header",
+        ))?;
+
+        let mut p = Parser::new();
+        p.set_query(header.to_string()).parse();
+
+        Ok(TableFile {
+            fields: p.query_data.fields,
+            constraints: p.query_data.constraints,
+            modes: p.query_data.modes,
+            indexes: p.query_data.indexes,
+            generation,
+            counters,
+            records: lines
+                .filter(|line| !line.is_empty())
+                .map(Record::parse)
+                .collect(),
+        })
+    }
+
+    /// Writes the whole table at once, which the storage guarantees never leaves a half written
+    /// table behind, as its next generation, then builds its indexes again.
+    pub fn save(
+        &mut self,
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+    ) -> Result<(), io::Error> {
+        self.generation += 1;
+        self.advance_counters();
+        let contents = self.serialize(db_name, table_name);
+        engine
+            .storage()
+            .write(&path(engine, db_name, table_name), contents.as_bytes())?;
+        index::refresh(
+            engine,
+            db_name,
+            table_name,
+            self,
+            &record_offsets(contents.as_bytes()),
+        )?;
+        statistics::refresh(engine, db_name, table_name, self)
+    }
+
+    pub fn serialize(&self, db_name: &str, table_name: &str) -> String {
+        let mut res = self.first_line();
+        res.push('\n');
+        res.push_str(&self.header(db_name, table_name));
+        res.push('\n');
+        for record in &self.records {
+            res.push_str(&record.serialize());
+            res.push('\n');
+        }
+        res
+    }
+
+    /// The generation, the number of live records and the counters of the table, written as the
+    /// first line of its file. Its numbers have a fixed width, so inserts and deletes patch it in
+    /// place.
+    fn first_line(&self) -> String {
+        let mut res = format!(
+            "{} {:020}",
+            generation(self.generation),
+            self.live_records().count()
+        );
+        for (i, (_, _, field)) in self.fields.iter().enumerate() {
+            if self.has_constraint(field, ConstraintType::Inc) {
+                res.push_str(&format!(" {}={:020}", field, self.counter(i)));
+            }
+        }
+        res
+    }
+
+    /// The next number of the `INC` field at `index`: the stored counter, unless a record
+    /// holds a number past it.
+    pub fn counter(&self, index: usize) -> u64 {
+        let (_, _, field) = &self.fields[index];
+        let next = self
+            .records
+            .iter()
+            .filter_map(|record| match record.values[index] {
+                Some(Identifier::IntLiteral(value)) => u64::try_from(value).ok(),
+                _ => None,
+            })
+            .max()
+            .map_or(0, |max| max + 1);
+        next.max(self.counters.get(field).copied().unwrap_or(0))
+    }
+
+    /// Moves the counters of the `INC` fields past the numbers held by the records.
+    fn advance_counters(&mut self) {
+        for i in 0..self.fields.len() {
+            let (_, _, field) = &self.fields[i];
+            if self.has_constraint(field, ConstraintType::Inc) {
+                let next = self.counter(i);
+                self.counters.insert(field.clone(), next);
+            }
+        }
+    }
+
+    /// The `TABLE` statement describing the table, written as the second line of its file.
+    pub fn header(&self, db_name: &str, table_name: &str) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let (_, _, identifier) = field;
+                format!("{} {}", datatype(field), identifier)
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let mut constrained_fields = self
+            .constraints
+            .iter()
+            .filter(|(_, constraints)| !constraints.is_empty())
+            .collect::<Vec<(&Identifier, &Vec<Constraint>)>>();
+        constrained_fields.sort_by_key(|(field, _)| *field);
+
+        let constraints = constrained_fields
+            .iter()
+            .map(|(field, constraints)| {
+                format!(
+                    "ON {} {}",
+                    field,
+                    constraints
+                        .iter()
+                        .map(|constraint| constraint.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let modes = self
+            .modes
+            .iter()
+            .map(|mode| mode.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        let indexes = self
+            .indexes
+            .iter()
+            .map(|index| index.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let mut res = format!(
+            "TABLE {} ON {} STRUCTURED ({})",
+            table_name, db_name, fields
+        );
+        if !constraints.is_empty() {
+            res.push_str(&format!(" CONSTRAINED ({})", constraints));
+        }
+        res.push_str(&format!(" MODE {}", modes));
+        if !indexes.is_empty() {
+            res.push_str(&format!(" INDEXED ({})", indexes));
+        }
+        res.push(';');
+        res
+    }
+
+    /// Finds an index of the table by name.
+    pub fn index(&self, name: &str) -> Option<&Index> {
+        self.indexes
+            .iter()
+            .find(|index| index.name == Identifier::StringLiteral(name.to_string()))
+    }
+
+    /// A copy of the table without its records.
+    pub fn schema(&self) -> TableFile {
+        TableFile {
+            fields: self.fields.clone(),
+            constraints: self.constraints.clone(),
+            modes: self.modes.clone(),
+            indexes: self.indexes.clone(),
+            generation: 0,
+            counters: HashMap::new(),
+            records: Vec::new(),
+        }
+    }
+
+    pub fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|(_, _, identifier)| *identifier == Identifier::Field(field.to_string()))
+    }
+
+    pub fn constraint(
+        &self,
+        field: &Identifier,
+        constraint_type: ConstraintType,
+    ) -> Option<&Constraint> {
+        self.constraints
+            .get(field)?
+            .iter()
+            .find(|constraint| constraint.constraint_type == constraint_type)
+    }
+
+    pub fn has_constraint(&self, field: &Identifier, constraint_type: ConstraintType) -> bool {
+        self.constraint(field, constraint_type).is_some()
+    }
+
+    pub fn pkey(&self) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|(_, _, identifier)| self.has_constraint(identifier, ConstraintType::PKey))
+    }
+
+    /// Lists the fields constrained by `FKEY (ON table)`, along with the table they refer to.
+    pub fn references(&self) -> Vec<(usize, String)> {
+        let mut res = Vec::new();
+        for (i, (_, _, field)) in self.fields.iter().enumerate() {
+            if let Some(constraint) = self.constraint(field, ConstraintType::FKey) {
+                if let Some(Expression::Identifier(table)) =
+                    constraint.options.iter().find(|option| {
+                        **option != Expression::Identifier(Identifier::Field(ON.to_string()))
+                    })
+                {
+                    res.push((i, table.to_string()));
+                }
+            }
+        }
+        res
+    }
+
+    /// Points the `FKEY`s referring to table `from` at table `to` instead. Returns whether any
+    /// `FKEY` was changed.
+    pub fn rename_references(&mut self, from: &str, to: &str) -> bool {
+        let mut changed = false;
+        for constraint in self.constraints.values_mut().flatten() {
+            if constraint.constraint_type != ConstraintType::FKey {
+                continue;
+            }
+            for option in constraint.options.iter_mut() {
+                if *option == Expression::Identifier(Identifier::Field(from.to_string())) {
+                    *option = Expression::Identifier(Identifier::Field(to.to_string()));
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn live_records(&self) -> impl Iterator<Item = &Record> {
+        self.records.iter().filter(|record| !record.deleted)
+    }
+
+    /// Reorganizes the records into the layout described by the table's modes: `FREAD` keeps
+    /// records ordered by their primary key, and `LMEM` drops deleted records instead of
+    /// leaving them marked as deleted like `FDELETE` does.
+    pub fn arrange(&mut self) {
+        if self.modes.contains(&Mode::Lmem) {
+            self.records.retain(|record| !record.deleted);
+        }
+        if self.modes.contains(&Mode::Fread) {
+            if let Some(pkey) = self.pkey() {
+                self.records
+                    .sort_by(|a, b| a.values[pkey].cmp(&b.values[pkey]));
+            }
+        }
+    }
+
+    /// Deletes the records at the given positions. `FDELETE` tables only flip the marker at the
+    /// start of each deleted record in place, along with the generation, while `LMEM` tables
+    /// are rewritten without them.
+    pub fn delete(
+        &mut self,
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        positions: &[usize],
+    ) -> Result<(), io::Error> {
+        for position in positions {
+            self.records[*position].deleted = true;
+        }
+
+        if !self.modes.contains(&Mode::Fdelete) {
+            self.arrange();
+            return self.save(engine, db_name, table_name);
+        }
+
+        let path = path(engine, db_name, table_name);
+        let contents = engine.storage().read(&path)?;
+
+        let offsets = record_offsets(&contents);
+        self.generation += 1;
+        let bumped = self.first_line();
+        let patches: Vec<(u64, &[u8])> = std::iter::once((0, bumped.as_bytes()))
+            .chain(
+                positions
+                    .iter()
+                    .map(|position| (offsets[*position], b"-".as_slice())),
+            )
+            .collect();
+        engine.storage().patch(&path, &patches)?;
+        index::remove(engine, db_name, table_name, self, positions, &offsets)?;
+        let removed: Vec<&Record> = positions
+            .iter()
+            .map(|position| &self.records[*position])
+            .collect();
+        statistics::update(engine, db_name, table_name, self, &[], &removed)
+    }
+
+    /// Stores a new record. `FADD` tables only append the record to the end of the file, once
+    /// the generation, the number of live records and the counters are bumped, while `FREAD`
+    /// tables place it according to its primary key and rewrite the file.
+    pub fn insert(
+        &mut self,
+        engine: &Engine,
+        db_name: &str,
+        table_name: &str,
+        record: Record,
+    ) -> Result<(), io::Error> {
+        match (self.modes.contains(&Mode::Fread), self.pkey()) {
+            (true, Some(pkey)) => {
+                let position = self
+                    .records
+                    .partition_point(|existing| existing.values[pkey] <= record.values[pkey]);
+                self.records.insert(position, record);
+                self.save(engine, db_name, table_name)
+            }
+            _ => {
+                // The generation goes first, along with the counts the record moves on, so a
+                // record never reaches the file unseen by the indexes
+                let path = path(engine, db_name, table_name);
+                self.generation += 1;
+                let line = format!("{}\n", record.serialize());
+                self.records.push(record);
+                self.advance_counters();
+                engine
+                    .storage()
+                    .patch(&path, &[(0, self.first_line().as_bytes())])?;
+                let offset = engine.storage().size(&path)?;
+                engine.storage().append(&path, line.as_bytes())?;
+                index::append(engine, db_name, table_name, self, offset)?;
+                let added = &self.records[self.records.len() - 1];
+                statistics::update(engine, db_name, table_name, self, &[added], &[])
+            }
+        }
+    }
+}
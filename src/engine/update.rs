@@ -0,0 +1,65 @@
+use crate::engine::table_file::TableFile;
+use crate::engine::{constraints, db_name, delete, eval, insert, table_name, Engine, Output};
+use crate::parser::Parser;
+use std::io;
+
+/// Applies the query's assignments to every record matching its conditions and returns how
+/// many records were updated. Assignments are evaluated against the values the record had
+/// before the update.
+pub fn update(engine: &Engine, parser: &Parser) -> Result<Output, io::Error> {
+    let (db_name, table_name) = (db_name(parser), table_name(parser));
+    let table = TableFile::load(engine, db_name, table_name)?;
+
+    let mut assignments: Vec<usize> = Vec::new();
+    for (field, _) in &parser.query_data.assignments {
+        assignments.push(table.field_index(&field.to_string()).ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Field {} not found on {}", field, table_name),
+        ))?);
+    }
+
+    let mut updated = table.clone();
+    let mut positions: Vec<usize> = Vec::new();
+    for (i, record) in table.records.iter().enumerate() {
+        if record.deleted || !eval::matches(&parser.query_data.conditions, &table, &record.values)?
+        {
+            continue;
+        }
+
+        for (index, (_, value)) in assignments.iter().zip(&parser.query_data.assignments) {
+            updated.records[i].values[*index] = eval::evaluate(value, &table, &record.values)?;
+        }
+        positions.push(i);
+    }
+    if positions.is_empty() {
+        return Ok(Output::Count(0));
+    }
+
+    for i in &positions {
+        constraints::check_record(&updated, &updated.records[*i].values, Some(*i))?;
+        insert::check_foreign_keys(
+            engine,
+            db_name,
+            table_name,
+            &updated,
+            &updated.records[*i].values,
+        )?;
+    }
+
+    // A record whose key changes has to be free of references, just as if it was deleted
+    if let Some(pkey) = table.pkey() {
+        let rekeyed: Vec<usize> = positions
+            .iter()
+            .copied()
+            .filter(|i| table.records[*i].values[pkey] != updated.records[*i].values[pkey])
+            .collect();
+        if !rekeyed.is_empty() {
+            delete::check_references(engine, db_name, table_name, &table, &rekeyed)?;
+        }
+    }
+
+    updated.arrange();
+    updated.save(engine, db_name, table_name)?;
+
+    Ok(Output::Count(positions.len()))
+}
@@ -0,0 +1,130 @@
+use crate::engine::lock;
+use crate::engine::storage::LockKind;
+use crate::engine::table_file::{self, TableFile};
+use crate::engine::Engine;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+/// The versions of tables being read by the statements of an engine and of its clones.
+///
+/// Every write to a table makes a new version of it, since table files are always replaced as a
+/// whole. A `READ` not going through an index pins the version current when it starts, under a
+/// short shared lock on the table, and scans it without holding the table, so writers carry on
+/// in the meantime and the read never sees their changes. Reads starting while a version is current share it, and each
+/// version is freed as soon as the last read pinning it is done.
+#[derive(Debug, Default)]
+pub struct Versions {
+    tables: Mutex<HashMap<PathBuf, Chain>>,
+}
+
+/// The versions of one table still pinned by a read, oldest first.
+#[derive(Debug, Default)]
+struct Chain {
+    next: u64,
+    versions: Vec<Weak<Version>>,
+}
+
+/// A table as it was at some point, kept for as long as a read uses it.
+#[derive(Debug)]
+pub struct Version {
+    pub number: u64,
+    pub table: TableFile,
+    contents: Vec<u8>,
+}
+
+impl Version {
+    /// The table file the version was parsed from.
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+}
+
+impl Chain {
+    /// Forgets the versions no read uses anymore.
+    fn collect(&mut self) {
+        self.versions.retain(|version| version.strong_count() > 0);
+    }
+}
+
+impl Versions {
+    fn tables(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Chain>> {
+        self.tables.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// How many versions of the table stored at `path` are pinned at the moment.
+    pub fn live(&self, path: &Path) -> usize {
+        let mut tables = self.tables();
+        let Some(chain) = tables.get_mut(path) else {
+            return 0;
+        };
+        chain.collect();
+        chain.versions.len()
+    }
+}
+
+/// Pins the current version of a table, which stays the same however the table changes until it
+/// is dropped.
+///
+/// Only committed tables are shared between reads. A table written to by the open transaction
+/// of the engine is read as the transaction left it, in a version kept out of the chain, so no
+/// other read is ever handed changes which may still be rolled back.
+pub fn pin(engine: &Engine, db_name: &str, table_name: &str) -> Result<Arc<Version>, io::Error> {
+    let path = table_file::path(engine, db_name, table_name);
+    if let Some(contents) = engine.staged(&path) {
+        let table = parse(&contents)?;
+        let number = engine.versions.tables().entry(path).or_default().next;
+        return Ok(Arc::new(Version {
+            number,
+            table,
+            contents,
+        }));
+    }
+
+    let contents = {
+        let _lock = lock::acquire(
+            engine,
+            db_name,
+            &lock::table_path(engine, db_name, table_name),
+            LockKind::Shared,
+        )?;
+        engine.storage().read(&path)?
+    };
+
+    let current = |chain: &mut Chain| {
+        chain.collect();
+        chain
+            .versions
+            .last()
+            .and_then(Weak::upgrade)
+            .filter(|version| version.contents == contents)
+    };
+    if let Some(version) = current(engine.versions.tables().entry(path.clone()).or_default()) {
+        return Ok(version);
+    }
+
+    // Parsing can take a while, so other reads are not kept waiting on it
+    let table = parse(&contents)?;
+
+    let mut tables = engine.versions.tables();
+    let chain = tables.entry(path).or_default();
+    if let Some(version) = current(chain) {
+        return Ok(version);
+    }
+    let version = Arc::new(Version {
+        number: chain.next,
+        table,
+        contents,
+    });
+    chain.next += 1;
+    chain.versions.push(Arc::downgrade(&version));
+    Ok(version)
+}
+
+fn parse(contents: &[u8]) -> Result<TableFile, io::Error> {
+    TableFile::parse(
+        std::str::from_utf8(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+    )
+}
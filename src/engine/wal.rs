@@ -0,0 +1,911 @@
+use crate::engine::hash;
+use crate::engine::storage::{self, Lock, LockKind, Storage};
+use crate::engine::{db_name, Engine};
+use crate::parser::Parser;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The name of the log kept in the directory of every database.
+pub const WAL: &str = ".wal";
+
+/// Once the log of a database grows past this many bytes it is checkpointed on its own.
+pub const CHECKPOINT_SIZE: u64 = 1 << 20;
+
+pub fn path(engine: &Engine, db_name: &str) -> PathBuf {
+    engine.path(db_name).join(WAL)
+}
+
+/// A single change to the files of a database, named relative to the directory of the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Write {
+        file: String,
+        contents: Vec<u8>,
+    },
+    /// An append records where it starts, so redoing it overwrites a row that was only partly
+    /// written instead of adding the row a second time.
+    Append {
+        file: String,
+        offset: u64,
+        contents: Vec<u8>,
+    },
+    Patch {
+        file: String,
+        patches: Vec<(u64, Vec<u8>)>,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    Remove {
+        file: String,
+    },
+    CreateDir {
+        dir: String,
+    },
+    RemoveDir {
+        dir: String,
+    },
+    /// The changes of a transaction, which are redone either all together or not at all.
+    Commit {
+        entries: Vec<Entry>,
+    },
+}
+
+/// Splits the fields of an entry, each written as its length, a `:` and its bytes.
+fn fields(mut payload: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut res = Vec::new();
+    while !payload.is_empty() {
+        let colon = payload.iter().position(|byte| *byte == b':')?;
+        let len: usize = std::str::from_utf8(&payload[..colon]).ok()?.parse().ok()?;
+        let rest = &payload[colon + 1..];
+        if rest.len() < len {
+            return None;
+        }
+        res.push(&rest[..len]);
+        payload = &rest[len..];
+    }
+    Some(res)
+}
+
+fn text(field: &[u8]) -> Option<String> {
+    String::from_utf8(field.to_vec()).ok()
+}
+
+fn number(field: &[u8]) -> Option<u64> {
+    std::str::from_utf8(field).ok()?.parse().ok()
+}
+
+impl Entry {
+    /// Writes the entry as a `hash length` line followed by its fields. The hash covers the
+    /// fields, so an entry cut short by a crash is recognised when the log is read back.
+    pub fn encode(&self) -> Vec<u8> {
+        let payload = self.payload();
+        let mut res = format!("{} {}\n", hash::hex(&payload), payload.len()).into_bytes();
+        res.extend_from_slice(&payload);
+        res
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        let fields: Vec<Vec<u8>> = match self {
+            Entry::Write { file, contents } => {
+                vec![b"write".to_vec(), file.clone().into(), contents.clone()]
+            }
+            Entry::Append {
+                file,
+                offset,
+                contents,
+            } => vec![
+                b"append".to_vec(),
+                file.clone().into(),
+                offset.to_string().into(),
+                contents.clone(),
+            ],
+            Entry::Patch { file, patches } => {
+                let mut fields = vec![b"patch".to_vec(), file.clone().into()];
+                for (offset, contents) in patches {
+                    fields.push(offset.to_string().into());
+                    fields.push(contents.clone());
+                }
+                fields
+            }
+            Entry::Rename { from, to } => {
+                vec![b"rename".to_vec(), from.clone().into(), to.clone().into()]
+            }
+            Entry::Remove { file } => vec![b"remove".to_vec(), file.clone().into()],
+            Entry::CreateDir { dir } => vec![b"create_dir".to_vec(), dir.clone().into()],
+            Entry::RemoveDir { dir } => vec![b"remove_dir".to_vec(), dir.clone().into()],
+            Entry::Commit { entries } => std::iter::once(b"commit".to_vec())
+                .chain(entries.iter().map(Entry::payload))
+                .collect(),
+        };
+
+        let mut payload = Vec::new();
+        for field in fields {
+            payload.extend_from_slice(format!("{}:", field.len()).as_bytes());
+            payload.extend_from_slice(&field);
+        }
+        payload
+    }
+
+    fn decode(payload: &[u8]) -> Option<Entry> {
+        let fields = fields(payload)?;
+        let (kind, fields) = fields.split_first()?;
+        Some(match (*kind, fields) {
+            (b"write", [file, contents]) => Entry::Write {
+                file: text(file)?,
+                contents: contents.to_vec(),
+            },
+            (b"append", [file, offset, contents]) => Entry::Append {
+                file: text(file)?,
+                offset: number(offset)?,
+                contents: contents.to_vec(),
+            },
+            (b"patch", [file, patches @ ..]) if patches.len() % 2 == 0 => Entry::Patch {
+                file: text(file)?,
+                patches: patches
+                    .chunks(2)
+                    .map(|patch| Some((number(patch[0])?, patch[1].to_vec())))
+                    .collect::<Option<Vec<(u64, Vec<u8>)>>>()?,
+            },
+            (b"rename", [from, to]) => Entry::Rename {
+                from: text(from)?,
+                to: text(to)?,
+            },
+            (b"remove", [file]) => Entry::Remove { file: text(file)? },
+            (b"create_dir", [dir]) => Entry::CreateDir { dir: text(dir)? },
+            (b"remove_dir", [dir]) => Entry::RemoveDir { dir: text(dir)? },
+            (b"commit", entries) => Entry::Commit {
+                entries: entries
+                    .iter()
+                    .map(|entry| Entry::decode(entry))
+                    .collect::<Option<Vec<Entry>>>()?,
+            },
+            _ => return None,
+        })
+    }
+
+    /// The files and directories changed by the entry, named relative to its database.
+    fn paths(&self) -> Vec<&str> {
+        match self {
+            Entry::Write { file, .. }
+            | Entry::Append { file, .. }
+            | Entry::Patch { file, .. }
+            | Entry::Remove { file } => vec![file],
+            Entry::Rename { from, to } => vec![from, to],
+            Entry::CreateDir { dir } | Entry::RemoveDir { dir } => vec![dir],
+            Entry::Commit { entries } => entries.iter().flat_map(Entry::paths).collect(),
+        }
+    }
+
+    /// Reads back the entries of a log. An entry left incomplete by a crash can only be the last
+    /// one, and was never applied, so reading stops there.
+    pub fn decode_all(mut log: &[u8]) -> Vec<Entry> {
+        let mut res = Vec::new();
+        while let Some(newline) = log.iter().position(|byte| *byte == b'\n') {
+            let header = String::from_utf8_lossy(&log[..newline]).to_string();
+            let Some((expected, len)) = header.split_once(' ') else {
+                break;
+            };
+            let Ok(len) = len.parse::<usize>() else {
+                break;
+            };
+            let rest = &log[newline + 1..];
+            if rest.len() < len || hash::hex(&rest[..len]) != expected {
+                break;
+            }
+            let Some(entry) = Entry::decode(&rest[..len]) else {
+                break;
+            };
+            res.push(entry);
+            log = &rest[len..];
+        }
+        res
+    }
+}
+
+/// The changes made by an open transaction. They are held here, where the statements of the
+/// transaction read them back, and only reach the log and the files underneath on commit.
+///
+/// Each file the transaction changed maps to its new contents, or to `None` once removed, so a
+/// file moved inside the transaction is removed from its old path and written to its new one.
+#[derive(Debug, Default)]
+struct Transaction {
+    db_path: Option<PathBuf>,
+    files: BTreeMap<PathBuf, Option<Vec<u8>>>,
+    savepoints: Vec<Savepoint>,
+}
+
+/// The changes a transaction had made when a savepoint was set, to go back to them later.
+#[derive(Debug)]
+struct Savepoint {
+    name: String,
+    db_path: Option<PathBuf>,
+    files: BTreeMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl Transaction {
+    /// The position of the latest savepoint called `name`.
+    fn savepoint(&self, name: &str) -> Result<usize, io::Error> {
+        self.savepoints
+            .iter()
+            .rposition(|savepoint| savepoint.name == name)
+            .ok_or(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Savepoint {} not found", name),
+            ))
+    }
+}
+
+fn in_transaction(operation: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Cannot {} inside a transaction", operation),
+    )
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found", path.display()),
+    )
+}
+
+fn no_transaction() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "No transaction is open")
+}
+
+/// A storage logging every change made to the files of a database before making it.
+///
+/// Each change is appended to the `.wal` file of its database and synced first, then applied
+/// to the storage underneath. If the engine stops halfway through a change, such as in the
+/// middle of appending a row, the log still holds the whole change, and it is redone the next
+/// time an engine is opened on the same root. Changes outside of a database, such as creating
+/// or dropping the database directory itself, are single operations and are not logged.
+///
+/// Every handle of an engine goes through its own [`Session`] of the storage, which holds back
+/// the changes of its transaction until they are logged as a single entry on commit.
+#[derive(Debug)]
+pub struct WalStorage {
+    root: PathBuf,
+    inner: Box<dyn Storage>,
+    /// Held while a change is logged and applied, so changes made by threads sharing the
+    /// storage reach the log, and the files, one at a time.
+    serial: Mutex<()>,
+}
+
+impl WalStorage {
+    pub fn new(root: &Path, inner: Box<dyn Storage>) -> WalStorage {
+        WalStorage {
+            root: root.to_path_buf(),
+            inner,
+            serial: Mutex::new(()),
+        }
+    }
+
+    fn serialize(&self) -> MutexGuard<'_, ()> {
+        self.serial
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Splits a path into the directory of the database it belongs to and its name within that
+    /// directory.
+    fn locate(&self, path: &Path) -> Option<(PathBuf, String)> {
+        let mut components = path.strip_prefix(&self.root).ok()?.components();
+        let db_path = self.root.join(components.next()?);
+        let file = components.as_path().to_string_lossy().to_string();
+        (!file.is_empty() && file != WAL && self.inner.is_dir(&db_path)).then_some((db_path, file))
+    }
+
+    /// Appends an entry to the log of a database. A log created for it is only in place once
+    /// the directory holding it is synced, so the directory is synced before the change it logs
+    /// is applied.
+    fn log(&self, db_path: &Path, entry: &Entry) -> Result<(), io::Error> {
+        let wal = db_path.join(WAL);
+        if self.inner.exists(&wal) {
+            self.inner.append(&wal, &entry.encode())
+        } else {
+            self.inner.write(&wal, &entry.encode())?;
+            self.inner.sync_dir(db_path)
+        }
+    }
+
+    /// Logs the change made to `path` by `apply`, then applies it.
+    fn run(
+        &self,
+        path: &Path,
+        entry: impl FnOnce(String) -> Result<Entry, io::Error>,
+        apply: impl FnOnce(&dyn Storage) -> Result<(), io::Error>,
+    ) -> Result<(), io::Error> {
+        let _serial = self.serialize();
+        let Some((db_path, file)) = self.locate(path) else {
+            return apply(self.inner.as_ref());
+        };
+
+        self.log(&db_path, &entry(file)?)?;
+        apply(self.inner.as_ref())
+    }
+
+    /// Logs the files changed by a transaction as one entry, then changes them.
+    fn commit(
+        &self,
+        db_path: &Path,
+        files: BTreeMap<PathBuf, Option<Vec<u8>>>,
+    ) -> Result<(), io::Error> {
+        let _serial = self.serialize();
+        let mut entries = Vec::new();
+        for (path, contents) in files {
+            let file = path
+                .strip_prefix(db_path)
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            match contents {
+                Some(contents) => entries.push(Entry::Write { file, contents }),
+                None if self.inner.exists(&path) => entries.push(Entry::Remove { file }),
+                // The file was only ever written inside the transaction
+                None => {}
+            }
+        }
+
+        let entry = Entry::Commit { entries };
+        self.log(db_path, &entry)?;
+        self.redo(db_path, entry)
+    }
+
+    fn redo(&self, db_path: &Path, entry: Entry) -> Result<(), io::Error> {
+        let inner = self.inner.as_ref();
+        let res = match entry {
+            Entry::Write { file, contents } => inner.write(&db_path.join(file), &contents),
+            Entry::Append {
+                file,
+                offset,
+                contents,
+            } => inner.patch(&db_path.join(file), &[(offset, &contents)]),
+            Entry::Patch { file, patches } => inner.patch(
+                &db_path.join(file),
+                &patches
+                    .iter()
+                    .map(|(offset, contents)| (*offset, contents.as_slice()))
+                    .collect::<Vec<(u64, &[u8])>>(),
+            ),
+            Entry::Rename { from, to } => inner.rename(&db_path.join(from), &db_path.join(to)),
+            Entry::Remove { file } => inner.remove_file(&db_path.join(file)),
+            Entry::CreateDir { dir } => inner.create_dir_all(&db_path.join(dir)),
+            Entry::RemoveDir { dir } => inner.remove_dir_all(&db_path.join(dir)),
+            Entry::Commit { entries } => entries
+                .into_iter()
+                .try_for_each(|entry| self.redo(db_path, entry)),
+        };
+
+        // A change finding its file or directory gone, or already in place, was overtaken by
+        // a later change in the log
+        match res {
+            Err(err)
+                if err.kind() == io::ErrorKind::NotFound
+                    || err.kind() == io::ErrorKind::AlreadyExists =>
+            {
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Redoes every change in the log of a database, in order, then checkpoints it.
+    ///
+    /// Changes which were already applied before the engine stopped are applied again. Doing so
+    /// in order brings every file back to the state it had after the last change.
+    pub fn replay(&self, db_path: &Path) -> Result<(), io::Error> {
+        let _serial = self.serialize();
+        let wal = db_path.join(WAL);
+        if !self.inner.exists(&wal) {
+            return Ok(());
+        }
+
+        let entries = Entry::decode_all(&self.inner.read(&wal)?);
+        for entry in &entries {
+            self.redo(db_path, entry.clone())?;
+        }
+        self.sync_dirs(db_path, &entries)?;
+        self.inner.remove_file(&wal)
+    }
+
+    /// Empties the log of a database. Every change in the log has already been applied, and the
+    /// files it wrote synced, by the time it is checkpointed. The directories holding them are
+    /// synced first, so files moved, created or removed by the log are not lost either.
+    pub fn checkpoint(&self, db_path: &Path) -> Result<(), io::Error> {
+        let _serial = self.serialize();
+        let wal = db_path.join(WAL);
+        if self.inner.exists(&wal) {
+            self.sync_dirs(db_path, &Entry::decode_all(&self.inner.read(&wal)?))?;
+            self.inner.remove_file(&wal)?;
+        }
+        Ok(())
+    }
+
+    /// Syncs every directory whose entries were changed by `entries`.
+    fn sync_dirs(&self, db_path: &Path, entries: &[Entry]) -> Result<(), io::Error> {
+        let dirs: BTreeSet<PathBuf> = entries
+            .iter()
+            .flat_map(Entry::paths)
+            .filter_map(|path| db_path.join(path).parent().map(Path::to_path_buf))
+            .collect();
+        for dir in dirs {
+            if self.inner.is_dir(&dir) {
+                self.inner.sync_dir(&dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the log of a database has grown past [`CHECKPOINT_SIZE`].
+    pub fn needs_checkpoint(&self, db_path: &Path) -> bool {
+        self.inner
+            .size(&db_path.join(WAL))
+            .is_ok_and(|size| size > CHECKPOINT_SIZE)
+    }
+}
+
+impl Storage for WalStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        self.inner.read(path)
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, io::Error> {
+        self.inner.read_at(path, offset, len)
+    }
+
+    fn size(&self, path: &Path) -> Result<u64, io::Error> {
+        self.inner.size(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |file| {
+                Ok(Entry::Write {
+                    file,
+                    contents: contents.to_vec(),
+                })
+            },
+            |inner| inner.write(path, contents),
+        )
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |file| {
+                Ok(Entry::Append {
+                    file,
+                    offset: self.inner.size(path)?,
+                    contents: contents.to_vec(),
+                })
+            },
+            |inner| inner.append(path, contents),
+        )
+    }
+
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |file| {
+                Ok(Entry::Patch {
+                    file,
+                    patches: patches
+                        .iter()
+                        .map(|(offset, contents)| (*offset, contents.to_vec()))
+                        .collect(),
+                })
+            },
+            |inner| inner.patch(path, patches),
+        )
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        // Both ends need to be in the same database for the move to be redone from its log
+        match (self.locate(from), self.locate(to)) {
+            (Some((from_db, _)), Some((to_db, to_file))) if from_db == to_db => self.run(
+                from,
+                |from_file| {
+                    Ok(Entry::Rename {
+                        from: from_file,
+                        to: to_file,
+                    })
+                },
+                |inner| inner.rename(from, to),
+            ),
+            _ => self.inner.rename(from, to),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |file| Ok(Entry::Remove { file }),
+            |inner| inner.remove_file(path),
+        )
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |dir| Ok(Entry::RemoveDir { dir }),
+            |inner| inner.remove_dir_all(path),
+        )
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |dir| Ok(Entry::CreateDir { dir }),
+            |inner| inner.create_dir(path),
+        )
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        self.run(
+            path,
+            |dir| Ok(Entry::CreateDir { dir }),
+            |inner| inner.create_dir_all(path),
+        )
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<storage::Entry>, io::Error> {
+        self.inner.list(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.inner.is_dir(path)
+    }
+
+    fn sync_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.sync_dir(path)
+    }
+
+    fn try_lock(&self, path: &Path, kind: LockKind) -> Result<Option<Lock>, io::Error> {
+        self.inner.try_lock(path, kind)
+    }
+}
+
+/// The storage of an engine as one of its handles sees it, see [`Engine`]: the [`WalStorage`]
+/// shared by every handle, along with the changes of the transaction open on this handle, if
+/// any. Those changes are held here, where only the statements run on the handle read them
+/// back, and only reach the log and the files underneath on commit.
+#[derive(Debug)]
+pub struct Session {
+    wal: Arc<WalStorage>,
+    transaction: Mutex<Option<Transaction>>,
+}
+
+impl Session {
+    pub fn new(wal: Arc<WalStorage>) -> Session {
+        Session {
+            wal,
+            transaction: Mutex::new(None),
+        }
+    }
+
+    pub fn wal(&self) -> &Arc<WalStorage> {
+        &self.wal
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Transaction>> {
+        self.transaction
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Ties a transaction to the database holding `path`, which every file it changes has to
+    /// belong to.
+    fn bind(&self, transaction: &mut Transaction, path: &Path) -> Result<(), io::Error> {
+        let Some((db_path, _)) = self.wal.locate(path) else {
+            return Err(in_transaction("change files outside of a database"));
+        };
+        match &transaction.db_path {
+            Some(bound) if *bound != db_path => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A transaction can only change the tables of a single database",
+            )),
+            Some(_) => Ok(()),
+            None => {
+                transaction.db_path = Some(db_path);
+                Ok(())
+            }
+        }
+    }
+
+    /// The contents a transaction gives to a file, read from the storage underneath the first
+    /// time the transaction touches it.
+    fn stage<'a>(
+        &self,
+        transaction: &'a mut Transaction,
+        path: &Path,
+    ) -> Result<&'a mut Vec<u8>, io::Error> {
+        self.bind(transaction, path)?;
+        if !transaction.files.contains_key(path) {
+            let contents = if self.wal.inner.exists(path) {
+                self.wal.inner.read(path)?
+            } else {
+                Vec::new()
+            };
+            transaction.files.insert(path.to_path_buf(), Some(contents));
+        }
+        Ok(transaction
+            .files
+            .get_mut(path)
+            .unwrap()
+            .get_or_insert_with(Vec::new))
+    }
+
+    /// The contents the open transaction gives to a file, if it has changed it.
+    pub fn staged(&self, path: &Path) -> Option<Vec<u8>> {
+        self.lock()
+            .as_ref()
+            .and_then(|transaction| transaction.files.get(path).cloned())
+            .flatten()
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// The directory of the database the open transaction writes to, once it has written.
+    pub fn transaction_database(&self) -> Option<PathBuf> {
+        self.lock()
+            .as_ref()
+            .and_then(|transaction| transaction.db_path.clone())
+    }
+
+    pub fn begin(&self) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        if transaction.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A transaction is already open",
+            ));
+        }
+        *transaction = Some(Transaction::default());
+        Ok(())
+    }
+
+    /// Logs every file changed by the open transaction as one entry, then writes them.
+    pub fn commit(&self) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let Transaction { db_path, files, .. } = transaction.take().ok_or_else(no_transaction)?;
+        match db_path {
+            Some(db_path) => self.wal.commit(&db_path, files),
+            None => Ok(()),
+        }
+    }
+
+    /// Discards every change made by the open transaction.
+    pub fn rollback(&self) -> Result<(), io::Error> {
+        self.lock().take().map(|_| ()).ok_or_else(no_transaction)
+    }
+
+    /// Remembers the changes made so far by the open transaction under `name`. A savepoint
+    /// reusing the name of an earlier one hides it until it is released.
+    pub fn savepoint(&self, name: &str) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let transaction = transaction.as_mut().ok_or_else(no_transaction)?;
+        let savepoint = Savepoint {
+            name: name.to_string(),
+            db_path: transaction.db_path.clone(),
+            files: transaction.files.clone(),
+        };
+        transaction.savepoints.push(savepoint);
+        Ok(())
+    }
+
+    /// Discards the changes made by the open transaction since the savepoint `name`. The
+    /// savepoint itself is kept, while those set after it are dropped.
+    pub fn rollback_to(&self, name: &str) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let transaction = transaction.as_mut().ok_or_else(no_transaction)?;
+        let position = transaction.savepoint(name)?;
+        transaction.savepoints.truncate(position + 1);
+
+        let savepoint = &transaction.savepoints[position];
+        transaction.db_path = savepoint.db_path.clone();
+        transaction.files = savepoint.files.clone();
+        Ok(())
+    }
+
+    /// Forgets the savepoint `name` and those set after it, keeping the changes made since.
+    pub fn release(&self, name: &str) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let transaction = transaction.as_mut().ok_or_else(no_transaction)?;
+        let position = transaction.savepoint(name)?;
+        transaction.savepoints.truncate(position);
+        Ok(())
+    }
+}
+
+impl Storage for Session {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        match self.lock().as_ref().and_then(|t| t.files.get(path)) {
+            Some(Some(contents)) => Ok(contents.clone()),
+            Some(None) => Err(not_found(path)),
+            None => self.wal.read(path),
+        }
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, io::Error> {
+        match self.lock().as_ref().and_then(|t| t.files.get(path)) {
+            Some(Some(contents)) => Ok(storage::slice(contents, offset, len)),
+            Some(None) => Err(not_found(path)),
+            None => self.wal.read_at(path, offset, len),
+        }
+    }
+
+    fn size(&self, path: &Path) -> Result<u64, io::Error> {
+        match self.lock().as_ref().and_then(|t| t.files.get(path)) {
+            Some(Some(contents)) => Ok(contents.len() as u64),
+            Some(None) => Err(not_found(path)),
+            None => self.wal.size(path),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        match transaction.as_mut() {
+            Some(transaction) => {
+                *self.stage(transaction, path)? = contents.to_vec();
+                Ok(())
+            }
+            None => self.wal.write(path, contents),
+        }
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let Some(transaction) = transaction.as_mut() else {
+            return self.wal.append(path, contents);
+        };
+
+        let exists = match transaction.files.get(path) {
+            Some(contents) => contents.is_some(),
+            None => self.wal.exists(path),
+        };
+        if !exists {
+            return Err(not_found(path));
+        }
+        self.stage(transaction, path)?.extend_from_slice(contents);
+        Ok(())
+    }
+
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let Some(transaction) = transaction.as_mut() else {
+            return self.wal.patch(path, patches);
+        };
+
+        let file = self.stage(transaction, path)?;
+        for (offset, contents) in patches {
+            let (start, end) = (*offset as usize, *offset as usize + contents.len());
+            if file.len() < end {
+                file.resize(end, 0);
+            }
+            file[start..end].copy_from_slice(contents);
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let Some(transaction) = transaction.as_mut() else {
+            return self.wal.rename(from, to);
+        };
+
+        self.bind(transaction, from)?;
+        self.bind(transaction, to)?;
+        let contents = match transaction.files.get(from) {
+            Some(Some(contents)) => contents.clone(),
+            Some(None) => return Err(not_found(from)),
+            None => self.wal.read(from)?,
+        };
+        transaction.files.insert(from.to_path_buf(), None);
+        transaction.files.insert(to.to_path_buf(), Some(contents));
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error> {
+        let mut transaction = self.lock();
+        let Some(transaction) = transaction.as_mut() else {
+            return self.wal.remove_file(path);
+        };
+
+        self.bind(transaction, path)?;
+        let exists = match transaction.files.get(path) {
+            Some(contents) => contents.is_some(),
+            None => self.wal.exists(path),
+        };
+        if !exists {
+            return Err(not_found(path));
+        }
+        transaction.files.insert(path.to_path_buf(), None);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        if self.in_transaction() {
+            return Err(in_transaction("remove directories"));
+        }
+        self.wal.remove_dir_all(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        if self.in_transaction() {
+            return Err(in_transaction("create directories"));
+        }
+        self.wal.create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        if self.in_transaction() {
+            return Err(in_transaction("create directories"));
+        }
+        self.wal.create_dir_all(path)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<storage::Entry>, io::Error> {
+        let mut entries = self.wal.list(path)?;
+        let transaction = self.lock();
+        let Some(transaction) = transaction.as_ref() else {
+            return Ok(entries);
+        };
+
+        entries.retain(|entry| !matches!(transaction.files.get(&entry.path), Some(None)));
+        for (file, contents) in &transaction.files {
+            if contents.is_some()
+                && file.parent() == Some(path)
+                && !entries.iter().any(|entry| entry.path == *file)
+            {
+                entries.push(storage::Entry {
+                    path: file.clone(),
+                    is_dir: false,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        match self.lock().as_ref().and_then(|t| t.files.get(path)) {
+            Some(contents) => contents.is_some(),
+            None => self.wal.exists(path),
+        }
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.wal.is_dir(path)
+    }
+
+    fn sync_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.wal.sync_dir(path)
+    }
+
+    fn try_lock(&self, path: &Path, kind: LockKind) -> Result<Option<Lock>, io::Error> {
+        self.wal.try_lock(path, kind)
+    }
+}
+
+/// Checkpoints the log of a database.
+pub fn checkpoint(engine: &Engine, parser: &Parser) -> Result<(), io::Error> {
+    let db_name = db_name(parser);
+    if !engine.storage().is_dir(&engine.path(db_name)) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Database {} not found", db_name),
+        ));
+    }
+    engine.checkpoint(db_name)
+}
@@ -1,7 +1,31 @@
+pub const DATABASES: &str = "DATABASES";
 pub const DATABASE: &str = "DATABASE";
+pub const TABLES: &str = "TABLES";
 pub const TABLE: &str = "TABLE";
 pub const INSERT: &str = "INSERT";
 pub const READ: &str = "READ";
+pub const EXPLAIN: &str = "EXPLAIN";
+pub const DELETE: &str = "DELETE";
+pub const UPDATE: &str = "UPDATE";
+pub const DROP: &str = "DROP";
+pub const RENAME: &str = "RENAME";
+pub const ALTER: &str = "ALTER";
+pub const SNAPSHOTS: &str = "SNAPSHOTS";
+pub const SNAPSHOT: &str = "SNAPSHOT";
+pub const RESTORE: &str = "RESTORE";
+pub const DIFF: &str = "DIFF";
+pub const SHOW: &str = "SHOW";
+pub const DESCRIBE: &str = "DESCRIBE";
+pub const CHECKPOINT: &str = "CHECKPOINT";
+pub const BEGIN: &str = "BEGIN";
+pub const COMMIT: &str = "COMMIT";
+pub const ROLLBACK: &str = "ROLLBACK";
+pub const SAVEPOINT: &str = "SAVEPOINT";
+pub const RELEASE: &str = "RELEASE";
+pub const INDEX: &str = "INDEX";
+pub const ANALYZE: &str = "ANALYZE";
+pub const BTREE: &str = "BTREE";
+pub const HASH: &str = "HASH";
 
 pub const ON: &str = "ON";
 pub const STRUCTURED: &str = "STRUCTURED";
@@ -11,7 +35,17 @@ pub const FREAD: &str = "FREAD";
 pub const FDELETE: &str = "FDELETE";
 pub const LMEM: &str = "LMEM";
 pub const CONSTRAINED: &str = "CONSTRAINED";
+pub const INDEXED: &str = "INDEXED";
 pub const WHERE: &str = "WHERE";
+pub const SET: &str = "SET";
+pub const INTO: &str = "INTO";
+pub const MESSAGE: &str = "MESSAGE";
+pub const AT: &str = "AT";
+pub const IF: &str = "IF";
+pub const CASCADE: &str = "CASCADE";
+pub const ALTER_ADD: &str = "ADD";
+pub const RETYPE: &str = "RETYPE";
+pub const TO: &str = "TO";
 
 pub const EXISTS: &str = "EXISTS";
 pub const UNIQUE: &str = "UNIQUE";
@@ -57,10 +91,35 @@ pub const KEYWORDS: &[&str] = &[
     LT,
     COMMA,
     SEMICOLON,
+    DATABASES,
     DATABASE,
+    TABLES,
     TABLE,
     INSERT,
     READ,
+    EXPLAIN,
+    DELETE,
+    UPDATE,
+    DROP,
+    RENAME,
+    ALTER,
+    SNAPSHOTS,
+    SNAPSHOT,
+    RESTORE,
+    DIFF,
+    SHOW,
+    DESCRIBE,
+    CHECKPOINT,
+    BEGIN,
+    COMMIT,
+    ROLLBACK,
+    SAVEPOINT,
+    RELEASE,
+    INDEXED,
+    INDEX,
+    ANALYZE,
+    BTREE,
+    HASH,
     ON,
     STRUCTURED,
     MODE,
@@ -70,6 +129,15 @@ pub const KEYWORDS: &[&str] = &[
     LMEM,
     CONSTRAINED,
     WHERE,
+    SET,
+    INTO,
+    MESSAGE,
+    AT,
+    IF,
+    CASCADE,
+    ALTER_ADD,
+    RETYPE,
+    TO,
     EXISTS,
     UNIQUE,
     PKEY,
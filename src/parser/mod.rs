@@ -1,5 +1,7 @@
+use query::Alteration;
 use query::Expression;
 use query::Identifier;
+use query::IndexKind;
 use query::QueryType;
 use regex::Regex;
 use std::cmp::min;
@@ -11,9 +13,21 @@ pub mod query_types;
 pub mod step;
 
 use constants::*;
+use query_types::altering;
+use query_types::analyzing;
+use query_types::checkpointing;
+use query_types::deleting;
+use query_types::describing;
+use query_types::dropping;
+use query_types::indexing;
 use query_types::inserting;
 use query_types::reading;
+use query_types::renaming;
+use query_types::showing;
+use query_types::snapshots;
 use query_types::table_creation;
+use query_types::transactions;
+use query_types::updating;
 use step::Step;
 
 #[derive(Debug)]
@@ -43,11 +57,26 @@ impl Parser {
                 modes: Vec::new(),
                 constraints: HashMap::new(),
                 curr_constraint: Identifier::StringLiteral(String::new()),
+                indexes: Vec::new(),
                 inserted_value: Vec::new(),
                 inserted_field: Vec::new(),
                 read_fields: Vec::new(),
                 conditions: Expression::None,
                 conditions_buf: Expression::None,
+                explain: false,
+                assignments: Vec::new(),
+                alteration: Alteration::None,
+                altered_field: Identifier::Field(String::new()),
+                if_exists: false,
+                cascade: false,
+                snapshot_name: Identifier::StringLiteral(String::new()),
+                other_snapshot_name: Identifier::StringLiteral(String::new()),
+                snapshot_message: Identifier::StringLiteral(String::new()),
+                new_name: Identifier::StringLiteral(String::new()),
+                savepoint_name: Identifier::StringLiteral(String::new()),
+                index_name: Identifier::StringLiteral(String::new()),
+                index_fields: Vec::new(),
+                index_kind: IndexKind::default(),
             },
             step: Step::Start,
         }
@@ -77,8 +106,15 @@ impl Parser {
     }
 
     pub fn parse(&mut self) {
-        println!("{} {}", self.query, self.location);
-        let (mut curr, func): (Step, fn(&mut Parser, Step) -> Step) = match self.pop().as_str() {
+        let mut token = self.pop();
+        if token == EXPLAIN {
+            // Only reads can be explained
+            self.query_data.explain = true;
+            token = self.pop();
+            self.ensure_token(token.clone(), READ);
+        }
+
+        let (mut curr, func): (Step, fn(&mut Parser, Step) -> Step) = match token.as_str() {
             DATABASE => {
                 self.query_data._type = QueryType::DatabaseCreation;
                 let identifier = self.pop_identifier();
@@ -121,6 +157,89 @@ impl Parser {
                 }
                 (Step::ReadFieldIdentifier, reading::reading)
             }
+            DELETE => {
+                self.query_data._type = QueryType::Delete;
+                {
+                    let token = self.pop();
+                    self.ensure_token(token, ON);
+                }
+                (Step::DeleteTableName, deleting::deleting)
+            }
+            UPDATE => {
+                self.query_data._type = QueryType::Update;
+                {
+                    let token = self.pop();
+                    self.ensure_token(token, ON);
+                }
+                (Step::UpdateTableName, updating::updating)
+            }
+            DROP => (Step::DropTarget, dropping::dropping),
+            RENAME => (Step::RenameTarget, renaming::renaming),
+            ALTER => {
+                self.query_data._type = QueryType::Alter;
+                (Step::AlterTableName, altering::altering)
+            }
+            SNAPSHOT => {
+                self.query_data._type = QueryType::Snapshot;
+                (Step::SnapshotName, snapshots::snapshots)
+            }
+            SNAPSHOTS => {
+                self.query_data._type = QueryType::SnapshotList;
+                {
+                    let token = self.pop();
+                    self.ensure_token(token, ON);
+                }
+                (Step::SnapshotListDatabase, snapshots::snapshots)
+            }
+            RESTORE => {
+                self.query_data._type = QueryType::Restore;
+                (Step::SnapshotName, snapshots::snapshots)
+            }
+            DIFF => {
+                self.query_data._type = QueryType::Diff;
+                (Step::DiffFrom, snapshots::snapshots)
+            }
+            SHOW => (Step::ShowTarget, showing::showing),
+            DESCRIBE => {
+                self.query_data._type = QueryType::Describe;
+                (Step::DescribeTableName, describing::describing)
+            }
+            CHECKPOINT => {
+                self.query_data._type = QueryType::Checkpoint;
+                {
+                    let token = self.pop();
+                    self.ensure_token(token, ON);
+                }
+                (Step::CheckpointDatabase, checkpointing::checkpointing)
+            }
+            BEGIN => {
+                self.query_data._type = QueryType::Begin;
+                (Step::TransactionEnd, transactions::transactions)
+            }
+            COMMIT => {
+                self.query_data._type = QueryType::Commit;
+                (Step::TransactionEnd, transactions::transactions)
+            }
+            ROLLBACK => {
+                self.query_data._type = QueryType::Rollback;
+                (Step::TransactionEnd, transactions::transactions)
+            }
+            SAVEPOINT => {
+                self.query_data._type = QueryType::Savepoint;
+                (Step::SavepointName, transactions::transactions)
+            }
+            RELEASE => {
+                self.query_data._type = QueryType::Release;
+                (Step::SavepointName, transactions::transactions)
+            }
+            INDEX => {
+                self.query_data._type = QueryType::IndexCreation;
+                (Step::IndexName, indexing::indexing)
+            }
+            ANALYZE => {
+                self.query_data._type = QueryType::Analyze;
+                (Step::AnalyzeTableName, analyzing::analyzing)
+            }
             found => {
                 panic!("Invalid token found {:?}", found);
             }
@@ -172,22 +291,28 @@ impl Parser {
             let token = &self.query
                 [self.location..min(self.query.len(), self.location + keyword.len())]
                 .to_uppercase();
-            if token == keyword {
+            if token == keyword && !self.continues_word(keyword) {
                 return (token.to_string(), token.len());
             }
         }
         self.peek_string_or_indentifier_with_length()
     }
 
+    /// Whether a word keyword found at the current location is only the start of a longer
+    /// identifier, such as `OR` in `order_id`.
+    fn continues_word(&self, keyword: &str) -> bool {
+        let is_word = |character: char| character.is_ascii_alphanumeric() || character == '_';
+        keyword.chars().all(is_word)
+            && self
+                .query
+                .chars()
+                .nth(self.location + keyword.len())
+                .is_some_and(is_word)
+    }
+
     fn pop_string_or_identifier(&mut self) -> String {
         let (res, len) = self.peek_string_or_indentifier_with_length();
         self.location += len;
-        println!(
-            "Loc: {} {} {:?}",
-            self.location,
-            self.query,
-            &self.query[self.location..self.location + 2]
-        );
         self.pop_whitespace();
 
         res
@@ -214,11 +339,6 @@ impl Parser {
             if self.query.chars().nth(i).unwrap() == '\''
                 && self.query.chars().nth(i - 1).unwrap() != '\\'
             {
-                println!(
-                    "Returning string {} {}",
-                    &self.query[self.location..i + 1],
-                    self.query.clone().chars().nth(i).unwrap()
-                );
                 return (
                     self.query[self.location..i + 1].to_string(),
                     // Why did I remove the + 2?
@@ -1,5 +1,6 @@
 use super::constants::*;
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Default, Debug, PartialEq, Eq)]
 pub enum QueryType {
@@ -9,6 +10,30 @@ pub enum QueryType {
     TableCreation,
     Insert,
     Read,
+    Delete,
+    Update,
+    DatabaseDrop,
+    TableDrop,
+    DatabaseRename,
+    TableRename,
+    Alter,
+    Snapshot,
+    SnapshotList,
+    Restore,
+    Diff,
+    ShowDatabases,
+    ShowTables,
+    Describe,
+    Checkpoint,
+    Begin,
+    Commit,
+    Rollback,
+    Savepoint,
+    RollbackTo,
+    Release,
+    IndexCreation,
+    IndexDrop,
+    Analyze,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -36,7 +61,7 @@ pub enum BinaryOperation {
     Or,
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub enum Identifier {
     StringLiteral(String),
     IntLiteral(i32),
@@ -53,7 +78,7 @@ pub enum Expression {
     Binary(BinaryOperation, Box<(Expression, Expression)>),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Mode {
     Fadd,
     Fread,
@@ -61,7 +86,7 @@ pub enum Mode {
     Lmem,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ConstraintType {
     None,
     Exists,
@@ -73,7 +98,7 @@ pub enum ConstraintType {
     Inc,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Constraint {
     pub constraint_type: ConstraintType,
     pub options: Vec<Expression>,
@@ -91,6 +116,7 @@ pub struct Query {
     pub modes: Vec<Mode>,
     pub constraints: HashMap<Identifier, Vec<Constraint>>,
     pub curr_constraint: Identifier,
+    pub indexes: Vec<Index>,
     //
     // ============ Insertions ============
     pub inserted_value: Vec<Identifier>,
@@ -100,6 +126,63 @@ pub struct Query {
     pub read_fields: Vec<Identifier>,
     pub conditions: Expression,
     pub conditions_buf: Expression,
+    pub explain: bool,
+    //
+    // ============ Updates ============
+    //                    FIELD       VALUE
+    pub assignments: Vec<(Identifier, Expression)>,
+    //
+    // ============ Alteration ============
+    pub alteration: Alteration,
+    pub altered_field: Identifier,
+    //
+    // ============ Drops ============
+    pub if_exists: bool,
+    pub cascade: bool,
+    //
+    // ============ Snapshots ============
+    pub snapshot_name: Identifier,
+    pub other_snapshot_name: Identifier,
+    pub snapshot_message: Identifier,
+    pub new_name: Identifier,
+    //
+    // ============ Transactions ============
+    pub savepoint_name: Identifier,
+    //
+    // ============ Indexes ============
+    pub index_name: Identifier,
+    pub index_fields: Vec<Identifier>,
+    pub index_kind: IndexKind,
+}
+
+/// An index kept on some fields of a table, declared in the `INDEXED` clause of its `TABLE`
+/// statement.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Index {
+    pub name: Identifier,
+    pub fields: Vec<Identifier>,
+    pub kind: IndexKind,
+}
+
+/// How an index is stored: ordered by default, or hashed when given `HASH`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    #[default]
+    BTree,
+    Hash,
+}
+
+/// What an `ALTER` statement changes about a table.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alteration {
+    #[default]
+    None,
+    Mode,
+    AddField,
+    DropField,
+    RetypeField,
+    AddConstraints,
+    DropConstraints,
 }
 
 pub fn string_to_unop(token: &String) -> UnaryOperation {
@@ -130,3 +213,164 @@ pub fn string_to_binop(token: &String) -> BinaryOperation {
         _ => panic!("Expected binary operation, found {}", token),
     }
 }
+
+pub fn string_to_mode(token: &String) -> Mode {
+    match token.as_str() {
+        FADD => Mode::Fadd,
+        FREAD => Mode::Fread,
+        FDELETE => Mode::Fdelete,
+        LMEM => Mode::Lmem,
+        _ => panic!("Expected a mode, found {:?}", token),
+    }
+}
+
+pub fn string_to_index_kind(token: &String) -> IndexKind {
+    match token.as_str() {
+        BTREE => IndexKind::BTree,
+        HASH => IndexKind::Hash,
+        _ => panic!("Expected an index kind, found {:?}", token),
+    }
+}
+
+impl fmt::Display for UnaryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = match self {
+            UnaryOperation::Abs => ABS,
+            UnaryOperation::Negative => NEG,
+            UnaryOperation::LogicalNot => NOT,
+            UnaryOperation::BitwiseNot => BWNOT,
+            UnaryOperation::Exists => EXISTS,
+        };
+        write!(f, "{}", token)
+    }
+}
+
+impl fmt::Display for BinaryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = match self {
+            BinaryOperation::Addition => ADD,
+            BinaryOperation::Subtraction => SUB,
+            BinaryOperation::Multiplication => MUL,
+            BinaryOperation::Division => DIV,
+            BinaryOperation::Equals => EQ,
+            BinaryOperation::NotEquals => NE,
+            BinaryOperation::GreaterThanEqualTo => GE,
+            BinaryOperation::LesserThanEqualTo => LE,
+            BinaryOperation::GreaterThan => GT,
+            BinaryOperation::LesserThan => LT,
+            BinaryOperation::And => AND,
+            BinaryOperation::Or => OR,
+        };
+        write!(f, "{}", token)
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Identifier::StringLiteral(data)
+            | Identifier::Datatype(data)
+            | Identifier::Field(data) => {
+                write!(f, "{}", data)
+            }
+            Identifier::IntLiteral(data) => write!(f, "{}", data),
+            Identifier::Array(items) => write!(
+                f,
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::None => Ok(()),
+            Expression::Identifier(identifier) => write!(f, "{}", identifier),
+            Expression::Unary(operator, operand) => write!(f, "{} {}", operator, operand),
+            Expression::Binary(operator, operands) => {
+                write!(f, "{} {} {}", operands.0, operator, operands.1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = match self {
+            Mode::Fadd => FADD,
+            Mode::Fread => FREAD,
+            Mode::Fdelete => FDELETE,
+            Mode::Lmem => LMEM,
+        };
+        write!(f, "{}", token)
+    }
+}
+
+impl fmt::Display for ConstraintType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = match self {
+            ConstraintType::None => "",
+            ConstraintType::Exists => EXISTS,
+            ConstraintType::Unique => UNIQUE,
+            ConstraintType::PKey => PKEY,
+            ConstraintType::FKey => FKEY,
+            ConstraintType::Suchthat => SUCHTHAT,
+            ConstraintType::Default => DEFAULT,
+            ConstraintType::Inc => INC,
+        };
+        write!(f, "{}", token)
+    }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            self.name,
+            self.fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )?;
+        match self.kind {
+            IndexKind::BTree => Ok(()),
+            kind => write!(f, " {}", kind),
+        }
+    }
+}
+
+impl fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = match self {
+            IndexKind::BTree => BTREE,
+            IndexKind::Hash => HASH,
+        };
+        write!(f, "{}", token)
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.options.is_empty() {
+            return write!(f, "{}", self.constraint_type);
+        }
+        write!(
+            f,
+            "{} ({})",
+            self.constraint_type,
+            self.options
+                .iter()
+                .map(|option| option.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
@@ -0,0 +1,180 @@
+use super::table_creation;
+use crate::parser::constants::*;
+use crate::parser::query::string_to_mode;
+use crate::parser::query::Alteration;
+use crate::parser::query::Identifier;
+use crate::parser::Parser;
+use crate::parser::Step;
+
+fn pop_field_name(parser: &mut Parser) -> Identifier {
+    let identifier = parser.pop_identifier();
+    if identifier.is_empty() {
+        panic!("Expected identifier for field name.");
+    }
+    Identifier::Field(identifier)
+}
+
+/// Reads a datatype along with its options, such as `UINT(2)` or `OPTIONS(a, b)`.
+fn pop_datatype(parser: &mut Parser) -> (Identifier, Vec<Identifier>) {
+    let dtype = parser.pop();
+    if !DATATYPES.contains(&dtype.as_str()) {
+        panic!("Expected a Datatype, found {:?}", dtype);
+    }
+
+    let token = parser.pop();
+    parser.ensure_token(token, OPEN_PAREN);
+
+    let mut options = Vec::new();
+    while parser.peek() != CLOSE_PAREN {
+        let token = parser.pop();
+        if dtype == DT_OPTIONS {
+            options.push(Identifier::StringLiteral(token));
+        } else {
+            options.push(Identifier::IntLiteral(token.parse::<i32>().unwrap()));
+        }
+
+        match parser.peek().as_str() {
+            COMMA => {
+                parser.pop();
+            }
+            CLOSE_PAREN => {}
+            found_token => panic!(
+                "Expected close paren ')' or comma ',', got {:?}",
+                found_token
+            ),
+        }
+    }
+    parser.pop();
+
+    (Identifier::Datatype(dtype), options)
+}
+
+/// Makes the constraints which follow apply to `field`, then hands them over to the table
+/// creation steps.
+fn constrain(parser: &mut Parser, field: Identifier) -> Step {
+    parser
+        .query_data
+        .constraints
+        .insert(field.clone(), Vec::new());
+    parser.query_data.curr_constraint = field;
+
+    match parser.peek().as_str() {
+        SEMICOLON => Step::End,
+        EXISTS | UNIQUE | PKEY | FKEY | SUCHTHAT | DEFAULT | INC => Step::DefineConstraint,
+        found_token => panic!(
+            "Expected a constraint or semicolon, found {:?}",
+            found_token
+        ),
+    }
+}
+
+pub fn altering(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::AlterTableName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for table name");
+            }
+
+            parser.query_data.table_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::AlterDatabaseName
+        }
+        Step::AlterDatabaseName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+            Step::AlterAction
+        }
+        Step::AlterAction => {
+            let token = parser.pop();
+            let on = parser.peek() == ON;
+            if on {
+                parser.pop();
+            }
+
+            let (alteration, next) = match (token.as_str(), on) {
+                (MODE, false) => (Alteration::Mode, Step::AlterMode),
+                (ALTER_ADD, false) => (Alteration::AddField, Step::AlterAddField),
+                (ALTER_ADD, true) => (Alteration::AddConstraints, Step::AlterAddConstraints),
+                (DROP, false) => (Alteration::DropField, Step::AlterDropField),
+                (DROP, true) => (Alteration::DropConstraints, Step::AlterDropConstraints),
+                (RETYPE, false) => (Alteration::RetypeField, Step::AlterRetypeField),
+                _ => panic!(
+                    "Expected MODE, ADD, DROP or RETYPE, found {:?}",
+                    parser.peek()
+                ),
+            };
+            parser.query_data.alteration = alteration;
+            next
+        }
+        Step::AlterMode => {
+            let token = parser.pop();
+            parser.query_data.modes.push(string_to_mode(&token));
+
+            match parser.peek().as_str() {
+                SEMICOLON => Step::End,
+                FADD | FREAD | FDELETE | LMEM => Step::AlterMode,
+                token => panic!("Expected another mode or semicolon, found {:?}", token),
+            }
+        }
+        Step::AlterAddField => {
+            let (dtype, options) = pop_datatype(parser);
+            let field = pop_field_name(parser);
+
+            parser
+                .query_data
+                .fields
+                .push((dtype, options, field.clone()));
+            parser.query_data.altered_field = field.clone();
+            constrain(parser, field)
+        }
+        Step::AlterAddConstraints | Step::AlterDropConstraints => {
+            let field = pop_field_name(parser);
+            parser.query_data.altered_field = field.clone();
+
+            match constrain(parser, field) {
+                Step::End => panic!("Expected a constraint, found \";\""),
+                next => next,
+            }
+        }
+        Step::AlterDropField => {
+            parser.query_data.altered_field = pop_field_name(parser);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        Step::AlterRetypeField => {
+            let field = pop_field_name(parser);
+            let (dtype, options) = pop_datatype(parser);
+
+            parser
+                .query_data
+                .fields
+                .push((dtype, options, field.clone()));
+            parser.query_data.altered_field = field;
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        Step::DefineConstraintOn if parser.query_data.alteration == Alteration::AddField => {
+            panic!("Only the added field can be constrained when adding a field")
+        }
+        Step::DefineConstraintOn
+        | Step::DefineConstraintIdentifier
+        | Step::DefineConstraint
+        | Step::DefineConstraintOption
+        | Step::DefineConstraintOptionCloseParen => table_creation::table_creation(parser, step),
+        found => panic!(
+            "Incorrect module used. Altering module used to handle step {:?}",
+            found
+        ),
+    }
+}
@@ -0,0 +1,24 @@
+use crate::parser::constants::*;
+use crate::parser::query::Identifier;
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn checkpointing(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::CheckpointDatabase => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Checkpointing module used to handle step {:?}",
+            found
+        ),
+    }
+}
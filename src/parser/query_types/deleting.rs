@@ -0,0 +1,42 @@
+use crate::parser::constants::*;
+use crate::parser::query::Identifier;
+use crate::parser::query_types::reading::pop_condition;
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn deleting(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::DeleteTableName => {
+            let token = parser.pop_identifier();
+            if token.is_empty() {
+                panic!("Expected identifier for table name");
+            }
+            parser.query_data.table_name = Identifier::StringLiteral(token);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::DeleteDatabaseName
+        }
+        Step::DeleteDatabaseName => {
+            let token = parser.pop_identifier();
+            if token.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(token);
+
+            match parser.pop().as_str() {
+                SEMICOLON => Step::End,
+                WHERE => Step::DeleteConditionPart,
+                found_token => panic!("Unexpected token {}", found_token),
+            }
+        }
+        Step::DeleteConditionPart => {
+            parser.query_data.conditions = pop_condition(parser);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Deleting module used to handle step {:?}",
+            found
+        ),
+    }
+}
@@ -0,0 +1,84 @@
+use crate::parser::constants::*;
+use crate::parser::query::{Identifier, QueryType};
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn dropping(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::DropTarget => {
+            parser.query_data._type = match parser.pop().as_str() {
+                DATABASE => QueryType::DatabaseDrop,
+                TABLE => QueryType::TableDrop,
+                INDEX => QueryType::IndexDrop,
+                found_token => panic!("Expected DATABASE, TABLE or INDEX, found {:?}", found_token),
+            };
+
+            if parser.peek() == IF {
+                parser.pop();
+                let token = parser.pop();
+                parser.ensure_token(token, EXISTS);
+                parser.query_data.if_exists = true;
+            }
+            Step::DropName
+        }
+        Step::DropName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for the dropped name.");
+            }
+
+            if parser.query_data._type == QueryType::DatabaseDrop {
+                parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+                let token = parser.pop();
+                parser.ensure_token(token, SEMICOLON);
+                return Step::End;
+            }
+
+            if parser.query_data._type == QueryType::IndexDrop {
+                parser.query_data.index_name = Identifier::StringLiteral(identifier);
+
+                let token = parser.pop();
+                parser.ensure_token(token, ON);
+                return Step::DropIndexTable;
+            }
+
+            parser.query_data.table_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::DropTableDatabase
+        }
+        Step::DropIndexTable => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for table name.");
+            }
+            parser.query_data.table_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::DropTableDatabase
+        }
+        Step::DropTableDatabase => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+            if parser.peek() == CASCADE {
+                parser.pop();
+                parser.query_data.cascade = true;
+            }
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Dropping module used to handle step {:?}",
+            found
+        ),
+    }
+}
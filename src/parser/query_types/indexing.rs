@@ -0,0 +1,73 @@
+use crate::parser::constants::*;
+use crate::parser::query::string_to_index_kind;
+use crate::parser::query::Identifier;
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn indexing(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::IndexName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for index name.");
+            }
+            parser.query_data.index_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::IndexTableName
+        }
+        Step::IndexTableName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for table name.");
+            }
+            parser.query_data.table_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::IndexDatabaseName
+        }
+        Step::IndexDatabaseName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, STRUCTURED);
+            let token = parser.pop();
+            parser.ensure_token(token, OPEN_PAREN);
+            Step::IndexField
+        }
+        Step::IndexField => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for field name.");
+            }
+            parser
+                .query_data
+                .index_fields
+                .push(Identifier::Field(identifier));
+
+            match parser.pop().as_str() {
+                COMMA => Step::IndexField,
+                CLOSE_PAREN => {
+                    if let BTREE | HASH = parser.peek().as_str() {
+                        let token = parser.pop();
+                        parser.query_data.index_kind = string_to_index_kind(&token);
+                    }
+                    let token = parser.pop();
+                    parser.ensure_token(token, SEMICOLON);
+                    Step::End
+                }
+                found => panic!("Expected comma or close paren, found {:?}", found),
+            }
+        }
+        found => panic!(
+            "Incorrect module used. Indexing module used to handle step {:?}",
+            found
+        ),
+    }
+}
@@ -1,6 +1,18 @@
+pub mod altering;
+pub mod analyzing;
+pub mod checkpointing;
+pub mod deleting;
+pub mod describing;
+pub mod dropping;
+pub mod indexing;
 pub mod inserting;
 pub mod reading;
+pub mod renaming;
+pub mod showing;
+pub mod snapshots;
 pub mod table_creation;
+pub mod transactions;
+pub mod updating;
 
 pub mod type_checker {
     use crate::parser::{
@@ -11,11 +11,9 @@ use super::type_checker;
 
 // Why did using this work, and not my previous attempts?
 // No fucking clue.
-fn parse_condition(tokens: Vec<String>) -> Expression {
+pub fn parse_condition(tokens: Vec<String>) -> Expression {
     let mut res = Expression::None;
 
-    println!("{:?}", tokens);
-
     for (i, token) in tokens.iter().enumerate() {
         if CONNECTORS.contains(&token.as_str()) {
             res = Expression::Binary(
@@ -55,6 +53,45 @@ fn parse_condition(tokens: Vec<String>) -> Expression {
     res
 }
 
+/// Whether the tokens are a single group of parentheses, the first closing the last.
+fn enclosed(tokens: &[String]) -> bool {
+    if tokens.first().map(String::as_str) != Some(OPEN_PAREN)
+        || tokens.last().map(String::as_str) != Some(CLOSE_PAREN)
+    {
+        return false;
+    }
+
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.as_str() {
+            OPEN_PAREN => depth += 1,
+            CLOSE_PAREN => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 && i < tokens.len() - 1 {
+            return false;
+        }
+    }
+    true
+}
+
+/// Pops the condition following a `WHERE`, up to the closing semicolon. The condition may be
+/// wrapped in parentheses as a whole, as in `WHERE (id = 1)`, or not, as in `WHERE id = 1`.
+pub fn pop_condition(parser: &mut Parser) -> Expression {
+    let mut tokens: Vec<String> = Vec::new();
+    while ![SEMICOLON, ""].contains(&parser.peek().as_str()) {
+        tokens.push(parser.pop());
+    }
+    if enclosed(&tokens) {
+        tokens = tokens[1..tokens.len() - 1].to_vec();
+    }
+    if tokens.is_empty() {
+        panic!("Expected a condition after WHERE");
+    }
+
+    parse_condition(tokens)
+}
+
 pub fn reading(parser: &mut Parser, step: Step) -> Step {
     match step {
         Step::ReadTableName => {
@@ -75,17 +112,25 @@ pub fn reading(parser: &mut Parser, step: Step) -> Step {
             match parser.pop().as_str() {
                 SEMICOLON => Step::End,
                 WHERE => Step::ReadConditionPart,
+                AT => Step::ReadSnapshotName,
                 found_token => panic!("Unexpected token {}", found_token),
             }
         }
-        Step::ReadConditionPart => {
-            let mut tokens: Vec<String> = Vec::new();
-            while parser.peek() != SEMICOLON {
-                tokens.push(parser.pop());
+        Step::ReadSnapshotName => {
+            let token = parser.pop_identifier();
+            if token.is_empty() {
+                panic!("Expected identifier for snapshot name.");
             }
+            parser.query_data.snapshot_name = Identifier::StringLiteral(token);
 
-            parser.query_data.conditions = parse_condition(tokens[1..tokens.len() - 1].to_vec());
-
+            match parser.pop().as_str() {
+                SEMICOLON => Step::End,
+                WHERE => Step::ReadConditionPart,
+                found_token => panic!("Unexpected token {}", found_token),
+            }
+        }
+        Step::ReadConditionPart => {
+            parser.query_data.conditions = pop_condition(parser);
             Step::End
         }
         Step::ReadFieldIdentifier => {
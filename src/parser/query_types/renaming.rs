@@ -0,0 +1,63 @@
+use crate::parser::constants::*;
+use crate::parser::query::{Identifier, QueryType};
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn renaming(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::RenameTarget => {
+            parser.query_data._type = match parser.pop().as_str() {
+                DATABASE => QueryType::DatabaseRename,
+                TABLE => QueryType::TableRename,
+                found_token => panic!("Expected DATABASE or TABLE, found {:?}", found_token),
+            };
+            Step::RenameName
+        }
+        Step::RenameName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for the renamed name.");
+            }
+
+            if parser.query_data._type == QueryType::DatabaseRename {
+                parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+                let token = parser.pop();
+                parser.ensure_token(token, INTO);
+                return Step::RenameNewName;
+            }
+
+            parser.query_data.table_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::RenameTableDatabase
+        }
+        Step::RenameTableDatabase => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, INTO);
+            Step::RenameNewName
+        }
+        Step::RenameNewName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for the new name.");
+            }
+            parser.query_data.new_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Renaming module used to handle step {:?}",
+            found
+        ),
+    }
+}
@@ -0,0 +1,41 @@
+use crate::parser::constants::*;
+use crate::parser::query::{Identifier, QueryType};
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn showing(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::ShowTarget => match parser.pop().as_str() {
+            DATABASES => {
+                parser.query_data._type = QueryType::ShowDatabases;
+
+                let token = parser.pop();
+                parser.ensure_token(token, SEMICOLON);
+                Step::End
+            }
+            TABLES => {
+                parser.query_data._type = QueryType::ShowTables;
+
+                let token = parser.pop();
+                parser.ensure_token(token, ON);
+                Step::ShowTablesDatabase
+            }
+            found_token => panic!("Expected DATABASES or TABLES, found {:?}", found_token),
+        },
+        Step::ShowTablesDatabase => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Showing module used to handle step {:?}",
+            found
+        ),
+    }
+}
@@ -0,0 +1,124 @@
+use crate::parser::constants::*;
+use crate::parser::query::{Identifier, QueryType};
+use crate::parser::query_types::type_checker;
+use crate::parser::Parser;
+use crate::parser::Step;
+
+fn end_of_target(parser: &mut Parser) -> Step {
+    match parser.pop().as_str() {
+        SEMICOLON => Step::End,
+        INTO if parser.query_data._type == QueryType::Restore
+            && parser.query_data.table_name != Identifier::StringLiteral(String::new()) =>
+        {
+            Step::SnapshotRestoreInto
+        }
+        MESSAGE if parser.query_data._type == QueryType::Snapshot => Step::SnapshotMessage,
+        found_token => panic!("Unexpected token {:?}", found_token),
+    }
+}
+
+pub fn snapshots(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::SnapshotName | Step::DiffFrom => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for snapshot name.");
+            }
+
+            parser.query_data.snapshot_name = Identifier::StringLiteral(identifier);
+
+            if step == Step::DiffFrom {
+                let token = parser.pop();
+                parser.ensure_token(token, COMMA);
+                return Step::DiffTo;
+            }
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::SnapshotTarget
+        }
+        Step::DiffTo => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for snapshot name.");
+            }
+
+            parser.query_data.other_snapshot_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::SnapshotListDatabase
+        }
+        Step::SnapshotTarget => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for table or database name.");
+            }
+
+            match parser.peek().as_str() {
+                // `ON table ON database`, the snapshot only covers a single table
+                ON => {
+                    parser.pop();
+                    parser.query_data.table_name = Identifier::StringLiteral(identifier);
+                    Step::SnapshotDatabase
+                }
+                _ => {
+                    parser.query_data.db_name = Identifier::StringLiteral(identifier);
+                    end_of_target(parser)
+                }
+            }
+        }
+        Step::SnapshotDatabase => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+            end_of_target(parser)
+        }
+        Step::SnapshotListDatabase => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+
+            parser.query_data.db_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        Step::SnapshotRestoreInto => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for table name.");
+            }
+
+            parser.query_data.new_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        Step::SnapshotMessage => {
+            let token = parser.pop();
+            if !type_checker::check_string_literal(&token) {
+                panic!(
+                    "Expected a string for the snapshot message, found {:?}",
+                    token
+                );
+            }
+
+            parser.query_data.snapshot_message = Identifier::StringLiteral(token);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Snapshots module used to handle step {:?}",
+            found
+        ),
+    }
+}
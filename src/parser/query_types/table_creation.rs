@@ -3,12 +3,16 @@ use std::collections::HashMap;
 use super::type_checker;
 use crate::parser::constants::*;
 use crate::parser::query::string_to_binop;
+use crate::parser::query::string_to_index_kind;
+use crate::parser::query::string_to_mode;
 use crate::parser::query::string_to_unop;
 use crate::parser::query::Constraint;
 use crate::parser::query::ConstraintType;
 use crate::parser::query::Expression;
 use crate::parser::query::Identifier;
-use crate::parser::query::Mode;
+use crate::parser::query::Index;
+use crate::parser::query::IndexKind;
+use crate::parser::query::QueryType;
 use crate::parser::Parser;
 use crate::parser::Step;
 
@@ -133,6 +137,11 @@ pub fn table_creation(parser: &mut Parser, step: Step) -> Step {
         Step::DefineTableStructureCloseParen => match parser.pop().to_uppercase().as_str() {
             SEMICOLON => Step::End,
             MODE => Step::DefineTableMode,
+            INDEXED => {
+                let token = parser.pop();
+                parser.ensure_token(token, OPEN_PAREN);
+                Step::DefineTableIndexName
+            }
             CONSTRAINED => {
                 let token = parser.pop();
                 parser.ensure_token(token, OPEN_PAREN);
@@ -142,18 +151,16 @@ pub fn table_creation(parser: &mut Parser, step: Step) -> Step {
         },
         Step::DefineTableMode => {
             let token = parser.pop();
-            let mode = match token.as_str() {
-                FADD => Mode::Fadd,
-                FREAD => Mode::Fread,
-                FDELETE => Mode::Fdelete,
-                LMEM => Mode::Lmem,
-                _ => panic!("Expected a mode, found {:?}", token),
-            };
-
-            parser.query_data.modes.push(mode);
+            parser.query_data.modes.push(string_to_mode(&token));
             match parser.peek().as_str() {
                 SEMICOLON => Step::End,
                 FADD | FREAD | FDELETE | LMEM => Step::DefineTableMode,
+                INDEXED => {
+                    parser.pop();
+                    let token = parser.pop();
+                    parser.ensure_token(token, OPEN_PAREN);
+                    Step::DefineTableIndexName
+                }
                 token => panic!("Expected another mode or semicolon, found {:?}", token),
             }
         }
@@ -207,7 +214,12 @@ pub fn table_creation(parser: &mut Parser, step: Step) -> Step {
                     parser.pop();
                     Step::DefineConstraintOn
                 }
-                CLOSE_PAREN => Step::DefineConstraintCloseParen,
+                CLOSE_PAREN => {
+                    parser.pop();
+                    Step::DefineConstraintCloseParen
+                }
+                // Constraints given to ALTER are not wrapped in CONSTRAINED (...)
+                SEMICOLON if parser.query_data._type == QueryType::Alter => Step::End,
                 _ => panic!(),
             }
         }
@@ -234,9 +246,8 @@ pub fn table_creation(parser: &mut Parser, step: Step) -> Step {
                         },
                         Expression::Unary(_, operand) => match **operand {
                             Expression::None => {
-                                *operand = Box::new(Expression::Identifier(
-                                    type_checker::as_identifier(&token),
-                                ))
+                                **operand =
+                                    Expression::Identifier(type_checker::as_identifier(&token))
                             }
                             _ => push_to_options(latest, token),
                         },
@@ -270,6 +281,7 @@ pub fn table_creation(parser: &mut Parser, step: Step) -> Step {
                     parser.pop();
                     Step::DefineConstraintCloseParen
                 }
+                SEMICOLON if parser.query_data._type == QueryType::Alter => Step::End,
                 found_token => {
                     panic!("Expected comma or constraint, found {:?}", found_token)
                 }
@@ -280,6 +292,50 @@ pub fn table_creation(parser: &mut Parser, step: Step) -> Step {
             parser.ensure_token(token, MODE);
             Step::DefineTableMode
         }
+        Step::DefineTableIndexName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for index name.");
+            }
+            parser.query_data.indexes.push(Index {
+                name: Identifier::StringLiteral(identifier),
+                fields: Vec::new(),
+                kind: IndexKind::default(),
+            });
+
+            let token = parser.pop();
+            parser.ensure_token(token, OPEN_PAREN);
+            Step::DefineTableIndexField
+        }
+        Step::DefineTableIndexField => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for field name.");
+            }
+            let index = parser.query_data.indexes.last_mut().unwrap();
+            index.fields.push(Identifier::Field(identifier));
+
+            match parser.pop().as_str() {
+                COMMA => Step::DefineTableIndexField,
+                CLOSE_PAREN => {
+                    if let BTREE | HASH = parser.peek().as_str() {
+                        let token = parser.pop();
+                        let index = parser.query_data.indexes.last_mut().unwrap();
+                        index.kind = string_to_index_kind(&token);
+                    }
+                    match parser.pop().as_str() {
+                        COMMA => Step::DefineTableIndexName,
+                        CLOSE_PAREN => {
+                            let token = parser.pop();
+                            parser.ensure_token(token, SEMICOLON);
+                            Step::End
+                        }
+                        found => panic!("Expected comma or close paren, found {:?}", found),
+                    }
+                }
+                found => panic!("Expected comma or close paren, found {:?}", found),
+            }
+        }
         found => panic!(
             "Incorrect module used. Table creation module used to handle step {:?}",
             found
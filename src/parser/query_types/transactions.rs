@@ -0,0 +1,32 @@
+use crate::parser::constants::*;
+use crate::parser::query::{Identifier, QueryType};
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn transactions(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::TransactionEnd => match parser.pop().to_uppercase().as_str() {
+            SEMICOLON => Step::End,
+            TO if parser.query_data._type == QueryType::Rollback => {
+                parser.query_data._type = QueryType::RollbackTo;
+                Step::SavepointName
+            }
+            found => panic!("Unexpected token {:?}", found),
+        },
+        Step::SavepointName => {
+            let identifier = parser.pop_identifier();
+            if identifier.is_empty() {
+                panic!("Expected identifier for savepoint name.");
+            }
+            parser.query_data.savepoint_name = Identifier::StringLiteral(identifier);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SEMICOLON);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Transactions module used to handle step {:?}",
+            found
+        ),
+    }
+}
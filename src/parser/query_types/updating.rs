@@ -0,0 +1,81 @@
+use crate::parser::constants::*;
+use crate::parser::query::{Expression, Identifier};
+use crate::parser::query_types::reading::{parse_condition, pop_condition};
+use crate::parser::query_types::type_checker;
+use crate::parser::Parser;
+use crate::parser::Step;
+
+pub fn updating(parser: &mut Parser, step: Step) -> Step {
+    match step {
+        Step::UpdateTableName => {
+            let token = parser.pop_identifier();
+            if token.is_empty() {
+                panic!("Expected identifier for table name");
+            }
+            parser.query_data.table_name = Identifier::StringLiteral(token);
+
+            let token = parser.pop();
+            parser.ensure_token(token, ON);
+            Step::UpdateDatabaseName
+        }
+        Step::UpdateDatabaseName => {
+            let token = parser.pop_identifier();
+            if token.is_empty() {
+                panic!("Expected identifier for database name.");
+            }
+            parser.query_data.db_name = Identifier::StringLiteral(token);
+
+            let token = parser.pop();
+            parser.ensure_token(token, SET);
+
+            let token = parser.pop();
+            parser.ensure_token(token, OPEN_PAREN);
+            Step::UpdateAssignmentField
+        }
+        Step::UpdateAssignmentField => {
+            let token = parser.pop_identifier();
+            if !type_checker::check_field(&token) || token.is_empty() {
+                panic!("Expected a field, found {:?}", token);
+            }
+            parser
+                .query_data
+                .assignments
+                .push((Identifier::Field(token), Expression::None));
+
+            let token = parser.pop();
+            parser.ensure_token(token, EQ);
+            Step::UpdateAssignmentValue
+        }
+        Step::UpdateAssignmentValue => {
+            let mut tokens: Vec<String> = Vec::new();
+            while ![COMMA, CLOSE_PAREN, SEMICOLON, ""].contains(&parser.peek().as_str()) {
+                tokens.push(parser.pop());
+            }
+            if tokens.is_empty() {
+                panic!(
+                    "Expected a value for field {}",
+                    parser.query_data.assignments.last().unwrap().0
+                );
+            }
+            parser.query_data.assignments.last_mut().unwrap().1 = parse_condition(tokens);
+
+            match parser.pop().as_str() {
+                COMMA => Step::UpdateAssignmentField,
+                CLOSE_PAREN => match parser.pop().as_str() {
+                    SEMICOLON => Step::End,
+                    WHERE => Step::UpdateConditionPart,
+                    found_token => panic!("Unexpected token {:?}", found_token),
+                },
+                found_token => panic!("Expected comma or close paren, found {:?}", found_token),
+            }
+        }
+        Step::UpdateConditionPart => {
+            parser.query_data.conditions = pop_condition(parser);
+            Step::End
+        }
+        found => panic!(
+            "Incorrect module used. Updating module used to handle step {:?}",
+            found
+        ),
+    }
+}
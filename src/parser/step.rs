@@ -17,6 +17,8 @@ pub enum Step {
     DefineConstraintOption,
     DefineConstraintOptionCloseParen,
     DefineConstraintCloseParen,
+    DefineTableIndexName,
+    DefineTableIndexField,
     // ============ Insertions ============
     InsertValueIdentifier,
     InsertTable,
@@ -26,6 +28,64 @@ pub enum Step {
     ReadFieldIdentifier,
     ReadTableName,
     ReadDatabaseName,
+    ReadSnapshotName,
     ReadConditionPart,
+    // ============ Deletion ============
+    DeleteTableName,
+    DeleteDatabaseName,
+    DeleteConditionPart,
+    // ============ Updates ============
+    UpdateTableName,
+    UpdateDatabaseName,
+    UpdateAssignmentField,
+    UpdateAssignmentValue,
+    UpdateConditionPart,
+    // ============ Introspection ============
+    ShowTarget,
+    ShowTablesDatabase,
+    DescribeTableName,
+    DescribeDatabaseName,
+    // ============ Logging ============
+    CheckpointDatabase,
+    // ============ Transactions ============
+    TransactionEnd,
+    SavepointName,
+    // ============ Indexes ============
+    IndexName,
+    IndexTableName,
+    IndexDatabaseName,
+    IndexField,
+    // ============ Statistics ============
+    AnalyzeTableName,
+    AnalyzeDatabaseName,
+    // ============ Drops ============
+    DropTarget,
+    DropName,
+    DropTableDatabase,
+    DropIndexTable,
+    // ============ Renames ============
+    RenameTarget,
+    RenameName,
+    RenameTableDatabase,
+    RenameNewName,
+    // ============ Alteration ============
+    AlterTableName,
+    AlterDatabaseName,
+    AlterMode,
+    AlterAction,
+    AlterAddField,
+    AlterAddConstraints,
+    AlterDropField,
+    AlterDropConstraints,
+    AlterRetypeField,
+    // ============ Snapshots ============
+    SnapshotName,
+    SnapshotTarget,
+    SnapshotDatabase,
+    SnapshotRestoreInto,
+    SnapshotMessage,
+    DiffFrom,
+    DiffTo,
+    SnapshotListDatabase,
     End,
 }
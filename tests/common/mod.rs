@@ -1,22 +1,70 @@
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::Parser;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub fn ensure_db_existance(db_name: &str) -> bool {
-    Path::new(db_name).exists()
+/// An engine opened on a directory of its own, which is removed once the test is over.
+pub struct TestEngine(Engine);
+
+impl Deref for TestEngine {
+    type Target = Engine;
+
+    fn deref(&self) -> &Engine {
+        &self.0
+    }
 }
 
-pub fn ensure_table_existance(db_name: &str, table_name: &str) -> bool {
-    Path::new(format!("{}/{}.asql", db_name, table_name).as_str()).exists()
+impl Drop for TestEngine {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.0.root());
+    }
 }
 
-pub fn remove_db(db_name: &str) -> Result<(), std::io::Error> {
-    fs::remove_dir_all(db_name)
+/// Opens an engine on a fresh temporary directory, so tests do not need to run one at a time.
+pub fn temp_engine() -> TestEngine {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let root = std::env::temp_dir().join(format!(
+        "arraysql-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    let _ = fs::remove_dir_all(&root);
+    TestEngine(Engine::open(root).unwrap())
 }
 
-pub fn soft_remove_db(db_name: &str) -> Result<(), std::io::Error> {
-    if !ensure_db_existance(db_name) {
-        Ok(())
-    } else {
-        remove_db(db_name)
-    }
+/// The database most tests run their statements on.
+pub const DATABASE_NAME: &str = "my_database";
+
+/// Parses a single statement and runs it on `engine`.
+pub fn run(engine: &Engine, query: &str) -> Result<Output, io::Error> {
+    let mut p = Parser::new();
+    p.set_query(query.to_string()).parse();
+    engine.execute(&p)
+}
+
+/// Opens an engine on a fresh temporary directory, holding an empty [`DATABASE_NAME`] database.
+pub fn temp_database() -> TestEngine {
+    let engine = temp_engine();
+    run(&engine, &format!("DATABASE {};", DATABASE_NAME)).unwrap();
+    engine
+}
+
+/// Opens an engine in memory, holding an empty [`DATABASE_NAME`] database.
+pub fn memory_database() -> Engine {
+    let engine = Engine::in_memory();
+    run(&engine, &format!("DATABASE {};", DATABASE_NAME)).unwrap();
+    engine
+}
+
+pub fn ensure_db_existance(engine: &Engine, db_name: &str) -> bool {
+    engine.storage().is_dir(&engine.path(db_name))
+}
+
+pub fn ensure_table_existance(engine: &Engine, db_name: &str, table_name: &str) -> bool {
+    engine
+        .storage()
+        .exists(&engine.path(db_name).join(format!("{}.asql", table_name)))
 }
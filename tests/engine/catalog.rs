@@ -0,0 +1,157 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::{catalog::Catalog, Engine};
+use arraysql::parser::query::{ConstraintType, Identifier, Mode};
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON user_id FKEY (ON users)) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+
+    engine
+}
+
+fn table_names(engine: &Engine) -> Vec<String> {
+    Catalog::load(engine, DATABASE_NAME)
+        .unwrap()
+        .tables
+        .keys()
+        .cloned()
+        .collect()
+}
+
+#[test]
+pub fn test_catalog_follows_schema_changes() {
+    let engine = pretest();
+
+    let catalog = Catalog::load(&engine, DATABASE_NAME).unwrap();
+    let users = catalog.table(DATABASE_NAME, "users").unwrap();
+    assert_eq!(
+        users.fields,
+        vec![
+            (
+                Identifier::Datatype("UINT".to_string()),
+                vec![Identifier::IntLiteral(1)],
+                Identifier::Field("id".to_string())
+            ),
+            (
+                Identifier::Datatype("STRING".to_string()),
+                vec![Identifier::IntLiteral(64)],
+                Identifier::Field("name".to_string())
+            ),
+        ]
+    );
+    assert!(users.has_constraint(&Identifier::Field("id".to_string()), ConstraintType::PKey));
+    assert_eq!(users.modes, vec![Mode::Fread, Mode::Lmem]);
+    assert!(users.records.is_empty());
+
+    run(
+        &engine,
+        &format!(
+            "ALTER users ON {} ADD UINT(1) age DEFAULT (0);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!("ALTER users ON {} MODE FDELETE;", DATABASE_NAME),
+    )
+    .unwrap();
+    let catalog = Catalog::load(&engine, DATABASE_NAME).unwrap();
+    let users = catalog.table(DATABASE_NAME, "users").unwrap();
+    assert_eq!(users.fields.len(), 3);
+    assert_eq!(users.modes, vec![Mode::Fread, Mode::Fdelete]);
+
+    run(
+        &engine,
+        &format!("RENAME TABLE users ON {} INTO customers;", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(table_names(&engine), vec!["customers", "orders"]);
+    let catalog = Catalog::load(&engine, DATABASE_NAME).unwrap();
+    assert_eq!(
+        catalog.table(DATABASE_NAME, "orders").unwrap().references(),
+        vec![(1, "customers".to_string())]
+    );
+
+    run(
+        &engine,
+        &format!("DROP TABLE customers ON {} CASCADE;", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(table_names(&engine), vec!["orders"]);
+    let catalog = Catalog::load(&engine, DATABASE_NAME).unwrap();
+    assert!(catalog
+        .table(DATABASE_NAME, "orders")
+        .unwrap()
+        .references()
+        .is_empty());
+}
+
+#[test]
+pub fn test_catalog_is_rebuilt() {
+    let engine = pretest();
+
+    engine
+        .storage()
+        .remove_file(&engine.path(DATABASE_NAME).join(".catalog"))
+        .unwrap();
+    assert_eq!(table_names(&engine), vec!["orders", "users"]);
+    assert!(common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "users"
+    ));
+    assert!(engine
+        .storage()
+        .exists(&engine.path(DATABASE_NAME).join(".catalog")));
+}
+
+#[test]
+pub fn test_catalog_validates_queries() {
+    let engine = pretest();
+
+    let err = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (1) ON customers STRUCTURED (id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(err.to_string(), "Table customers not found on my_database");
+
+    let err = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (nickname) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = run(
+        &engine,
+        &format!("READ STRUCTURED (nickname) ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = run(
+        &engine,
+        "READ STRUCTURED (name) ON users ON missing_database;",
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(err.to_string(), "Database missing_database not found");
+}
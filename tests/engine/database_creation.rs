@@ -1,48 +1,52 @@
 use crate::common;
-use arraysql::{engine, parser};
-use serial_test::serial;
+use arraysql::parser;
 
 const DB_NAME: &str = "my_database";
 
 #[test]
-#[serial]
 pub fn test_database_creation() {
-    common::soft_remove_db(DB_NAME).unwrap();
+    let engine = common::temp_engine();
 
     let mut p = parser::Parser::new();
     p.set_query(format!("DATABASE {};", DB_NAME)).parse();
 
-    let res = engine::engine(&p);
+    let res = engine.execute(&p);
     if let Err(err) = res {
         panic!("Err: {}", err);
     }
     assert!(res.is_ok());
 
-    assert!(common::ensure_db_existance(DB_NAME));
-
-    //  Post test
-    let res = common::remove_db(DB_NAME);
-    assert!(res.is_ok());
+    assert!(common::ensure_db_existance(&engine, DB_NAME));
 }
 
 #[test]
-#[serial]
 pub fn test_database_already_exists() {
-    common::soft_remove_db(DB_NAME).unwrap();
+    let engine = common::temp_engine();
 
     let mut p = parser::Parser::new();
     p.set_query(format!("DATABASE {};", DB_NAME)).parse();
 
-    let res = engine::engine(&p);
+    let res = engine.execute(&p);
     assert!(res.is_ok());
 
-    assert!(common::ensure_db_existance(DB_NAME));
+    assert!(common::ensure_db_existance(&engine, DB_NAME));
 
     //  Trying to create the database again
-    let res = engine::engine(&p);
+    let res = engine.execute(&p);
     assert!(res.is_err());
+}
 
-    //  Post test
-    let res = common::remove_db(DB_NAME);
-    assert!(res.is_ok());
+#[test]
+pub fn test_engines_are_isolated() {
+    let (engine, other) = (common::temp_engine(), common::temp_engine());
+
+    let mut p = parser::Parser::new();
+    p.set_query(format!("DATABASE {};", DB_NAME)).parse();
+
+    engine.execute(&p).unwrap();
+    assert!(common::ensure_db_existance(&engine, DB_NAME));
+    assert!(!common::ensure_db_existance(&other, DB_NAME));
+
+    // The same database can be created again under another root
+    other.execute(&p).unwrap();
 }
@@ -0,0 +1,172 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::{table_file::TableFile, Engine, Output};
+use arraysql::parser::query::Identifier;
+
+fn pretest(mode: &str) -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name, UINT(1) age) CONSTRAINED (ON id PKEY INC) MODE {};",
+        DATABASE_NAME, mode
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON id PKEY INC, ON user_id FKEY (ON users)) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    for (name, age) in [("'alice'", "31"), ("'bob'", "12"), ("'carol'", "8")] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}, {}) ON users STRUCTURED (name, age) ON {};",
+                name, age, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (1) ON orders STRUCTURED (user_id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    engine
+}
+
+fn names(engine: &Engine) -> Vec<Vec<Option<Identifier>>> {
+    match run(
+        engine,
+        &format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap()
+    {
+        Output::ResultSet(_, rows) => rows,
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+#[test]
+pub fn test_delete() {
+    let engine = pretest("LMEM");
+
+    let res = run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (age >= 30);", DATABASE_NAME),
+    );
+    assert_eq!(res.unwrap(), Output::Count(1));
+    assert_eq!(
+        names(&engine),
+        vec![
+            vec![Some(Identifier::StringLiteral("'bob'".to_string()))],
+            vec![Some(Identifier::StringLiteral("'carol'".to_string()))]
+        ]
+    );
+
+    // LMEM tables do not keep deleted records around
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records
+            .len(),
+        2
+    );
+
+    let res = run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (age >= 30);", DATABASE_NAME),
+    );
+    assert_eq!(res.unwrap(), Output::Count(0));
+
+    // The condition does not need parentheses
+    let res = run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE id = 2;", DATABASE_NAME),
+    );
+    assert_eq!(res.unwrap(), Output::Count(1));
+    assert_eq!(
+        names(&engine),
+        vec![vec![Some(Identifier::StringLiteral("'bob'".to_string()))]]
+    );
+}
+
+#[test]
+pub fn test_delete_keeps_inc_counter() {
+    let engine = pretest("LMEM");
+
+    // carol holds the largest id, which is not handed out again once she is gone
+    run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE id = 2;", DATABASE_NAME),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('dave', 40) ON users STRUCTURED (name, age) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    let table = TableFile::load(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(
+        table.records.last().unwrap().values[0],
+        Some(Identifier::IntLiteral(3))
+    );
+}
+
+#[test]
+pub fn test_delete_fdelete() {
+    let engine = pretest("FDELETE");
+
+    let res = run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (age >= 30);", DATABASE_NAME),
+    );
+    assert_eq!(res.unwrap(), Output::Count(1));
+
+    // FDELETE tables only mark the record as deleted
+    let table = TableFile::load(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(table.records.len(), 3);
+    assert!(table.records[0].deleted);
+    assert_eq!(names(&engine).len(), 2);
+
+    // Switching to LMEM compacts the deleted records away
+    run(
+        &engine,
+        &format!("ALTER users ON {} MODE LMEM;", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records
+            .len(),
+        2
+    );
+}
+
+#[test]
+pub fn test_delete_referenced() {
+    let engine = pretest("LMEM");
+
+    // bob (1) is referenced by an order
+    let res = run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (age < 13);", DATABASE_NAME),
+    );
+    assert!(res.is_err());
+    assert_eq!(names(&engine).len(), 3);
+
+    let res = run(&engine, &format!("DELETE ON orders ON {};", DATABASE_NAME));
+    assert_eq!(res.unwrap(), Output::Count(1));
+
+    let res = run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (age < 13);", DATABASE_NAME),
+    );
+    assert_eq!(res.unwrap(), Output::Count(2));
+}
@@ -0,0 +1,122 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::lock;
+use arraysql::engine::table_file::TableFile;
+use arraysql::parser::query::ConstraintType;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON id PKEY INC, ON user_id FKEY (ON users) EXISTS) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+
+    engine
+}
+
+#[test]
+pub fn test_drop_database() {
+    let engine = pretest();
+
+    run(&engine, &format!("DROP DATABASE {};", DATABASE_NAME)).unwrap();
+    assert!(!common::ensure_db_existance(&engine, DATABASE_NAME));
+
+    let err = run(&engine, &format!("DROP DATABASE {};", DATABASE_NAME)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    run(
+        &engine,
+        &format!("DROP DATABASE IF EXISTS {};", DATABASE_NAME),
+    )
+    .unwrap();
+}
+
+#[test]
+pub fn test_drop_table() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (0) ON orders STRUCTURED (user_id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let lock_files = [
+        lock::table_path(&engine, DATABASE_NAME, "orders"),
+        lock::writer_path(&engine, DATABASE_NAME, "orders"),
+    ];
+    assert!(lock_files.iter().all(|file| engine.storage().exists(file)));
+
+    run(&engine, &format!("DROP TABLE orders ON {};", DATABASE_NAME)).unwrap();
+    assert!(!lock_files.iter().any(|file| engine.storage().exists(file)));
+    assert!(!common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "orders"
+    ));
+    assert!(common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "users"
+    ));
+
+    let err = run(&engine, &format!("DROP TABLE orders ON {};", DATABASE_NAME)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    run(
+        &engine,
+        &format!("DROP TABLE IF EXISTS orders ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+}
+
+#[test]
+pub fn test_drop_referenced_table() {
+    let engine = pretest();
+
+    let err = run(&engine, &format!("DROP TABLE users ON {};", DATABASE_NAME)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "users"
+    ));
+
+    run(
+        &engine,
+        &format!("DROP TABLE users ON {} CASCADE;", DATABASE_NAME),
+    )
+    .unwrap();
+    assert!(!common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "users"
+    ));
+
+    // The FKEY is gone, the other constraints of the field are kept
+    let orders = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    assert!(orders.references().is_empty());
+    let (_, _, user_id) = &orders.fields[1];
+    assert!(orders.has_constraint(user_id, ConstraintType::Exists));
+
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (7) ON orders STRUCTURED (user_id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+}
@@ -0,0 +1,241 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::{table_file::TableFile, Engine, Output};
+use arraysql::parser::query::{ConstraintType, Identifier};
+
+const TABLE_NAME: &str = "users";
+
+fn alter(engine: &Engine, alteration: &str) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!("ALTER {} ON {} {};", TABLE_NAME, DATABASE_NAME, alteration),
+    )
+}
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE {} ON {} STRUCTURED (UINT(1) id, STRING(8) name) CONSTRAINED (ON id EXISTS PKEY INC) MODE FADD LMEM;",
+        TABLE_NAME, DATABASE_NAME
+    ))
+    .unwrap();
+
+    for name in ["'alice'", "'bob'"] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}) ON {} STRUCTURED (name) ON {};",
+                name, TABLE_NAME, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+
+    engine
+}
+
+fn column(table: &TableFile, field: &str) -> Vec<Option<Identifier>> {
+    let index = table.field_index(field).unwrap();
+    table
+        .records
+        .iter()
+        .map(|record| record.values[index].clone())
+        .collect()
+}
+
+#[test]
+pub fn test_add_field() {
+    let engine = pretest();
+
+    alter(&engine, "ADD UINT(1) age EXISTS DEFAULT (18)").unwrap();
+    alter(&engine, "ADD UINT(2) rank INC").unwrap();
+    alter(&engine, "ADD STRING(8) nickname").unwrap();
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(table.fields.len(), 5);
+    assert_eq!(
+        column(&table, "age"),
+        vec![Some(Identifier::IntLiteral(18)); 2]
+    );
+    assert_eq!(
+        column(&table, "rank"),
+        vec![
+            Some(Identifier::IntLiteral(0)),
+            Some(Identifier::IntLiteral(1))
+        ]
+    );
+    assert_eq!(column(&table, "nickname"), vec![None, None]);
+
+    // Existing records cannot satisfy a required field without a default
+    let err = alter(&engine, "ADD UINT(1) score EXISTS").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = alter(&engine, "ADD UINT(1) age").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('carol') ON {} STRUCTURED (name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(column(&table, "age")[2], Some(Identifier::IntLiteral(18)));
+}
+
+#[test]
+pub fn test_drop_field() {
+    let engine = pretest();
+    alter(&engine, "ADD UINT(1) age DEFAULT (18) SUCHTHAT (age >= 13)").unwrap();
+    alter(&engine, "ADD UINT(1) min_age DEFAULT (13)").unwrap();
+    alter(&engine, "ADD ON age SUCHTHAT (age >= min_age)").unwrap_err();
+    alter(&engine, "DROP ON age SUCHTHAT").unwrap();
+    alter(&engine, "ADD ON age SUCHTHAT (age >= min_age)").unwrap();
+
+    let err = alter(&engine, "DROP min_age").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    alter(&engine, "DROP age").unwrap();
+    alter(&engine, "DROP min_age").unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(table.fields.len(), 2);
+    assert!(table.records.iter().all(|record| record.values.len() == 2));
+
+    let err = alter(&engine, "DROP age").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) user_id) CONSTRAINED (ON user_id FKEY (ON {})) MODE FADD;",
+        DATABASE_NAME, TABLE_NAME
+    ))
+    .unwrap();
+    let err = alter(&engine, "DROP id").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = alter(&engine, "DROP ON id PKEY").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+pub fn test_retype_field() {
+    let engine = pretest();
+
+    let err = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('a long name') ON {} STRUCTURED (name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    alter(&engine, "RETYPE name STRING(255)").unwrap();
+    alter(&engine, "RETYPE id UINT(2)").unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (300, 'a long name') ON {} STRUCTURED (id, name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    for narrowing in [
+        "RETYPE name STRING(8)",
+        "RETYPE id UINT(1)",
+        "RETYPE id STRING(8)",
+    ] {
+        let err = alter(&engine, narrowing).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(table.fields[0].1, vec![Identifier::IntLiteral(2)]);
+    assert_eq!(table.fields[1].1, vec![Identifier::IntLiteral(255)]);
+}
+
+#[test]
+pub fn test_alter_constraints() {
+    let engine = pretest();
+    let name = Identifier::Field("name".to_string());
+
+    alter(&engine, "ADD ON name UNIQUE EXISTS").unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert!(table.has_constraint(&name, ConstraintType::Unique));
+    assert!(table.has_constraint(&name, ConstraintType::Exists));
+
+    let err = alter(&engine, "ADD ON name UNIQUE").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = alter(&engine, "ADD ON name PKEY").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = alter(&engine, "ADD ON name SUCHTHAT (name = 'alice')").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON {} STRUCTURED (name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    alter(&engine, "DROP ON name UNIQUE").unwrap();
+    let err = alter(&engine, "DROP ON name UNIQUE").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON {} STRUCTURED (name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert!(!table.has_constraint(&name, ConstraintType::Unique));
+    assert!(table.has_constraint(&name, ConstraintType::Exists));
+}
+
+#[test]
+pub fn test_add_field_references() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "TABLE tags ON {} STRUCTURED (STRING(8) tag) MODE FADD;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    // The referenced table has to exist, and to have a PKEY
+    let err = alter(&engine, "ADD UINT(1) team_id FKEY (ON teams)").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let err = alter(&engine, "ADD UINT(1) tag_id FKEY (ON tags)").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // Existing records have to refer to live records
+    let err = alter(
+        &engine,
+        "ADD UINT(1) manager_id DEFAULT (7) FKEY (ON users)",
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    alter(
+        &engine,
+        "ADD UINT(1) manager_id DEFAULT (0) FKEY (ON users)",
+    )
+    .unwrap();
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert!(table.has_constraint(
+        &Identifier::Field("manager_id".to_string()),
+        ConstraintType::FKey
+    ));
+    assert_eq!(
+        column(&table, "manager_id"),
+        vec![Some(Identifier::IntLiteral(0)); 2]
+    );
+}
@@ -0,0 +1,738 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::index::{self, BTreeIndex, HashIndex};
+use arraysql::engine::storage::{self, Lock, LockKind, MemoryStorage, Storage};
+use arraysql::engine::table_file::{self, TableFile};
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::query::Identifier;
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name, OPTIONS(a, b) tier) CONSTRAINED (ON id PKEY INC) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON id PKEY INC, ON user_id FKEY (ON users)) MODE FADD FDELETE;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    for (name, tier) in [("'bob'", "b"), ("'alice'", "a"), ("'carol'", "a")] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}, {}) ON users STRUCTURED (name, tier) ON {};",
+                name, tier, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+
+    engine
+}
+
+fn insert_order(engine: &Engine, user_id: u8) {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ({}) ON orders STRUCTURED (user_id) ON {};",
+            user_id, DATABASE_NAME
+        ),
+    )
+    .unwrap();
+}
+
+/// The generation of a table as it is now.
+fn generation(engine: &Engine, table_name: &str) -> u64 {
+    TableFile::load(engine, DATABASE_NAME, table_name)
+        .unwrap()
+        .generation
+}
+
+/// The offsets of the records of a table in its file, deleted records included.
+fn offsets(engine: &Engine, table_name: &str) -> Vec<u64> {
+    table_file::record_offsets(
+        &engine
+            .storage()
+            .read(&table_file::path(engine, DATABASE_NAME, table_name))
+            .unwrap(),
+    )
+}
+
+/// Loads an index the way a read would, against the table as it is now.
+fn load(engine: &Engine, table_name: &str, index_name: &str) -> Option<BTreeIndex> {
+    let generation = generation(engine, table_name);
+    BTreeIndex::load(engine, DATABASE_NAME, table_name, index_name, generation).unwrap()
+}
+
+/// Adds a record to a table the way a write would, but without bringing its indexes up to
+/// date, as if the engine stopped right after writing the table file.
+fn write_behind_indexes(engine: &Engine, table_name: &str, values: &str) {
+    let mut table = TableFile::load(engine, DATABASE_NAME, table_name).unwrap();
+    table.generation += 1;
+    let mut contents = table.serialize(DATABASE_NAME, table_name);
+    contents.push_str(&format!("+ {}\n", values));
+    engine
+        .storage()
+        .write(
+            &table_file::path(engine, DATABASE_NAME, table_name),
+            contents.as_bytes(),
+        )
+        .unwrap();
+}
+
+/// Checks a stored index holds the same entries as one built from the table right now.
+fn assert_current(engine: &Engine, table_name: &str, index_name: &str) {
+    let table = TableFile::load(engine, DATABASE_NAME, table_name).unwrap();
+    let offsets = offsets(engine, table_name);
+    let expected = BTreeIndex::build(&table, table.index(index_name).unwrap(), &offsets).unwrap();
+    assert_eq!(load(engine, table_name, index_name), Some(expected));
+}
+
+fn key(values: &[Identifier]) -> Vec<Option<Identifier>> {
+    values.iter().cloned().map(Some).collect()
+}
+
+fn text(value: &str) -> Identifier {
+    Identifier::StringLiteral(format!("'{}'", value))
+}
+
+#[test]
+fn test_create_index() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_tier ON users ON {} STRUCTURED (tier, name);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    // Records are kept ordered by id, alice, bob then carol
+    let index = load(&engine, "users", "by_tier").unwrap();
+    let tier = |tier: &str| Identifier::Field(tier.to_string());
+    assert_eq!(
+        index.entries.keys().cloned().collect::<Vec<_>>(),
+        vec![
+            key(&[tier("a"), text("alice")]),
+            key(&[tier("a"), text("carol")]),
+            key(&[tier("b"), text("bob")]),
+        ]
+    );
+    assert_eq!(
+        index.get(&key(&[tier("b"), text("bob")])),
+        &[offsets(&engine, "users")[0]]
+    );
+
+    let table = TableFile::load(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(
+        table.index("by_tier").unwrap().to_string(),
+        "by_tier (tier, name)"
+    );
+    assert_eq!(
+        run(&engine, &format!("DESCRIBE users ON {};", DATABASE_NAME)).unwrap(),
+        Output::ResultSet(
+            vec![
+                "field".to_string(),
+                "datatype".to_string(),
+                "constraints".to_string(),
+                "indexes".to_string(),
+                "modes".to_string()
+            ],
+            vec![
+                vec![
+                    Some(text("id")),
+                    Some(text("UINT(1)")),
+                    Some(text("PKEY INC")),
+                    None,
+                    Some(text("FREAD LMEM"))
+                ],
+                vec![
+                    Some(text("name")),
+                    Some(text("STRING(64)")),
+                    None,
+                    Some(text("by_tier (tier, name)")),
+                    Some(text("FREAD LMEM"))
+                ],
+                vec![
+                    Some(text("tier")),
+                    Some(text("OPTIONS(a, b)")),
+                    None,
+                    Some(text("by_tier (tier, name)")),
+                    Some(text("FREAD LMEM"))
+                ],
+            ]
+        )
+    );
+}
+
+#[test]
+fn test_invalid_index() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_name ON users ON {} STRUCTURED (name);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    let cases = [
+        (
+            "INDEX by_name ON users ON {} STRUCTURED (tier);",
+            ErrorKind::AlreadyExists,
+        ),
+        (
+            "INDEX by_age ON users ON {} STRUCTURED (age);",
+            ErrorKind::InvalidInput,
+        ),
+        (
+            "INDEX by_names ON users ON {} STRUCTURED (name, name);",
+            ErrorKind::InvalidInput,
+        ),
+        (
+            "INDEX by_name ON customers ON {} STRUCTURED (name);",
+            ErrorKind::NotFound,
+        ),
+        (
+            "TABLE notes ON {} STRUCTURED (UINT(1) id) MODE FADD INDEXED (by_text (text));",
+            ErrorKind::InvalidInput,
+        ),
+    ];
+    for (query, kind) in cases {
+        let err = run(&engine, &query.replace("{}", DATABASE_NAME)).unwrap_err();
+        assert_eq!(err.kind(), kind, "{}", query);
+    }
+}
+
+#[test]
+fn test_index_maintained() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_user ON orders ON {} STRUCTURED (user_id);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_name ON users ON {} STRUCTURED (name);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    // Appended records only go in their leaf, which the header page and the root still are
+    let file = index::path(&engine, DATABASE_NAME, "orders", "by_user");
+    for user_id in [0, 1, 0, 2] {
+        insert_order(&engine, user_id);
+    }
+    assert_current(&engine, "orders", "by_user");
+    assert_eq!(engine.storage().size(&file).unwrap(), 2 * 1024);
+    let orders = offsets(&engine, "orders");
+    let index = load(&engine, "orders", "by_user").unwrap();
+    assert_eq!(
+        index.get(&key(&[Identifier::IntLiteral(0)])),
+        &[orders[0], orders[2]]
+    );
+
+    run(
+        &engine,
+        &format!(
+            "UPDATE ON orders ON {} SET (user_id = 1) WHERE (id = 0);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_current(&engine, "orders", "by_user");
+
+    run(
+        &engine,
+        &format!("DELETE ON orders ON {} WHERE (user_id = 1);", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_current(&engine, "orders", "by_user");
+    // Records deleted in place are taken out of their leaf, where they stay in the table file
+    assert_eq!(engine.storage().size(&file).unwrap(), 2 * 1024);
+    let index = load(&engine, "orders", "by_user").unwrap();
+    assert_eq!(index.get(&key(&[Identifier::IntLiteral(1)])), &[] as &[u64]);
+    assert_eq!(index.get(&key(&[Identifier::IntLiteral(0)])), &[orders[2]]);
+
+    // Ordered tables move their records around on every write
+    run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (name = 'zoe') WHERE (name = 'alice');",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_current(&engine, "users", "by_name");
+}
+
+#[test]
+fn test_drop_index() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_name ON users ON {} STRUCTURED (name);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let file = index::path(&engine, DATABASE_NAME, "users", "by_name");
+    assert!(engine.storage().exists(&file));
+
+    // Fields of an index stay until the index is dropped
+    let err = run(
+        &engine,
+        &format!("ALTER users ON {} DROP name;", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    run(
+        &engine,
+        &format!("DROP INDEX by_name ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    assert!(!engine.storage().exists(&file));
+    assert!(TableFile::load(&engine, DATABASE_NAME, "users")
+        .unwrap()
+        .indexes
+        .is_empty());
+
+    let err = run(
+        &engine,
+        &format!("DROP INDEX by_name ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    run(
+        &engine,
+        &format!(
+            "DROP INDEX IF EXISTS by_name ON users ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_index_follows_table() {
+    let engine = pretest();
+    run(&engine, &format!(
+        "TABLE notes ON {} STRUCTURED (UINT(1) id, STRING(64) text) CONSTRAINED (ON id PKEY INC) MODE FADD INDEXED (by_text (text));",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('hello') ON notes STRUCTURED (text) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_current(&engine, "notes", "by_text");
+
+    run(&engine, &format!("SNAPSHOT before ON {};", DATABASE_NAME)).unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('world') ON notes STRUCTURED (text) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(&engine, &format!("RESTORE before ON {};", DATABASE_NAME)).unwrap();
+    assert_current(&engine, "notes", "by_text");
+    assert_eq!(load(&engine, "notes", "by_text").unwrap().entries.len(), 1);
+
+    // A table restored on its own gets its indexes built again rather than copied
+    run(
+        &engine,
+        &format!("SNAPSHOT notes_backup ON notes ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('again') ON notes STRUCTURED (text) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!("RESTORE notes_backup ON notes ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_current(&engine, "notes", "by_text");
+    assert_eq!(load(&engine, "notes", "by_text").unwrap().entries.len(), 1);
+    run(
+        &engine,
+        &format!(
+            "RESTORE notes_backup ON notes ON {} INTO old_notes;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_current(&engine, "old_notes", "by_text");
+
+    run(
+        &engine,
+        &format!("RENAME TABLE notes ON {} INTO memos;", DATABASE_NAME),
+    )
+    .unwrap();
+    assert!(!engine
+        .storage()
+        .exists(&index::path(&engine, DATABASE_NAME, "notes", "by_text")));
+    assert_current(&engine, "memos", "by_text");
+
+    run(&engine, &format!("DROP TABLE memos ON {};", DATABASE_NAME)).unwrap();
+    assert!(!engine
+        .storage()
+        .exists(&index::path(&engine, DATABASE_NAME, "memos", "by_text")));
+}
+
+#[test]
+fn test_stale_index() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_name ON users ON {} STRUCTURED (name);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    // A table changed behind the index's back leaves the index unused
+    write_behind_indexes(&engine, "users", "7, 'dave', b");
+    assert_eq!(load(&engine, "users", "by_name"), None);
+}
+
+/// Loads a hash index the way a read would, against the table as it is now.
+fn load_hash(engine: &Engine, table_name: &str, index_name: &str) -> Option<HashIndex> {
+    let generation = generation(engine, table_name);
+    HashIndex::load(engine, DATABASE_NAME, table_name, index_name, generation).unwrap()
+}
+
+/// Looks a key up in a hash index the way a read would, against the table as it is now.
+fn lookup(engine: &Engine, table_name: &str, index_name: &str, user_id: i32) -> Option<Vec<u64>> {
+    let generation = generation(engine, table_name);
+    let key = key(&[Identifier::IntLiteral(user_id)]);
+    HashIndex::lookup(
+        engine,
+        DATABASE_NAME,
+        table_name,
+        index_name,
+        generation,
+        &key,
+    )
+    .unwrap()
+}
+
+fn assert_hash_current(engine: &Engine, table_name: &str, index_name: &str) {
+    let table = TableFile::load(engine, DATABASE_NAME, table_name).unwrap();
+    let offsets = offsets(engine, table_name);
+    let expected = HashIndex::build(&table, table.index(index_name).unwrap(), &offsets).unwrap();
+    assert_eq!(load_hash(engine, table_name, index_name), Some(expected));
+}
+
+#[test]
+fn test_hash_index() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "INDEX by_user ON orders ON {} STRUCTURED (user_id) HASH;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    assert_eq!(
+        table.index("by_user").unwrap().to_string(),
+        "by_user (user_id) HASH"
+    );
+
+    // Appended records only add an entry to the index, after its 16 buckets
+    let file = index::path(&engine, DATABASE_NAME, "orders", "by_user");
+    for user_id in [0, 1, 0, 2] {
+        insert_order(&engine, user_id);
+    }
+    assert_hash_current(&engine, "orders", "by_user");
+    let stored = engine.storage().read_to_string(&file).unwrap();
+    assert_eq!(stored.lines().count(), 2 + 16 + 4);
+    let orders = offsets(&engine, "orders");
+    assert!(stored.ends_with(&format!("{};2\n", orders[3])));
+    assert_eq!(
+        lookup(&engine, "orders", "by_user", 0),
+        Some(vec![orders[0], orders[2]])
+    );
+    assert_eq!(lookup(&engine, "orders", "by_user", 3), Some(Vec::new()));
+
+    run(
+        &engine,
+        &format!("DELETE ON orders ON {} WHERE (user_id = 0);", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_hash_current(&engine, "orders", "by_user");
+    assert_eq!(lookup(&engine, "orders", "by_user", 0), Some(Vec::new()));
+    insert_order(&engine, 0);
+    assert_hash_current(&engine, "orders", "by_user");
+    assert_eq!(
+        lookup(&engine, "orders", "by_user", 0),
+        Some(vec![offsets(&engine, "orders")[4]])
+    );
+
+    // An index holding twice as many entries as buckets is built again, with a single entry
+    // for each key, and the 4 records appended since
+    for _ in 0..30 {
+        insert_order(&engine, 1);
+    }
+    assert_hash_current(&engine, "orders", "by_user");
+    let stored = engine.storage().read_to_string(&file).unwrap();
+    assert_eq!(stored.lines().count(), 2 + 16 + 3 + 4);
+    assert_eq!(lookup(&engine, "orders", "by_user", 1).unwrap().len(), 31);
+}
+
+#[test]
+fn test_stale_hash_index() {
+    let engine = pretest();
+    run(&engine, &format!(
+        "TABLE notes ON {} STRUCTURED (UINT(1) id, STRING(64) text) CONSTRAINED (ON id PKEY INC) MODE FADD INDEXED (by_text (text) HASH);",
+        DATABASE_NAME
+    ))
+    .unwrap();
+
+    // Records appended after the table changed behind the index's back keep it unused
+    write_behind_indexes(&engine, "notes", "7, 'hello'");
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('world') ON notes STRUCTURED (text) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_eq!(load_hash(&engine, "notes", "by_text"), None);
+}
+
+/// Creates a table of notes, indexed by their text, holding `count` notes added out of order.
+fn insert_notes(engine: &Engine, count: usize) {
+    run(engine, &format!(
+        "TABLE notes ON {} STRUCTURED (UINT(2) id, STRING(64) text) CONSTRAINED (ON id PKEY INC) MODE FADD FDELETE INDEXED (by_text (text));",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    for i in 0..count {
+        run(
+            engine,
+            &format!(
+                "INSERT STRUCTURED ('note number {:04}') ON notes STRUCTURED (text) ON {};",
+                i * 37 % count,
+                DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+}
+
+fn read_notes(engine: &Engine, conditions: &str) -> Vec<Vec<Option<Identifier>>> {
+    let Output::ResultSet(_, rows) = run(
+        engine,
+        &format!(
+            "READ STRUCTURED (id) ON notes ON {} WHERE ({});",
+            DATABASE_NAME, conditions
+        ),
+    )
+    .unwrap() else {
+        panic!("READ returns a result set");
+    };
+    rows
+}
+
+#[test]
+fn test_index_pages() {
+    let engine = pretest();
+    insert_notes(&engine, 200);
+
+    // Leaves split as they fill up, under branches pointing to them
+    assert_current(&engine, "notes", "by_text");
+    let file = index::path(&engine, DATABASE_NAME, "notes", "by_text");
+    assert!(engine.storage().size(&file).unwrap() > 8 * 1024);
+    let stored = engine.storage().read_to_string(&file).unwrap();
+    assert!(stored.lines().any(|line| line == "B"));
+
+    // Note 74 is the third one added, and notes 10 to 19 are added every 37th
+    assert_eq!(
+        read_notes(&engine, "text = 'note number 0074'"),
+        vec![vec![Some(Identifier::IntLiteral(2))]]
+    );
+    assert_eq!(
+        read_notes(
+            &engine,
+            "text >= 'note number 0010' AND text < 'note number 0020'"
+        )
+        .len(),
+        10
+    );
+
+    run(
+        &engine,
+        &format!(
+            "DELETE ON notes ON {} WHERE (text < 'note number 0100');",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_current(&engine, "notes", "by_text");
+    assert_eq!(
+        read_notes(&engine, "text = 'note number 0074'"),
+        Vec::<Vec<_>>::new()
+    );
+    assert_eq!(read_notes(&engine, "text <= 'note number 0150'").len(), 51);
+}
+
+/// A storage in memory counting the bytes read from every file, and the files read whole.
+#[derive(Debug, Default)]
+struct Reads {
+    inner: MemoryStorage,
+    read: Arc<Mutex<HashMap<PathBuf, usize>>>,
+    whole: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl Reads {
+    fn note(&self, path: &Path, len: usize) {
+        *self
+            .read
+            .lock()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_default() += len;
+    }
+}
+
+impl Storage for Reads {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        let contents = self.inner.read(path)?;
+        self.note(path, contents.len());
+        self.whole.lock().unwrap().push(path.to_path_buf());
+        Ok(contents)
+    }
+
+    fn read_at(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, io::Error> {
+        let contents = self.inner.read_at(path, offset, len)?;
+        self.note(path, contents.len());
+        Ok(contents)
+    }
+
+    fn size(&self, path: &Path) -> Result<u64, io::Error> {
+        self.inner.size(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        self.inner.write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        self.inner.append(path, contents)
+    }
+
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error> {
+        self.inner.patch(path, patches)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        self.inner.rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.remove_dir_all(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<storage::Entry>, io::Error> {
+        self.inner.list(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.inner.is_dir(path)
+    }
+
+    fn try_lock(&self, path: &Path, kind: LockKind) -> Result<Option<Lock>, io::Error> {
+        self.inner.try_lock(path, kind)
+    }
+}
+
+#[test]
+fn test_index_read_skips_table() {
+    let reads = Reads::default();
+    let (read, whole) = (reads.read.clone(), reads.whole.clone());
+    let engine = Engine::new("/", reads).unwrap();
+    run(&engine, &format!("DATABASE {};", DATABASE_NAME)).unwrap();
+    insert_notes(&engine, 200);
+    let table = table_file::path(&engine, DATABASE_NAME, "notes");
+    let file = index::path(&engine, DATABASE_NAME, "notes", "by_text");
+    let size = engine.storage().size(&file).unwrap() as usize;
+
+    // Only the pages on the way to the key and the record found are read
+    read.lock().unwrap().clear();
+    whole.lock().unwrap().clear();
+    assert_eq!(read_notes(&engine, "text = 'note number 0074'").len(), 1);
+    assert!(!whole.lock().unwrap().contains(&table));
+    assert!(read.lock().unwrap()[&file] <= 4 * 1024);
+    assert!(read.lock().unwrap()[&file] < size / 2);
+    assert!(read.lock().unwrap()[&table] < 1024);
+
+    // Scans read the table whole
+    read.lock().unwrap().clear();
+    assert_eq!(read_notes(&engine, "id = 2").len(), 1);
+    assert!(whole.lock().unwrap().contains(&table));
+    assert!(!read.lock().unwrap().contains_key(&file));
+
+    // Hash lookups only read the bucket of the key and its entries
+    run(
+        &engine,
+        &format!(
+            "INDEX by_hash ON notes ON {} STRUCTURED (text) HASH;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let file = index::path(&engine, DATABASE_NAME, "notes", "by_hash");
+    read.lock().unwrap().clear();
+    whole.lock().unwrap().clear();
+    assert_eq!(read_notes(&engine, "text = 'note number 0074'").len(), 1);
+    assert!(!whole.lock().unwrap().contains(&table));
+    assert!(read.lock().unwrap()[&file] < 1024);
+    assert!(read.lock().unwrap()[&table] < 1024);
+}
@@ -0,0 +1,138 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::Output;
+use arraysql::parser::query::Identifier;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name, OPTIONS(a, b) tier) CONSTRAINED (ON id EXISTS PKEY INC) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON user_id FKEY (ON users)) MODE FADD FDELETE;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+
+    engine
+}
+
+fn text(value: &str) -> Option<Identifier> {
+    Some(Identifier::StringLiteral(format!("'{}'", value)))
+}
+
+#[test]
+pub fn test_show_databases() {
+    let engine = pretest();
+
+    // Directories without a catalog are not databases
+    engine
+        .storage()
+        .create_dir(&engine.root().join("notes"))
+        .unwrap();
+    run(&engine, "DATABASE archive;").unwrap();
+
+    assert_eq!(
+        run(&engine, "SHOW DATABASES;").unwrap(),
+        Output::ResultSet(
+            vec!["database".to_string()],
+            vec![vec![text("archive")], vec![text(DATABASE_NAME)]]
+        )
+    );
+}
+
+#[test]
+pub fn test_show_tables() {
+    let engine = pretest();
+
+    assert_eq!(
+        run(&engine, &format!("SHOW TABLES ON {};", DATABASE_NAME)).unwrap(),
+        Output::ResultSet(
+            vec!["table".to_string(), "modes".to_string()],
+            vec![
+                vec![text("orders"), text("FADD FDELETE")],
+                vec![text("users"), text("FREAD LMEM")],
+            ]
+        )
+    );
+
+    let err = run(&engine, "SHOW TABLES ON missing_database;").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+pub fn test_describe() {
+    let engine = pretest();
+
+    assert_eq!(
+        run(&engine, &format!("DESCRIBE users ON {};", DATABASE_NAME)).unwrap(),
+        Output::ResultSet(
+            vec![
+                "field".to_string(),
+                "datatype".to_string(),
+                "constraints".to_string(),
+                "indexes".to_string(),
+                "modes".to_string()
+            ],
+            vec![
+                vec![
+                    text("id"),
+                    text("UINT(1)"),
+                    text("EXISTS PKEY INC"),
+                    None,
+                    text("FREAD LMEM")
+                ],
+                vec![
+                    text("name"),
+                    text("STRING(64)"),
+                    None,
+                    None,
+                    text("FREAD LMEM")
+                ],
+                vec![
+                    text("tier"),
+                    text("OPTIONS(a, b)"),
+                    None,
+                    None,
+                    text("FREAD LMEM")
+                ],
+            ]
+        )
+    );
+    assert_eq!(
+        run(&engine, &format!("DESCRIBE orders ON {};", DATABASE_NAME)).unwrap(),
+        Output::ResultSet(
+            vec![
+                "field".to_string(),
+                "datatype".to_string(),
+                "constraints".to_string(),
+                "indexes".to_string(),
+                "modes".to_string()
+            ],
+            vec![
+                vec![
+                    text("id"),
+                    text("UINT(1)"),
+                    None,
+                    None,
+                    text("FADD FDELETE")
+                ],
+                vec![
+                    text("user_id"),
+                    text("UINT(1)"),
+                    text("FKEY (ON users)"),
+                    None,
+                    text("FADD FDELETE")
+                ],
+            ]
+        )
+    );
+
+    let err = run(
+        &engine,
+        &format!("DESCRIBE customers ON {};", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
@@ -0,0 +1,205 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::lock;
+use arraysql::engine::storage::LockKind;
+use arraysql::engine::{Engine, Output};
+use std::io::ErrorKind;
+use std::thread;
+use std::time::Duration;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON id PKEY INC, ON user_id FKEY (ON users)) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "TABLE notes ON {} STRUCTURED (UINT(1) id, STRING(64) text) CONSTRAINED (ON id PKEY INC) MODE FADD;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    insert_user(&engine).unwrap();
+
+    engine
+}
+
+/// Another engine on the same root, as another process would open it.
+fn other(engine: &Engine) -> Engine {
+    Engine::open(engine.root())
+        .unwrap()
+        .with_lock_timeout(Duration::from_millis(50))
+}
+
+fn insert_user(engine: &Engine) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+}
+
+fn read_users(engine: &Engine) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+    )
+}
+
+fn assert_locked(result: Result<Output, std::io::Error>) {
+    let error = result.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+    assert_eq!(
+        error.to_string(),
+        format!("Database {} is locked", DATABASE_NAME)
+    );
+}
+
+#[test]
+fn test_exclusive_database_lock() {
+    let engine = pretest();
+    let other = other(&engine);
+
+    let _lock = lock::acquire(
+        &engine,
+        DATABASE_NAME,
+        &lock::database_path(&engine, DATABASE_NAME),
+        LockKind::Exclusive,
+    )
+    .unwrap();
+    assert_locked(read_users(&other));
+    assert_locked(insert_user(&other));
+    assert_locked(run(&other, &format!("SHOW TABLES ON {};", DATABASE_NAME)));
+
+    // Statements not using the database go ahead
+    run(&other, "SHOW DATABASES;").unwrap();
+    run(&other, "DATABASE other_database;").unwrap();
+}
+
+#[test]
+fn test_shared_table_lock() {
+    let engine = pretest();
+    let other = other(&engine);
+
+    let _lock = lock::acquire(
+        &engine,
+        DATABASE_NAME,
+        &lock::table_path(&engine, DATABASE_NAME, "users"),
+        LockKind::Shared,
+    )
+    .unwrap();
+
+    // Readers share the table, writers wait for it
+    read_users(&other).unwrap();
+    assert_locked(insert_user(&other));
+
+    // Writers only checking their references against it go ahead
+    run(
+        &other,
+        &format!(
+            "INSERT STRUCTURED (0) ON orders STRUCTURED (user_id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_exclusive_table_lock() {
+    let engine = pretest();
+    let other = other(&engine);
+
+    let _lock = lock::acquire(
+        &engine,
+        DATABASE_NAME,
+        &lock::table_path(&engine, DATABASE_NAME, "users"),
+        LockKind::Exclusive,
+    )
+    .unwrap();
+    assert_locked(read_users(&other));
+    assert_locked(run(
+        &other,
+        &format!(
+            "INSERT STRUCTURED (0) ON orders STRUCTURED (user_id) ON {};",
+            DATABASE_NAME
+        ),
+    ));
+
+    // Unrelated tables and the database itself are left free
+    run(
+        &other,
+        &format!(
+            "INSERT STRUCTURED ('hello') ON notes STRUCTURED (text) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(&other, &format!("DESCRIBE orders ON {};", DATABASE_NAME)).unwrap();
+}
+
+#[test]
+fn test_waiting_for_lock() {
+    let engine = pretest();
+    let other = Engine::open(engine.root()).unwrap();
+
+    let lock = lock::acquire(
+        &engine,
+        DATABASE_NAME,
+        &lock::database_path(&engine, DATABASE_NAME),
+        LockKind::Exclusive,
+    )
+    .unwrap();
+    let release = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        drop(lock);
+    });
+
+    insert_user(&other).unwrap();
+    release.join().unwrap();
+    match read_users(&engine).unwrap() {
+        Output::ResultSet(_, rows) => assert_eq!(rows.len(), 2),
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+#[test]
+fn test_locks_released() {
+    let engine = pretest();
+    let other = other(&engine);
+
+    insert_user(&engine).unwrap();
+    insert_user(&other).unwrap();
+    run(
+        &engine,
+        &format!("SNAPSHOT my_snapshot ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    read_users(&other).unwrap();
+}
+
+#[test]
+fn test_in_memory_locks() {
+    let engine = common::memory_database();
+
+    let path = lock::database_path(&engine, DATABASE_NAME);
+    let _first = engine
+        .storage()
+        .try_lock(&path, LockKind::Exclusive)
+        .unwrap()
+        .unwrap();
+    assert!(engine
+        .storage()
+        .try_lock(&path, LockKind::Exclusive)
+        .unwrap()
+        .is_some());
+    run(&engine, &format!("SHOW TABLES ON {};", DATABASE_NAME)).unwrap();
+}
@@ -3,3 +3,60 @@ mod database_creation;
 
 #[cfg(test)]
 mod table_creation;
+
+#[cfg(test)]
+mod mode_alteration;
+
+#[cfg(test)]
+mod field_alteration;
+
+#[cfg(test)]
+mod snapshots;
+
+#[cfg(test)]
+mod reading;
+
+#[cfg(test)]
+mod deleting;
+
+#[cfg(test)]
+mod updating;
+
+#[cfg(test)]
+mod dropping;
+
+#[cfg(test)]
+mod renaming;
+
+#[cfg(test)]
+mod catalog;
+
+#[cfg(test)]
+mod introspection;
+
+#[cfg(test)]
+mod storage;
+
+#[cfg(test)]
+mod wal;
+
+#[cfg(test)]
+mod transactions;
+
+#[cfg(test)]
+mod locking;
+
+#[cfg(test)]
+mod sharing;
+
+#[cfg(test)]
+mod versions;
+
+#[cfg(test)]
+mod indexes;
+
+#[cfg(test)]
+mod planning;
+
+#[cfg(test)]
+mod statistics;
@@ -0,0 +1,121 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::table_file::TableFile;
+use arraysql::parser::query::{Identifier, Mode};
+
+const TABLE_NAME: &str = "users";
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE {} ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id EXISTS PKEY INC) MODE FADD LMEM;",
+        TABLE_NAME, DATABASE_NAME
+    ))
+    .unwrap();
+
+    for (id, name) in [(3, "'carol'"), (1, "'alice'"), (2, "'bob'")] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}, {}) ON {} STRUCTURED (id, name) ON {};",
+                id, name, TABLE_NAME, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+
+    engine
+}
+
+fn ids(table: &TableFile) -> Vec<Option<Identifier>> {
+    table
+        .records
+        .iter()
+        .map(|record| record.values[0].clone())
+        .collect()
+}
+
+#[test]
+pub fn test_alter_to_fread() {
+    let engine = pretest();
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(
+        ids(&table),
+        vec![
+            Some(Identifier::IntLiteral(3)),
+            Some(Identifier::IntLiteral(1)),
+            Some(Identifier::IntLiteral(2))
+        ]
+    );
+
+    let res = run(
+        &engine,
+        &format!("ALTER {} ON {} MODE FREAD;", TABLE_NAME, DATABASE_NAME),
+    );
+    assert!(res.is_ok());
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(table.modes, vec![Mode::Fread, Mode::Lmem]);
+    assert_eq!(
+        ids(&table),
+        vec![
+            Some(Identifier::IntLiteral(1)),
+            Some(Identifier::IntLiteral(2)),
+            Some(Identifier::IntLiteral(3))
+        ]
+    );
+    assert!(table.pkey().is_some());
+
+    // Inserts into FREAD tables keep the primary key order
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('dave') ON {} STRUCTURED (name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(
+        table.records.last().unwrap().values,
+        vec![
+            Some(Identifier::IntLiteral(4)),
+            Some(Identifier::StringLiteral("'dave'".to_string()))
+        ]
+    );
+}
+
+#[test]
+pub fn test_alter_conflicting_modes() {
+    let engine = pretest();
+
+    let res = run(
+        &engine,
+        &format!("ALTER {} ON {} MODE FADD FREAD;", TABLE_NAME, DATABASE_NAME),
+    );
+    assert!(res.is_err());
+
+    let table = TableFile::load(&engine, DATABASE_NAME, TABLE_NAME).unwrap();
+    assert_eq!(table.modes, vec![Mode::Fadd, Mode::Lmem]);
+}
+
+#[test]
+pub fn test_duplicate_pkey_rejected() {
+    let engine = pretest();
+
+    let res = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (1, 'eve') ON {} STRUCTURED (id, name) ON {};",
+            TABLE_NAME, DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, TABLE_NAME)
+            .unwrap()
+            .records
+            .len(),
+        3
+    );
+}
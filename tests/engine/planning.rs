@@ -0,0 +1,354 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::index;
+use arraysql::engine::plan::{self, Access, Plan};
+use arraysql::engine::table_file::TableFile;
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::{self, query::Identifier};
+use std::ops::Bound;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FREAD INDEXED (by_name (name) HASH);",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id, UINT(1) amount) CONSTRAINED (ON id PKEY INC) MODE FADD FDELETE INDEXED (by_user (user_id, amount));",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    for name in ["'bob'", "'alice'", "'carol'"] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}) ON users STRUCTURED (name) ON {};",
+                name, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+    for i in 0..12 {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}, {}) ON orders STRUCTURED (user_id, amount) ON {};",
+                i % 3,
+                i,
+                DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+
+    engine
+}
+
+/// Plans a `READ` of a table with some conditions.
+fn plan_for(engine: &Engine, table_name: &str, conditions: &str) -> Plan {
+    let mut p = parser::Parser::new();
+    p.set_query(format!(
+        "READ STRUCTURED (id) ON {} ON {} WHERE ({});",
+        table_name, DATABASE_NAME, conditions
+    ))
+    .parse();
+    let table = TableFile::load(engine, DATABASE_NAME, table_name).unwrap();
+    let count = table.live_records().count();
+    plan::plan(&table, count, &p.query_data.conditions, None)
+}
+
+fn int(value: i32) -> Identifier {
+    Identifier::IntLiteral(value)
+}
+
+fn read_orders(engine: &Engine, conditions: &str) -> Output {
+    run(
+        engine,
+        &format!(
+            "READ STRUCTURED (id, user_id, amount) ON orders ON {} WHERE ({});",
+            DATABASE_NAME, conditions
+        ),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_plan_access() {
+    let engine = pretest();
+    let users = TableFile::load(&engine, DATABASE_NAME, "users").unwrap();
+    let orders = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    let by_name = users.index("by_name").unwrap().clone();
+    let by_user = orders.index("by_user").unwrap().clone();
+
+    let cases = [
+        (
+            "users",
+            "id = 1",
+            Access::Pkey(Bound::Included(int(1)), Bound::Included(int(1))),
+        ),
+        (
+            "users",
+            "id >= 1 AND 3 > id AND id > 0",
+            Access::Pkey(Bound::Included(int(1)), Bound::Excluded(int(3))),
+        ),
+        (
+            "users",
+            "name = 'bob'",
+            Access::IndexLookup(
+                by_name.clone(),
+                vec![Some(Identifier::StringLiteral("'bob'".to_string()))],
+            ),
+        ),
+        // The index reads a single record from the table file, while the primary key is
+        // searched for once the whole table is read
+        (
+            "users",
+            "name = 'bob' AND id = 2",
+            Access::IndexLookup(
+                by_name.clone(),
+                vec![Some(Identifier::StringLiteral("'bob'".to_string()))],
+            ),
+        ),
+        // Hash indexes cannot be ranged over
+        ("users", "name > 'bob'", Access::Scan),
+        (
+            "orders",
+            "user_id = 1 AND amount = 4",
+            Access::IndexLookup(by_user.clone(), vec![Some(int(1)), Some(int(4))]),
+        ),
+        (
+            "orders",
+            "user_id = 1 AND amount > 4",
+            Access::IndexRange(
+                by_user.clone(),
+                vec![Some(int(1))],
+                Bound::Excluded(int(4)),
+                Bound::Unbounded,
+            ),
+        ),
+        (
+            "orders",
+            "user_id <= 1",
+            Access::IndexRange(
+                by_user.clone(),
+                Vec::new(),
+                Bound::Unbounded,
+                Bound::Included(int(1)),
+            ),
+        ),
+        // Only the leading fields of an index narrow its keys down
+        ("orders", "amount = 4", Access::Scan),
+        // FADD tables are not ordered by their primary key
+        ("orders", "id = 4", Access::Scan),
+        ("orders", "user_id = 1 OR amount = 4", Access::Scan),
+        ("orders", "NOT user_id = 1", Access::Scan),
+    ];
+    for (table_name, conditions, access) in cases {
+        assert_eq!(
+            plan_for(&engine, table_name, conditions).access,
+            access,
+            "{}",
+            conditions
+        );
+    }
+
+    assert_eq!(
+        plan_for(&engine, "orders", "user_id = 1 AND amount > 4 AND id < 9").pushed,
+        plan::predicates(&{
+            let mut p = parser::Parser::new();
+            p.set_query(format!(
+                "READ STRUCTURED (id) ON orders ON {} WHERE (user_id = 1 AND amount > 4);",
+                DATABASE_NAME
+            ))
+            .parse();
+            p.query_data.conditions
+        })
+    );
+}
+
+#[test]
+fn test_planned_reads() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!("DELETE ON orders ON {} WHERE (amount = 4);", DATABASE_NAME),
+    )
+    .unwrap();
+
+    let conditions = [
+        "user_id = 1",
+        "user_id = 1 AND amount = 4",
+        "user_id = 1 AND amount = 7",
+        "user_id = 2 AND amount >= 5 AND amount < 11",
+        "user_id > 0 AND user_id <= 1",
+        "user_id >= 1 AND user_id < 1",
+        "user_id = 0 AND id > 2",
+        "user_id = 5",
+    ];
+    let planned: Vec<Output> = conditions
+        .iter()
+        .map(|conditions| read_orders(&engine, conditions))
+        .collect();
+
+    // Without the index, every read scans the table
+    run(
+        &engine,
+        &format!("DROP INDEX by_user ON orders ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    for (conditions, planned) in conditions.iter().zip(planned) {
+        assert_eq!(plan_for(&engine, "orders", conditions).access, Access::Scan);
+        assert_eq!(read_orders(&engine, conditions), planned, "{}", conditions);
+    }
+
+    let Output::ResultSet(_, rows) = read_orders(&engine, "user_id = 1 AND amount >= 4") else {
+        panic!("Expected a result set");
+    };
+    assert_eq!(
+        rows.iter().map(|row| row[2].clone()).collect::<Vec<_>>(),
+        vec![Some(int(7)), Some(int(10))]
+    );
+
+    let read_users = |conditions: &str| {
+        run(
+            &engine,
+            &format!(
+                "READ STRUCTURED (id, name) ON users ON {} WHERE ({});",
+                DATABASE_NAME, conditions
+            ),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        read_users("id > 0 AND id <= 1"),
+        Output::ResultSet(
+            vec!["id".to_string(), "name".to_string()],
+            vec![vec![
+                Some(int(1)),
+                Some(Identifier::StringLiteral("'alice'".to_string()))
+            ]]
+        )
+    );
+    assert_eq!(
+        read_users("name = 'carol'"),
+        Output::ResultSet(
+            vec!["id".to_string(), "name".to_string()],
+            vec![vec![
+                Some(int(2)),
+                Some(Identifier::StringLiteral("'carol'".to_string()))
+            ]]
+        )
+    );
+}
+
+#[test]
+fn test_stale_index_scanned() {
+    let engine = pretest();
+    let expected = read_orders(&engine, "user_id = 1");
+
+    // An index that missed changes to its table is passed over for a scan
+    let file = index::path(&engine, DATABASE_NAME, "orders", "by_user");
+    let stored = engine.storage().read_to_string(&file).unwrap();
+    let (_, entries) = stored.split_once('\n').unwrap();
+    engine
+        .storage()
+        .write(&file, format!("0000000000000000\n{}", entries).as_bytes())
+        .unwrap();
+    assert_eq!(read_orders(&engine, "user_id = 1"), expected);
+
+    engine.storage().remove_file(&file).unwrap();
+    assert_eq!(read_orders(&engine, "user_id = 1"), expected);
+}
+
+fn explain(engine: &Engine, query: &str) -> Vec<(String, String, i32)> {
+    let Output::ResultSet(columns, rows) = run(
+        engine,
+        &format!("EXPLAIN {}", query.replace("{}", DATABASE_NAME)),
+    )
+    .unwrap() else {
+        panic!("Expected a result set");
+    };
+    assert_eq!(columns, vec!["operator", "detail", "rows"]);
+    rows.into_iter()
+        .map(|row| match &row[..] {
+            [Some(Identifier::StringLiteral(operator)), Some(Identifier::StringLiteral(detail)), Some(Identifier::IntLiteral(rows))] => {
+                (operator.clone(), detail.clone(), *rows)
+            }
+            row => panic!("Unexpected row {:?}", row),
+        })
+        .collect()
+}
+
+/// A row of `EXPLAIN`, its operator and detail quoted the way `READ` returns strings.
+fn operator(operator: &str, detail: &str, rows: i32) -> (String, String, i32) {
+    (format!("'{}'", operator), format!("'{}'", detail), rows)
+}
+
+#[test]
+fn test_explain() {
+    let engine = pretest();
+    assert_eq!(
+        explain(
+            &engine,
+            "READ STRUCTURED (id, amount) ON orders ON {} WHERE (user_id = 1 AND amount > 4 AND id < 9);"
+        ),
+        vec![
+            operator(
+                "INDEX RANGE",
+                "by_user (user_id, amount) ON user_id = 1 AND amount > 4",
+                1
+            ),
+            operator("FILTER", "id < 9", 1),
+            operator("PROJECT", "id, amount", 1),
+        ]
+    );
+    assert_eq!(
+        explain(
+            &engine,
+            "READ STRUCTURED (name) ON users ON {} WHERE (1 = id);"
+        ),
+        vec![
+            operator("PKEY LOOKUP", "id = 1", 1),
+            operator("PROJECT", "name", 1),
+        ]
+    );
+    assert_eq!(
+        explain(
+            &engine,
+            "READ STRUCTURED (name) ON users ON {} WHERE (name = 'bob');"
+        ),
+        vec![
+            operator("INDEX LOOKUP", "by_name (name) HASH ON name = 'bob'", 1),
+            operator("PROJECT", "name", 1),
+        ]
+    );
+    assert_eq!(
+        explain(&engine, "READ STRUCTURED (id) ON orders ON {};"),
+        vec![
+            operator("SCAN", "orders", 12),
+            operator("PROJECT", "id", 12)
+        ]
+    );
+    assert_eq!(
+        explain(
+            &engine,
+            "READ STRUCTURED (id) ON orders ON {} WHERE (amount = 4 OR id = 2);"
+        ),
+        vec![
+            operator("SCAN", "orders", 12),
+            operator("FILTER", "amount = 4 OR id = 2", 6),
+            operator("PROJECT", "id", 6),
+        ]
+    );
+
+    // Explaining a read checks the table and fields it reads like the read would
+    let err = run(
+        &engine,
+        &format!(
+            "EXPLAIN READ STRUCTURED (age) ON users ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
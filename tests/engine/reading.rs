@@ -0,0 +1,147 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::Output;
+use arraysql::parser::query::Identifier;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name, UINT(1) age) CONSTRAINED (ON id PKEY INC) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    for (name, age) in [("'alice'", "31"), ("'bob'", "12")] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}, {}) ON users STRUCTURED (name, age) ON {};",
+                name, age, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('carol') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    engine
+}
+
+fn literal(data: &str) -> Option<Identifier> {
+    Some(Identifier::StringLiteral(data.to_string()))
+}
+
+#[test]
+pub fn test_read() {
+    let engine = pretest();
+
+    let res = run(
+        &engine,
+        &format!("READ STRUCTURED (name, age) ON users ON {};", DATABASE_NAME),
+    );
+    assert_eq!(
+        res.unwrap(),
+        Output::ResultSet(
+            vec!["name".to_string(), "age".to_string()],
+            vec![
+                vec![literal("'alice'"), Some(Identifier::IntLiteral(31))],
+                vec![literal("'bob'"), Some(Identifier::IntLiteral(12))],
+                vec![literal("'carol'"), None],
+            ]
+        )
+    );
+
+    let res = run(
+        &engine,
+        &format!("READ STRUCTURED (missing) ON users ON {};", DATABASE_NAME),
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+pub fn test_read_where() {
+    let engine = pretest();
+
+    let res = run(
+        &engine,
+        &format!(
+            "READ STRUCTURED (name) ON users ON {} WHERE (age >= 13);",
+            DATABASE_NAME
+        ),
+    );
+    assert_eq!(
+        res.unwrap(),
+        Output::ResultSet(vec!["name".to_string()], vec![vec![literal("'alice'")]])
+    );
+
+    let res = run(
+        &engine,
+        &format!(
+        "READ STRUCTURED (name) ON users ON {} WHERE (name = 'carol' OR EXISTS age AND age < 13);",
+        DATABASE_NAME
+    ),
+    );
+    assert_eq!(
+        res.unwrap(),
+        Output::ResultSet(
+            vec!["name".to_string()],
+            vec![vec![literal("'bob'")], vec![literal("'carol'")]]
+        )
+    );
+}
+
+#[test]
+pub fn test_read_at_snapshot() {
+    let engine = pretest();
+
+    run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME)).unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('dave', 40) ON users STRUCTURED (name, age) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    let res = run(
+        &engine,
+        &format!(
+            "READ STRUCTURED (name) ON users ON {} AT first WHERE (age >= 13);",
+            DATABASE_NAME
+        ),
+    );
+    assert_eq!(
+        res.unwrap(),
+        Output::ResultSet(vec!["name".to_string()], vec![vec![literal("'alice'")]])
+    );
+
+    // The live table is left as it is
+    let res = run(
+        &engine,
+        &format!(
+            "READ STRUCTURED (name) ON users ON {} WHERE (age >= 13);",
+            DATABASE_NAME
+        ),
+    );
+    assert_eq!(
+        res.unwrap(),
+        Output::ResultSet(
+            vec!["name".to_string()],
+            vec![vec![literal("'alice'")], vec![literal("'dave'")]]
+        )
+    );
+
+    let res = run(
+        &engine,
+        &format!(
+            "READ STRUCTURED (name) ON users ON {} AT missing;",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+}
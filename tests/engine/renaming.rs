@@ -0,0 +1,141 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::table_file::TableFile;
+use arraysql::parser::query::Identifier;
+
+const NEW_DATABASE_NAME: &str = "my_renamed_database";
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, UINT(1) invited_by) CONSTRAINED (ON id PKEY INC, ON invited_by FKEY (ON users)) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON id PKEY INC, ON user_id FKEY (ON users)) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (0) ON users STRUCTURED (id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (0) ON orders STRUCTURED (user_id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    engine
+}
+
+#[test]
+pub fn test_rename_table() {
+    let engine = pretest();
+
+    run(
+        &engine,
+        &format!("RENAME TABLE users ON {} INTO customers;", DATABASE_NAME),
+    )
+    .unwrap();
+    assert!(!common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "users"
+    ));
+    assert!(common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "customers"
+    ));
+
+    let customers = TableFile::load(&engine, DATABASE_NAME, "customers").unwrap();
+    assert_eq!(customers.references(), vec![(1, "customers".to_string())]);
+    let orders = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    assert_eq!(orders.references(), vec![(1, "customers".to_string())]);
+    assert_eq!(
+        orders.records[0].values,
+        vec![
+            Some(Identifier::IntLiteral(0)),
+            Some(Identifier::IntLiteral(0))
+        ]
+    );
+
+    // The renamed table is still protected by the FKEY of orders
+    let err = run(
+        &engine,
+        &format!("DROP TABLE customers ON {};", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = run(
+        &engine,
+        &format!("RENAME TABLE users ON {} INTO clients;", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let err = run(
+        &engine,
+        &format!("RENAME TABLE customers ON {} INTO orders;", DATABASE_NAME),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+}
+
+#[test]
+pub fn test_rename_database() {
+    let engine = pretest();
+
+    run(
+        &engine,
+        &format!(
+            "RENAME DATABASE {} INTO {};",
+            DATABASE_NAME, NEW_DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert!(!common::ensure_db_existance(&engine, DATABASE_NAME));
+    assert!(common::ensure_table_existance(
+        &engine,
+        NEW_DATABASE_NAME,
+        "orders"
+    ));
+
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (0) ON orders STRUCTURED (user_id) ON {};",
+            NEW_DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let orders = TableFile::load(&engine, NEW_DATABASE_NAME, "orders").unwrap();
+    assert_eq!(orders.records.len(), 2);
+    assert!(engine
+        .storage()
+        .read_to_string(&engine.path(NEW_DATABASE_NAME).join("orders.asql"))
+        .unwrap()
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with(&format!("TABLE orders ON {} ", NEW_DATABASE_NAME)));
+
+    run(&engine, &format!("DATABASE {};", DATABASE_NAME)).unwrap();
+    let err = run(
+        &engine,
+        &format!(
+            "RENAME DATABASE {} INTO {};",
+            NEW_DATABASE_NAME, DATABASE_NAME
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+}
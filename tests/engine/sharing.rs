@@ -0,0 +1,115 @@
+use crate::common::{self, run, DATABASE_NAME};
+use arraysql::engine::lock;
+use arraysql::engine::storage::{Lock, LockKind};
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::query::Identifier;
+use std::io::ErrorKind;
+use std::thread;
+use std::time::Duration;
+
+const THREADS: usize = 8;
+const INSERTS: usize = 10;
+
+fn pretest() -> Engine {
+    let engine = common::memory_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+
+    engine
+}
+
+fn insert_user(engine: &Engine) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+}
+
+fn ids(engine: &Engine) -> Result<Vec<Option<Identifier>>, std::io::Error> {
+    match run(
+        engine,
+        &format!("READ STRUCTURED (id) ON users ON {};", DATABASE_NAME),
+    )? {
+        Output::ResultSet(_, rows) => Ok(rows.into_iter().map(|mut row| row.remove(0)).collect()),
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+fn users_lock(engine: &Engine, kind: LockKind) -> Lock {
+    lock::acquire(
+        engine,
+        DATABASE_NAME,
+        &lock::table_path(engine, DATABASE_NAME, "users"),
+        kind,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_engine_is_shareable() {
+    fn shareable<T: Send + Sync + Clone + 'static>() {}
+    shareable::<Engine>();
+}
+
+#[test]
+fn test_concurrent_writes() {
+    let engine = pretest();
+
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..INSERTS {
+                    insert_user(&engine).unwrap();
+                }
+            });
+        }
+    });
+
+    // No insert overwrote another
+    let mut ids = ids(&engine).unwrap();
+    assert_eq!(ids.len(), THREADS * INSERTS);
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), THREADS * INSERTS);
+}
+
+#[test]
+fn test_concurrent_reads() {
+    let engine = pretest();
+    insert_user(&engine).unwrap();
+    let impatient = engine.clone().with_lock_timeout(Duration::from_millis(50));
+
+    // A read in progress elsewhere lets other reads through, but not writes
+    let _lock = users_lock(&engine, LockKind::Shared);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| assert_eq!(ids(&impatient).unwrap().len(), 1));
+        }
+    });
+    let error = insert_user(&impatient).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+}
+
+#[test]
+fn test_write_waits_for_write() {
+    let engine = pretest();
+
+    let lock = users_lock(&engine, LockKind::Exclusive);
+    let impatient = engine.clone().with_lock_timeout(Duration::from_millis(50));
+    assert_eq!(ids(&impatient).unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| insert_user(&engine));
+        thread::sleep(Duration::from_millis(100));
+        assert!(!writer.is_finished());
+        drop(lock);
+        writer.join().unwrap().unwrap();
+    });
+    assert_eq!(ids(&engine).unwrap().len(), 1);
+}
@@ -0,0 +1,562 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::{hash, snapshot, table_file::TableFile, Engine, Output};
+use arraysql::parser::query::Identifier;
+
+fn literal(data: &str) -> Option<Identifier> {
+    Some(Identifier::StringLiteral(data.to_string()))
+}
+
+/// A string of a result set, quoted the way `READ` returns strings.
+fn text(data: &str) -> Option<Identifier> {
+    Some(Identifier::StringLiteral(format!("'{}'", data)))
+}
+
+/// Lists the snapshot log of the database, leaving out the creation times.
+fn snapshot_log(engine: &Engine) -> Vec<Vec<Option<Identifier>>> {
+    match run(engine, &format!("SNAPSHOTS ON {};", DATABASE_NAME)).unwrap() {
+        Output::ResultSet(columns, rows) => {
+            assert_eq!(
+                columns,
+                vec!["snapshot", "table", "parent", "created", "message"]
+            );
+            rows.into_iter()
+                .map(|mut row| {
+                    let created = match row.remove(3) {
+                        Some(Identifier::StringLiteral(created)) => created,
+                        found => panic!("Expected a creation time, found {:?}", found),
+                    };
+                    assert!(created.trim_matches('\'').parse::<u64>().unwrap() > 0);
+                    row
+                })
+                .collect()
+        }
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    engine
+}
+
+#[test]
+pub fn test_snapshot_and_restore() {
+    let engine = pretest();
+
+    let res = run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME));
+    assert!(res.is_ok());
+
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!("TABLE orders ON {} STRUCTURED (UINT(8) id);", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records
+            .len(),
+        2
+    );
+
+    let res = run(&engine, &format!("RESTORE first ON {};", DATABASE_NAME));
+    assert!(res.is_ok());
+
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records
+            .len(),
+        1
+    );
+    assert!(!common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "orders"
+    ));
+
+    // The snapshot is still available after restoring it
+    let res = run(&engine, &format!("RESTORE first ON {};", DATABASE_NAME));
+    assert!(res.is_ok());
+}
+
+#[test]
+pub fn test_snapshot_is_immutable() {
+    let engine = pretest();
+
+    run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME)).unwrap();
+    let res = run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME));
+    assert!(res.is_err());
+
+    let res = run(&engine, &format!("RESTORE missing ON {};", DATABASE_NAME));
+    assert!(res.is_err());
+}
+
+#[test]
+pub fn test_snapshot_list() {
+    let engine = pretest();
+
+    run(&engine, &format!("SNAPSHOT second ON {};", DATABASE_NAME)).unwrap();
+    run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME)).unwrap();
+
+    // Taken within the same second, the parent is still listed first
+    assert_eq!(
+        snapshot_log(&engine),
+        vec![
+            vec![text("second"), None, None, text("")],
+            vec![text("first"), None, text("second"), text("")]
+        ]
+    );
+}
+
+#[test]
+pub fn test_table_snapshot_and_restore() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!("TABLE orders ON {} STRUCTURED (UINT(8) id);", DATABASE_NAME),
+    )
+    .unwrap();
+
+    let res = run(
+        &engine,
+        &format!("SNAPSHOT users_backup ON users ON {};", DATABASE_NAME),
+    );
+    assert!(res.is_ok());
+
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (7) ON orders STRUCTURED (id) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    let res = run(
+        &engine,
+        &format!("RESTORE users_backup ON users ON {};", DATABASE_NAME),
+    );
+    assert!(res.is_ok());
+
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records
+            .len(),
+        1
+    );
+    // Other tables are left untouched
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "orders")
+            .unwrap()
+            .records
+            .len(),
+        1
+    );
+
+    // The INC counter continues from the snapshot's records
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('carol') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records[1]
+            .values[0],
+        Some(Identifier::IntLiteral(1))
+    );
+
+    assert_eq!(
+        snapshot_log(&engine),
+        vec![vec![text("users_backup"), text("users"), None, text("")]]
+    );
+}
+
+#[test]
+pub fn test_snapshot_keeps_inc_counter() {
+    let engine = pretest();
+    let insert = |name: &str| {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ('{}') ON users STRUCTURED (name) ON {};",
+                name, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    };
+    insert("bob");
+    run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE id = 1;", DATABASE_NAME),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!("SNAPSHOT users_backup ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    insert("carol");
+
+    // bob's id stays taken in the restored table
+    run(
+        &engine,
+        &format!("RESTORE users_backup ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    insert("dave");
+    let table = TableFile::load(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(
+        table
+            .records
+            .iter()
+            .map(|record| record.values[0].clone())
+            .collect::<Vec<Option<Identifier>>>(),
+        vec![
+            Some(Identifier::IntLiteral(0)),
+            Some(Identifier::IntLiteral(2))
+        ]
+    );
+}
+
+#[test]
+pub fn test_table_restore_into_new_table() {
+    let engine = pretest();
+
+    run(
+        &engine,
+        &format!("SNAPSHOT users_backup ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    let res = run(
+        &engine,
+        &format!(
+            "RESTORE users_backup ON users ON {} INTO old_users;",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_ok());
+
+    assert!(common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "old_users"
+    ));
+    assert_eq!(
+        TableFile::load(&engine, DATABASE_NAME, "old_users")
+            .unwrap()
+            .records,
+        TableFile::load(&engine, DATABASE_NAME, "users")
+            .unwrap()
+            .records
+    );
+
+    // Restoring into an existing table is refused
+    let res = run(
+        &engine,
+        &format!(
+            "RESTORE users_backup ON users ON {} INTO old_users;",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+
+    // Table snapshots cannot be restored as a whole database, and the other way around
+    run(
+        &engine,
+        &format!("SNAPSHOT everything ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    assert!(run(
+        &engine,
+        &format!("RESTORE users_backup ON {};", DATABASE_NAME)
+    )
+    .is_err());
+    assert!(run(
+        &engine,
+        &format!("RESTORE everything ON users ON {};", DATABASE_NAME)
+    )
+    .is_err());
+}
+
+#[test]
+pub fn test_snapshot_history() {
+    let engine = pretest();
+
+    run(
+        &engine,
+        &format!("SNAPSHOT first ON {} MESSAGE 'Only alice';", DATABASE_NAME),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!("SNAPSHOT second ON {} MESSAGE 'Added bob';", DATABASE_NAME),
+    )
+    .unwrap();
+
+    // Restoring moves the head, so the next snapshot descends from the restored one
+    run(&engine, &format!("RESTORE first ON {};", DATABASE_NAME)).unwrap();
+    run(&engine, &format!("SNAPSHOT third ON {};", DATABASE_NAME)).unwrap();
+
+    assert_eq!(
+        snapshot_log(&engine),
+        vec![
+            vec![text("first"), None, None, text("Only alice")],
+            vec![text("second"), None, text("first"), text("Added bob")],
+            vec![text("third"), None, text("first"), text("")]
+        ]
+    );
+
+    // first and third hold the same table, which is only stored once
+    let objects = engine
+        .storage()
+        .list(&engine.path(DATABASE_NAME).join(".snapshots/objects"))
+        .unwrap()
+        .len();
+    assert_eq!(objects, 2);
+}
+
+#[test]
+pub fn test_snapshot_diff() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "TABLE tags ON {} STRUCTURED (STRING(16) tag);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME)).unwrap();
+
+    // alice (0) is changed, bob (1) is removed and carol (2) is added
+    let mut table = TableFile::load(&engine, DATABASE_NAME, "users").unwrap();
+    table.records[0].values[1] = literal("'alicia'");
+    table.records.remove(1);
+    table.save(&engine, DATABASE_NAME, "users").unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (2, 'carol') ON users STRUCTURED (id, name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('new') ON tags STRUCTURED (tag) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(&engine, &format!("SNAPSHOT second ON {};", DATABASE_NAME)).unwrap();
+
+    let res = run(
+        &engine,
+        &format!("DIFF first, second ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Output::ResultSet(
+            vec!["table".to_string(), "change".to_string(), "key".to_string()],
+            vec![
+                vec![text("tags"), text("added"), literal("['new']")],
+                vec![
+                    text("users"),
+                    text("changed"),
+                    Some(Identifier::IntLiteral(0))
+                ],
+                vec![
+                    text("users"),
+                    text("removed"),
+                    Some(Identifier::IntLiteral(1))
+                ],
+                vec![
+                    text("users"),
+                    text("added"),
+                    Some(Identifier::IntLiteral(2))
+                ],
+            ]
+        )
+    );
+
+    assert!(run(
+        &engine,
+        &format!("DIFF first, missing ON {};", DATABASE_NAME)
+    )
+    .is_err());
+}
+
+#[test]
+pub fn test_snapshot_diff_without_pkey() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "TABLE pairs ON {} STRUCTURED (UINT(1) a, UINT(1) b);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let insert = |values: &str, fields: &str| {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}) ON pairs STRUCTURED ({}) ON {};",
+                values, fields, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    };
+    insert("1, 1", "a, b");
+    insert("1", "a");
+    run(&engine, &format!("SNAPSHOT first ON {};", DATABASE_NAME)).unwrap();
+
+    // The same record twice more, and records only differing in which field is null
+    insert("1, 1", "a, b");
+    insert("1, 1", "a, b");
+    insert("1", "b");
+    run(&engine, &format!("SNAPSHOT second ON {};", DATABASE_NAME)).unwrap();
+
+    let res = run(
+        &engine,
+        &format!("DIFF first, second ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Output::ResultSet(
+            vec!["table".to_string(), "change".to_string(), "key".to_string()],
+            vec![
+                vec![text("pairs"), text("added"), literal("[, 1]")],
+                vec![text("pairs"), text("added"), literal("[1, 1]")],
+                vec![text("pairs"), text("added"), literal("[1, 1]")],
+            ]
+        )
+    );
+
+    let res = run(
+        &engine,
+        &format!("DIFF second, first ON {};", DATABASE_NAME),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Output::ResultSet(
+            vec!["table".to_string(), "change".to_string(), "key".to_string()],
+            vec![
+                vec![text("pairs"), text("removed"), literal("[, 1]")],
+                vec![text("pairs"), text("removed"), literal("[1, 1]")],
+                vec![text("pairs"), text("removed"), literal("[1, 1]")],
+            ]
+        )
+    );
+}
+
+#[test]
+pub fn test_snapshot_message_escaping() {
+    let engine = pretest();
+    run(
+        &engine,
+        &format!(
+            "SNAPSHOT first ON {} MESSAGE 'Moved C:\\new to D:\\\\n';",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(
+        snapshot_log(&engine),
+        vec![vec![
+            text("first"),
+            None,
+            None,
+            text("Moved C:\\new to D:\\\\n")
+        ]]
+    );
+}
+
+#[test]
+pub fn test_object_hash_collision() {
+    let engine = pretest();
+    let contents = b"some table".to_vec();
+
+    // Another object already sits where these contents would be stored
+    let taken = snapshot::object_path(&engine, DATABASE_NAME, &hash::hex(&contents));
+    engine
+        .storage()
+        .create_dir_all(taken.parent().unwrap())
+        .unwrap();
+    engine.storage().write(&taken, b"other table").unwrap();
+
+    let name = snapshot::store_object(&engine, DATABASE_NAME, &contents).unwrap();
+    assert_ne!(name, hash::hex(&contents));
+    assert_eq!(
+        snapshot::read_object(&engine, DATABASE_NAME, &name).unwrap(),
+        contents
+    );
+    assert_eq!(
+        snapshot::read_object(&engine, DATABASE_NAME, &hash::hex(&contents)).unwrap(),
+        b"other table"
+    );
+
+    // Storing the same contents again finds them under the name they were given
+    assert_eq!(
+        snapshot::store_object(&engine, DATABASE_NAME, &contents).unwrap(),
+        name
+    );
+}
@@ -0,0 +1,206 @@
+use crate::common::{self, TestEngine, DATABASE_NAME};
+use arraysql::engine::catalog::Catalog;
+use arraysql::engine::plan::{self, Access};
+use arraysql::engine::statistics::Statistics;
+use arraysql::engine::table_file::TableFile;
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::{self, query::Identifier};
+use std::io::ErrorKind;
+use std::ops::Bound;
+
+fn run(engine: &Engine, query: &str) -> Result<Output, std::io::Error> {
+    common::run(engine, &query.replace("{}", DATABASE_NAME))
+}
+
+/// Orders mostly placed by the same user, each for a different amount.
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(
+        &engine,
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id, UINT(1) amount) CONSTRAINED (ON id PKEY INC) MODE FADD FDELETE INDEXED (by_user (user_id), by_amount (amount));",
+    )
+    .unwrap();
+    for i in 0..12 {
+        insert_order(&engine, if i < 10 { 0 } else { 1 }, i);
+    }
+
+    engine
+}
+
+fn insert_order(engine: &Engine, user_id: i32, amount: i32) {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ({}, {}) ON orders STRUCTURED (user_id, amount) ON {{}};",
+            user_id, amount
+        ),
+    )
+    .unwrap();
+}
+
+fn load_table(engine: &Engine, table_name: &str) -> Option<Statistics> {
+    Catalog::load(engine, DATABASE_NAME)
+        .unwrap()
+        .statistics
+        .remove(table_name)
+}
+
+fn load(engine: &Engine) -> Option<Statistics> {
+    load_table(engine, "orders")
+}
+
+fn int(value: i32) -> Identifier {
+    Identifier::IntLiteral(value)
+}
+
+/// Plans a `READ` of the orders the way the read would, statistics included.
+fn plan_for(engine: &Engine, conditions: &str) -> Access {
+    let mut p = parser::Parser::new();
+    p.set_query(format!(
+        "READ STRUCTURED (id) ON orders ON {} WHERE ({});",
+        DATABASE_NAME, conditions
+    ))
+    .parse();
+    let table = TableFile::load(engine, DATABASE_NAME, "orders").unwrap();
+    let count = table.live_records().count();
+    plan::plan(
+        &table,
+        count,
+        &p.query_data.conditions,
+        load(engine).as_ref(),
+    )
+    .access
+}
+
+#[test]
+fn test_analyze() {
+    let engine = pretest();
+    assert_eq!(load(&engine), None);
+
+    run(&engine, "ANALYZE orders ON {};").unwrap();
+    let statistics = load(&engine).unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    assert_eq!(statistics, Statistics::build(&table));
+
+    assert_eq!(statistics.rows, 12);
+    let user_id = &statistics.fields["user_id"];
+    assert_eq!((user_id.distinct, user_id.nulls), (2, 0), "{:?}", user_id);
+    assert_eq!(
+        (user_id.min.clone(), user_id.max.clone()),
+        (Some(int(0)), Some(int(1)))
+    );
+    // Buckets ending on the same value are merged
+    assert_eq!(user_id.histogram, vec![(int(0), 10), (int(1), 2)]);
+    let amount = &statistics.fields["amount"];
+    assert_eq!(amount.distinct, 12);
+    assert_eq!(
+        amount
+            .histogram
+            .iter()
+            .map(|(_, count)| count)
+            .sum::<usize>(),
+        12
+    );
+
+    let err = run(&engine, "ANALYZE customers ON {};").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn test_statistics_maintained() {
+    let engine = pretest();
+    run(&engine, "ANALYZE orders ON {};").unwrap();
+
+    // Appended records are counted without going over the table
+    insert_order(&engine, 2, 40);
+    let statistics = load(&engine).unwrap();
+    assert_eq!(statistics.rows, 13);
+    let user_id = &statistics.fields["user_id"];
+    assert_eq!((user_id.distinct, user_id.max.clone()), (3, Some(int(2))));
+    assert_eq!(user_id.histogram.last(), Some(&(int(2), 3)));
+
+    run(&engine, "DELETE ON orders ON {} WHERE (user_id = 1);").unwrap();
+    let statistics = load(&engine).unwrap();
+    assert_eq!(statistics.rows, 11);
+    assert_eq!(
+        statistics.fields["user_id"].histogram.last(),
+        Some(&(int(2), 1))
+    );
+
+    // Writes rewriting the table gather the statistics again
+    run(
+        &engine,
+        "UPDATE ON orders ON {} SET (user_id = 3) WHERE (amount = 40);",
+    )
+    .unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    assert_eq!(load(&engine), Some(Statistics::build(&table)));
+
+    // Statistics follow their table
+    run(&engine, "RENAME TABLE orders ON {} INTO purchases;").unwrap();
+    assert_eq!(load(&engine), None);
+    assert_eq!(
+        load_table(&engine, "purchases"),
+        Some(Statistics::build(&table))
+    );
+    run(&engine, "DROP TABLE purchases ON {};").unwrap();
+    assert_eq!(load_table(&engine, "purchases"), None);
+}
+
+#[test]
+fn test_planning_by_selectivity() {
+    let engine = pretest();
+
+    // Without statistics, every equality is taken to be as selective
+    assert_eq!(
+        plan_for(&engine, "user_id = 0 AND amount = 5"),
+        plan_for(&engine, "amount = 5 AND user_id = 0"),
+    );
+    assert!(matches!(
+        plan_for(&engine, "user_id >= 0"),
+        Access::IndexRange(..)
+    ));
+
+    run(&engine, "ANALYZE orders ON {};").unwrap();
+    let table = TableFile::load(&engine, DATABASE_NAME, "orders").unwrap();
+    let by_amount = table.index("by_amount").unwrap().clone();
+    assert_eq!(
+        plan_for(&engine, "user_id = 0 AND amount = 5"),
+        Access::IndexLookup(by_amount.clone(), vec![Some(int(5))])
+    );
+    // A range holding every record is cheaper to scan
+    assert_eq!(plan_for(&engine, "user_id >= 0"), Access::Scan);
+    assert_eq!(
+        plan_for(&engine, "amount > 9"),
+        Access::IndexRange(
+            by_amount,
+            Vec::new(),
+            Bound::Excluded(int(9)),
+            Bound::Unbounded
+        )
+    );
+
+    // EXPLAIN shows the estimates the statistics give
+    let Output::ResultSet(_, rows) = run(
+        &engine,
+        "EXPLAIN READ STRUCTURED (id) ON orders ON {} WHERE (amount = 50);",
+    )
+    .unwrap() else {
+        panic!("Expected a result set");
+    };
+    assert_eq!(
+        rows[0][0],
+        Some(Identifier::StringLiteral("'INDEX LOOKUP'".to_string()))
+    );
+    assert_eq!(rows[0][2], Some(int(0)));
+
+    // Reads give the same records whichever way they are planned
+    let Output::ResultSet(_, rows) = run(
+        &engine,
+        "READ STRUCTURED (amount) ON orders ON {} WHERE (user_id = 0 AND amount >= 8);",
+    )
+    .unwrap() else {
+        panic!("Expected a result set");
+    };
+    assert_eq!(rows, vec![vec![Some(int(8))], vec![Some(int(9))]]);
+}
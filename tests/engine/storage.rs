@@ -0,0 +1,116 @@
+use crate::common::{self, run, DATABASE_NAME};
+use arraysql::engine::storage::{MemoryStorage, Storage};
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::query::Identifier;
+use std::path::Path;
+
+/// Goes through every kind of write the engine makes, returning what the queries returned.
+fn workload(engine: &Engine) -> Vec<Output> {
+    let queries = [
+        format!("DATABASE {};", DATABASE_NAME),
+        format!(
+            "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FADD FDELETE;",
+            DATABASE_NAME
+        ),
+        format!(
+            "TABLE scores ON {} STRUCTURED (UINT(1) id, UINT(1) points) CONSTRAINED (ON id PKEY) MODE FREAD;",
+            DATABASE_NAME
+        ),
+        format!("INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};", DATABASE_NAME),
+        format!("INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON {};", DATABASE_NAME),
+        format!("INSERT STRUCTURED (2, 10) ON scores STRUCTURED (id, points) ON {};", DATABASE_NAME),
+        format!("INSERT STRUCTURED (1, 20) ON scores STRUCTURED (id, points) ON {};", DATABASE_NAME),
+        format!("SNAPSHOT first ON {};", DATABASE_NAME),
+        format!("DELETE ON users ON {} WHERE (id = 0);", DATABASE_NAME),
+        format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+        format!("RENAME TABLE scores ON {} INTO points;", DATABASE_NAME),
+        format!("SHOW TABLES ON {};", DATABASE_NAME),
+        format!("RESTORE first ON {};", DATABASE_NAME),
+        format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+        format!("READ STRUCTURED (id) ON scores ON {};", DATABASE_NAME),
+        "SHOW DATABASES;".to_string(),
+        format!("DROP DATABASE {};", DATABASE_NAME),
+        "SHOW DATABASES;".to_string(),
+    ];
+
+    queries
+        .iter()
+        .map(|query| run(engine, query).unwrap())
+        .collect()
+}
+
+#[test]
+pub fn test_backends_agree() {
+    let on_disk = common::temp_engine();
+    let in_memory = Engine::in_memory();
+
+    let outputs = workload(&in_memory);
+    assert_eq!(workload(&on_disk), outputs);
+    assert_eq!(
+        outputs[9],
+        Output::ResultSet(
+            vec!["name".to_string()],
+            vec![vec![Some(Identifier::StringLiteral("'bob'".to_string()))]]
+        )
+    );
+    match &outputs[13] {
+        Output::ResultSet(_, rows) => assert_eq!(rows.len(), 2),
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+#[test]
+pub fn test_in_memory_engines_are_isolated() {
+    let engine = common::memory_database();
+
+    assert!(common::ensure_db_existance(&engine, DATABASE_NAME));
+    assert!(!common::ensure_db_existance(
+        &Engine::in_memory(),
+        DATABASE_NAME
+    ));
+    assert!(!engine.path(DATABASE_NAME).exists());
+
+    // Clones of an engine share its storage
+    let clone = engine.clone();
+    run(&clone, &format!("DROP DATABASE {};", DATABASE_NAME)).unwrap();
+    assert!(!common::ensure_db_existance(&engine, DATABASE_NAME));
+}
+
+#[test]
+pub fn test_memory_storage() {
+    let storage = MemoryStorage::new();
+    let root = Path::new("/root");
+    storage.create_dir_all(&root.join("a/b")).unwrap();
+
+    // Files need their directory to exist
+    let err = storage.write(&root.join("c/file"), b"").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let err = storage.create_dir(&root.join("a")).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+    storage
+        .write(&root.join("a/b/file"), b"+ 1\n+ 2\n")
+        .unwrap();
+    storage.append(&root.join("a/b/file"), b"+ 3\n").unwrap();
+    storage
+        .patch(&root.join("a/b/file"), &[(4, b"-"), (8, b"-")])
+        .unwrap();
+    assert_eq!(
+        storage.read_to_string(&root.join("a/b/file")).unwrap(),
+        "+ 1\n- 2\n- 3\n"
+    );
+
+    // Directories are moved along with everything in them
+    storage.rename(&root.join("a"), &root.join("c")).unwrap();
+    assert!(!storage.exists(&root.join("a/b/file")));
+    assert!(storage.exists(&root.join("c/b/file")));
+    let entries = storage.list(&root.join("c")).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name(), "b");
+    assert!(entries[0].is_dir);
+
+    storage.remove_dir_all(&root.join("c")).unwrap();
+    assert!(storage.list(root).unwrap().is_empty());
+    let err = storage.read(&root.join("c/b/file")).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
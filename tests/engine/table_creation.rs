@@ -1,34 +1,26 @@
-use crate::common::{self, remove_db};
-use serial_test::serial;
+use crate::common::{self, TestEngine};
 
 const DATABASE_NAME: &str = "my_database";
 const TABLE_NAME: &str = "users";
 
-fn pretest(p: &mut arraysql::parser::Parser) -> Result<(), std::io::Error> {
-    println!("Soft removing");
-    common::soft_remove_db(DATABASE_NAME)?;
-    println!("Soft removal successful");
+fn pretest(p: &mut arraysql::parser::Parser) -> TestEngine {
+    let engine = common::temp_engine();
 
     p.set_query(format!("DATABASE {};", DATABASE_NAME)).parse();
-    println!("Parsing");
-    let res = arraysql::engine::engine(p);
+    let res = engine.execute(p);
     if let Err(err) = res {
         panic!("Err: {}", err);
     }
 
-    println!("Parsing successful");
-
     p.reset();
 
-    Ok(())
+    engine
 }
 
 #[test]
-#[serial]
 pub fn test_table_creation() {
     let mut p = arraysql::parser::Parser::new();
-    let res = pretest(&mut p);
-    assert!(res.is_ok());
+    let engine = pretest(&mut p);
 
     p.set_query(format!(
         "TABLE {} ON {} STRUCTURED (UINT(1) id);",
@@ -36,14 +28,43 @@ pub fn test_table_creation() {
     ))
     .parse();
 
-    let res = arraysql::engine::engine(&p);
+    let res = engine.execute(&p);
     if let Err(err) = res {
         panic!("Err: {}", err);
     }
     assert!(res.is_ok());
 
-    assert!(common::ensure_table_existance(DATABASE_NAME, TABLE_NAME));
+    assert!(common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        TABLE_NAME
+    ));
+}
 
-    let res = remove_db(DATABASE_NAME);
-    assert!(res.is_ok());
+#[test]
+pub fn test_table_creation_references() {
+    let mut p = arraysql::parser::Parser::new();
+    let engine = pretest(&mut p);
+
+    // The table an FKEY refers to has to exist, unless it is the table itself
+    p.set_query(format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) user_id) CONSTRAINED (ON user_id FKEY (ON {})) MODE FADD;",
+        DATABASE_NAME, TABLE_NAME
+    ))
+    .parse();
+    let err = engine.execute(&p).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(!common::ensure_table_existance(
+        &engine,
+        DATABASE_NAME,
+        "orders"
+    ));
+
+    p.reset();
+    p.set_query(format!(
+        "TABLE {} ON {} STRUCTURED (UINT(1) id, UINT(1) manager_id) CONSTRAINED (ON id PKEY, ON manager_id FKEY (ON {})) MODE FADD;",
+        TABLE_NAME, DATABASE_NAME, TABLE_NAME
+    ))
+    .parse();
+    engine.execute(&p).unwrap();
 }
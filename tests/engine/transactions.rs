@@ -0,0 +1,290 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::query::Identifier;
+use std::thread;
+use std::time::Duration;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC, ON name UNIQUE) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(&engine, &format!(
+        "TABLE orders ON {} STRUCTURED (UINT(1) id, UINT(1) user_id) CONSTRAINED (ON id PKEY INC, ON user_id FKEY (ON users)) MODE FADD FDELETE;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    insert_user(&engine, "'alice'").unwrap();
+
+    engine
+}
+
+fn insert_user(engine: &Engine, name: &str) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ({}) ON users STRUCTURED (name) ON {};",
+            name, DATABASE_NAME
+        ),
+    )
+}
+
+fn insert_order(engine: &Engine, user_id: u8) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ({}) ON orders STRUCTURED (user_id) ON {};",
+            user_id, DATABASE_NAME
+        ),
+    )
+}
+
+fn rows(engine: &Engine, query: &str) -> Vec<Vec<Option<Identifier>>> {
+    match run(engine, query).unwrap() {
+        Output::ResultSet(_, rows) => rows,
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+fn names(engine: &Engine) -> Vec<Vec<Option<Identifier>>> {
+    rows(
+        engine,
+        &format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+    )
+}
+
+fn orders(engine: &Engine) -> usize {
+    rows(
+        engine,
+        &format!("READ STRUCTURED (id) ON orders ON {};", DATABASE_NAME),
+    )
+    .len()
+}
+
+fn name(value: &str) -> Vec<Option<Identifier>> {
+    vec![Some(Identifier::StringLiteral(value.to_string()))]
+}
+
+#[test]
+pub fn test_commit() {
+    let engine = pretest();
+    let other = Engine::open(engine.root()).unwrap();
+
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    // The FKEY check sees bob, who is not committed yet
+    insert_order(&engine, 1).unwrap();
+    assert_eq!(names(&engine), vec![name("'alice'"), name("'bob'")]);
+    assert_eq!(orders(&engine), 1);
+
+    // Nothing is written until the transaction commits
+    assert_eq!(names(&other), vec![name("'alice'")]);
+    assert_eq!(orders(&other), 0);
+
+    run(&engine, "COMMIT;").unwrap();
+    assert!(!engine.in_transaction());
+    assert_eq!(names(&other), vec![name("'alice'"), name("'bob'")]);
+    assert_eq!(orders(&other), 1);
+}
+
+#[test]
+pub fn test_rollback() {
+    let engine = pretest();
+    insert_order(&engine, 0).unwrap();
+
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    run(&engine, &format!("DELETE ON orders ON {};", DATABASE_NAME)).unwrap();
+    run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (name = 'carol') WHERE (id = 0);",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    assert_eq!(names(&engine), vec![name("'carol'"), name("'bob'")]);
+    assert_eq!(orders(&engine), 0);
+
+    run(&engine, "ROLLBACK;").unwrap();
+    assert_eq!(names(&engine), vec![name("'alice'")]);
+    assert_eq!(orders(&engine), 1);
+}
+
+#[test]
+pub fn test_constraints_see_uncommitted_writes() {
+    let engine = pretest();
+
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    assert!(insert_user(&engine, "'bob'").is_err());
+    assert!(insert_order(&engine, 2).is_err());
+
+    // alice can no longer be deleted once an order of the transaction refers to her
+    insert_order(&engine, 0).unwrap();
+    assert!(run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (id = 0);", DATABASE_NAME),
+    )
+    .is_err());
+
+    // A failed statement does not end the transaction
+    run(&engine, "COMMIT;").unwrap();
+    assert_eq!(names(&engine), vec![name("'alice'"), name("'bob'")]);
+    assert_eq!(orders(&engine), 1);
+}
+
+#[test]
+pub fn test_transaction_errors() {
+    let engine = pretest();
+
+    assert!(run(&engine, "COMMIT;").is_err());
+    assert!(run(&engine, "ROLLBACK;").is_err());
+
+    run(&engine, "BEGIN;").unwrap();
+    assert!(run(&engine, "BEGIN;").is_err());
+
+    // Only records can be changed inside a transaction
+    let err = run(&engine, "DATABASE other_database;").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(run(&engine, &format!("DROP TABLE orders ON {};", DATABASE_NAME)).is_err());
+    run(&engine, "ROLLBACK;").unwrap();
+
+    // Every write of a transaction goes to the same database
+    run(&engine, "DATABASE other_database;").unwrap();
+    run(
+        &engine,
+        "TABLE users ON other_database STRUCTURED (STRING(64) name) MODE FADD;",
+    )
+    .unwrap();
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    let err = run(
+        &engine,
+        "INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON other_database;",
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    run(&engine, "COMMIT;").unwrap();
+    assert_eq!(names(&engine), vec![name("'alice'"), name("'bob'")]);
+}
+
+#[test]
+pub fn test_savepoints() {
+    let engine = pretest();
+
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    run(&engine, "SAVEPOINT orders;").unwrap();
+    insert_order(&engine, 1).unwrap();
+    insert_order(&engine, 0).unwrap();
+
+    // A failing group of records is undone, and the rest of the transaction kept
+    run(&engine, "SAVEPOINT users;").unwrap();
+    insert_user(&engine, "'carol'").unwrap();
+    assert!(insert_user(&engine, "'alice'").is_err());
+    run(&engine, "ROLLBACK TO users;").unwrap();
+    assert_eq!(names(&engine), vec![name("'alice'"), name("'bob'")]);
+    assert_eq!(orders(&engine), 2);
+
+    // The savepoint can be rolled back to again
+    insert_user(&engine, "'dave'").unwrap();
+    run(&engine, "ROLLBACK TO users;").unwrap();
+    assert_eq!(names(&engine).len(), 2);
+
+    // Rolling back to an earlier savepoint drops the later ones
+    run(&engine, "ROLLBACK TO orders;").unwrap();
+    assert_eq!(orders(&engine), 0);
+    let err = run(&engine, "ROLLBACK TO users;").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    // Releasing a savepoint keeps what was written since
+    insert_order(&engine, 1).unwrap();
+    run(&engine, "RELEASE orders;").unwrap();
+    assert!(run(&engine, "ROLLBACK TO orders;").is_err());
+
+    run(&engine, "COMMIT;").unwrap();
+    assert_eq!(names(&engine), vec![name("'alice'"), name("'bob'")]);
+    assert_eq!(orders(&engine), 1);
+
+    let err = run(&engine, "SAVEPOINT orders;").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+pub fn test_concurrent_write_waits_for_commit() {
+    let engine = pretest();
+    let other = Engine::open(engine.root())
+        .unwrap()
+        .with_lock_timeout(Duration::from_millis(50));
+
+    // Writes to a table the transaction wrote to wait until it ends, instead of being
+    // overwritten when it commits
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    let err = insert_user(&other, "'carol'").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    // Reads and writes to other tables go ahead
+    assert_eq!(names(&other), vec![name("'alice'")]);
+    insert_order(&other, 0).unwrap();
+
+    run(&engine, "COMMIT;").unwrap();
+    insert_user(&other, "'carol'").unwrap();
+    assert_eq!(
+        names(&engine),
+        vec![name("'alice'"), name("'bob'"), name("'carol'")]
+    );
+
+    // A write waiting on the transaction goes ahead once it ends
+    let waiting = Engine::open(engine.root()).unwrap();
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine, "'dave'").unwrap();
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| insert_user(&waiting, "'erin'"));
+        thread::sleep(Duration::from_millis(100));
+        assert!(!writer.is_finished());
+        run(&engine, "ROLLBACK;").unwrap();
+        writer.join().unwrap().unwrap();
+    });
+    assert_eq!(
+        names(&other),
+        vec![
+            name("'alice'"),
+            name("'bob'"),
+            name("'carol'"),
+            name("'erin'")
+        ]
+    );
+}
+
+#[test]
+pub fn test_clones_have_their_own_transactions() {
+    let engine = pretest();
+    let clone = engine.clone().with_lock_timeout(Duration::from_millis(50));
+
+    run(&engine, "BEGIN;").unwrap();
+    assert!(engine.in_transaction());
+    assert!(!clone.in_transaction());
+
+    // The clone writes outside of the transaction, so rolling it back keeps that write
+    insert_user(&engine, "'bob'").unwrap();
+    insert_order(&clone, 0).unwrap();
+    assert_eq!(names(&clone), vec![name("'alice'")]);
+    run(&engine, "ROLLBACK;").unwrap();
+    assert_eq!(orders(&engine), 1);
+    assert_eq!(names(&engine), vec![name("'alice'")]);
+
+    // Each clone commits its own transaction
+    run(&engine, "BEGIN;").unwrap();
+    run(&clone, "BEGIN;").unwrap();
+    insert_user(&engine, "'bob'").unwrap();
+    insert_order(&clone, 0).unwrap();
+    run(&clone, "COMMIT;").unwrap();
+    assert!(engine.in_transaction());
+    assert_eq!(orders(&engine), 2);
+    run(&engine, "ROLLBACK;").unwrap();
+    assert_eq!(names(&clone), vec![name("'alice'")]);
+}
@@ -0,0 +1,168 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::{Engine, Output};
+use arraysql::parser::query::Identifier;
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(8) name, UINT(1) age) CONSTRAINED (ON age SUCHTHAT (age <= 120), ON id PKEY INC, ON name EXISTS UNIQUE) MODE FADD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    for (name, age) in [("'alice'", "31"), ("'bob'", "12"), ("'carol'", "8")] {
+        run(
+            &engine,
+            &format!(
+                "INSERT STRUCTURED ({}, {}) ON users STRUCTURED (name, age) ON {};",
+                name, age, DATABASE_NAME
+            ),
+        )
+        .unwrap();
+    }
+
+    engine
+}
+
+fn read(engine: &Engine, fields: &str) -> Vec<Vec<Option<Identifier>>> {
+    match run(
+        engine,
+        &format!(
+            "READ STRUCTURED ({}) ON users ON {};",
+            fields, DATABASE_NAME
+        ),
+    )
+    .unwrap()
+    {
+        Output::ResultSet(_, rows) => rows,
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+fn ages(engine: &Engine) -> Vec<Vec<Option<Identifier>>> {
+    read(engine, "age")
+}
+
+fn int(data: i32) -> Vec<Option<Identifier>> {
+    vec![Some(Identifier::IntLiteral(data))]
+}
+
+#[test]
+pub fn test_update() {
+    let engine = pretest();
+
+    let res = run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (age = age + 1) WHERE (age < 13);",
+            DATABASE_NAME
+        ),
+    );
+    assert_eq!(res.unwrap(), Output::Count(2));
+    assert_eq!(ages(&engine), vec![int(31), int(13), int(9)]);
+
+    let res = run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (name = 'dave', age = 40) WHERE (name = 'alice');",
+            DATABASE_NAME
+        ),
+    );
+    assert_eq!(res.unwrap(), Output::Count(1));
+    assert_eq!(
+        read(&engine, "name, age")[0],
+        vec![
+            Some(Identifier::StringLiteral("'dave'".to_string())),
+            Some(Identifier::IntLiteral(40))
+        ]
+    );
+}
+
+#[test]
+pub fn test_update_constraints() {
+    let engine = pretest();
+
+    // Datatype size
+    let res = run(
+        &engine,
+        &format!("UPDATE ON users ON {} SET (age = 300);", DATABASE_NAME),
+    );
+    assert!(res.is_err());
+    let res = run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (name = 'a_much_longer_name') WHERE (age = 8);",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+
+    // SUCHTHAT
+    let res = run(
+        &engine,
+        &format!("UPDATE ON users ON {} SET (age = 121);", DATABASE_NAME),
+    );
+    assert!(res.is_err());
+
+    // UNIQUE, both against other records and between the updated records
+    let res = run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (name = 'bob') WHERE (age = 8);",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+    let res = run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (name = 'same') WHERE (age < 13);",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+
+    // PKEY
+    let res = run(
+        &engine,
+        &format!(
+            "UPDATE ON users ON {} SET (id = 0) WHERE (age = 8);",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+
+    // Nothing was written by the failed updates
+    assert_eq!(ages(&engine), vec![int(31), int(12), int(8)]);
+}
+
+#[test]
+pub fn test_insert_constraints() {
+    let engine = pretest();
+
+    let res = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('dave', 121) ON users STRUCTURED (name, age) ON {};",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+    let res = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED (40) ON users STRUCTURED (age) ON {};",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+    let res = run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('dave', 'old') ON users STRUCTURED (name, age) ON {};",
+            DATABASE_NAME
+        ),
+    );
+    assert!(res.is_err());
+
+    assert_eq!(ages(&engine).len(), 3);
+}
@@ -0,0 +1,133 @@
+use crate::common::{self, run, DATABASE_NAME};
+use arraysql::engine::storage::LockKind;
+use arraysql::engine::{lock, table_file, versions, Engine, Output};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn pretest() -> Engine {
+    let engine = common::memory_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FREAD;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    insert_user(&engine).unwrap();
+
+    engine
+}
+
+fn insert_user(engine: &Engine) -> Result<Output, std::io::Error> {
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+}
+
+fn count_users(engine: &Engine) -> usize {
+    match run(
+        engine,
+        &format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap()
+    {
+        Output::ResultSet(_, rows) => rows.len(),
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+fn live(engine: &Engine) -> usize {
+    engine
+        .versions()
+        .live(&table_file::path(engine, DATABASE_NAME, "users"))
+}
+
+#[test]
+fn test_pinned_version() {
+    let engine = pretest();
+    let impatient = engine.clone().with_lock_timeout(Duration::from_millis(50));
+
+    // A read in progress neither holds back writers nor sees their changes
+    let first = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    insert_user(&impatient).unwrap();
+    insert_user(&impatient).unwrap();
+    assert_eq!(first.table.live_records().count(), 1);
+    assert_eq!(count_users(&engine), 3);
+
+    let second = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(second.table.live_records().count(), 3);
+    assert!(second.number > first.number);
+}
+
+#[test]
+fn test_shared_version() {
+    let engine = pretest();
+
+    let first = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    let second = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(live(&engine), 1);
+}
+
+#[test]
+fn test_version_collection() {
+    let engine = pretest();
+    assert_eq!(count_users(&engine), 1);
+    assert_eq!(live(&engine), 0);
+
+    let first = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    insert_user(&engine).unwrap();
+    let second = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(live(&engine), 2);
+
+    drop(first);
+    assert_eq!(live(&engine), 1);
+    drop(second);
+    assert_eq!(live(&engine), 0);
+}
+
+#[test]
+fn test_pin_waits_for_write() {
+    let engine = pretest();
+
+    // Reads never see a write that is still being made
+    let lock = lock::acquire(
+        &engine,
+        DATABASE_NAME,
+        &lock::table_path(&engine, DATABASE_NAME, "users"),
+        LockKind::Exclusive,
+    )
+    .unwrap();
+    thread::scope(|scope| {
+        let reader = scope.spawn(|| count_users(&engine));
+        thread::sleep(Duration::from_millis(100));
+        assert!(!reader.is_finished());
+        drop(lock);
+        assert_eq!(reader.join().unwrap(), 1);
+    });
+}
+
+#[test]
+fn test_uncommitted_version() {
+    let engine = pretest();
+    let other = engine.clone();
+
+    // The transaction reads its own writes, in a version no other read is handed
+    run(&engine, "BEGIN;").unwrap();
+    insert_user(&engine).unwrap();
+    let staged = versions::pin(&engine, DATABASE_NAME, "users").unwrap();
+    assert_eq!(staged.table.live_records().count(), 2);
+    assert_eq!(live(&engine), 0);
+
+    let committed = versions::pin(&other, DATABASE_NAME, "users").unwrap();
+    assert_eq!(committed.table.live_records().count(), 1);
+    assert_eq!(count_users(&other), 1);
+    assert_eq!(live(&engine), 1);
+
+    run(&engine, "COMMIT;").unwrap();
+    assert_eq!(committed.table.live_records().count(), 1);
+    assert_eq!(count_users(&other), 2);
+}
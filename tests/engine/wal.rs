@@ -0,0 +1,422 @@
+use crate::common::{self, run, TestEngine, DATABASE_NAME};
+use arraysql::engine::storage::{self, FileStorage, Lock, LockKind, MemoryStorage, Storage};
+use arraysql::engine::wal::{self, Entry};
+use arraysql::engine::{table_file, Engine, Output};
+use arraysql::parser::query::Identifier;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// A storage in memory noting down the files it changes and the directories it syncs, in order.
+#[derive(Debug, Default)]
+struct Recorder {
+    inner: MemoryStorage,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Recorder {
+    fn note(&self, event: &str, path: &Path) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", event, path.display()));
+    }
+}
+
+impl Storage for Recorder {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        self.inner.read(path)
+    }
+
+    fn size(&self, path: &Path) -> Result<u64, io::Error> {
+        self.inner.size(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        self.note("write", path);
+        self.inner.write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+        self.note("append", path);
+        self.inner.append(path, contents)
+    }
+
+    fn patch(&self, path: &Path, patches: &[(u64, &[u8])]) -> Result<(), io::Error> {
+        self.note("patch", path);
+        self.inner.patch(path, patches)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), io::Error> {
+        self.inner.rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), io::Error> {
+        self.note("remove", path);
+        self.inner.remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.remove_dir_all(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), io::Error> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<storage::Entry>, io::Error> {
+        self.inner.list(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.inner.is_dir(path)
+    }
+
+    fn sync_dir(&self, path: &Path) -> Result<(), io::Error> {
+        self.note("sync", path);
+        self.inner.sync_dir(path)
+    }
+
+    fn try_lock(&self, path: &Path, kind: LockKind) -> Result<Option<Lock>, io::Error> {
+        self.inner.try_lock(path, kind)
+    }
+}
+
+fn pretest() -> TestEngine {
+    let engine = common::temp_database();
+    run(&engine, &format!(
+        "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FADD FDELETE;",
+        DATABASE_NAME
+    ))
+    .unwrap();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+
+    engine
+}
+
+fn names(engine: &Engine) -> Vec<Vec<Option<Identifier>>> {
+    match run(
+        engine,
+        &format!("READ STRUCTURED (name) ON users ON {};", DATABASE_NAME),
+    )
+    .unwrap()
+    {
+        Output::ResultSet(_, rows) => rows,
+        found => panic!("Expected a result set, found {:?}", found),
+    }
+}
+
+fn name(value: &str) -> Vec<Option<Identifier>> {
+    vec![Some(Identifier::StringLiteral(value.to_string()))]
+}
+
+#[test]
+pub fn test_entries() {
+    let entries = vec![
+        Entry::Write {
+            file: "users.asql".to_string(),
+            contents: b"TABLE users\n+ 0, 'a:b'\n".to_vec(),
+        },
+        Entry::Append {
+            file: "users.asql".to_string(),
+            offset: 12,
+            contents: b"+ 1, ''\n".to_vec(),
+        },
+        Entry::Patch {
+            file: "users.asql".to_string(),
+            patches: vec![(12, b"-".to_vec()), (20, b"-".to_vec())],
+        },
+        Entry::Rename {
+            from: "users.asql".to_string(),
+            to: "customers.asql".to_string(),
+        },
+        Entry::Remove {
+            file: "customers.asql".to_string(),
+        },
+        Entry::CreateDir {
+            dir: ".snapshots/objects".to_string(),
+        },
+        Entry::RemoveDir {
+            dir: ".restore".to_string(),
+        },
+        Entry::Commit {
+            entries: vec![
+                Entry::Write {
+                    file: "users.asql".to_string(),
+                    contents: b"TABLE users\n".to_vec(),
+                },
+                Entry::Write {
+                    file: "orders.asql".to_string(),
+                    contents: Vec::new(),
+                },
+            ],
+        },
+    ];
+
+    let log: Vec<u8> = entries.iter().flat_map(|entry| entry.encode()).collect();
+    assert_eq!(Entry::decode_all(&log), entries);
+
+    // An entry cut short, or damaged, ends the log
+    assert_eq!(Entry::decode_all(&log[..log.len() - 1]), entries[..7]);
+    let mut damaged = log.clone();
+    let last = damaged.len() - 2;
+    damaged[last] ^= 1;
+    assert_eq!(Entry::decode_all(&damaged), entries[..7]);
+}
+
+#[test]
+pub fn test_torn_row_is_redone() {
+    let engine = pretest();
+    let table_path = table_file::path(&engine, DATABASE_NAME, "users");
+    let offset = engine.storage().size(&table_path).unwrap();
+
+    // The engine stopped after logging a row, but before all of it reached the table file
+    let row = b"+ 1, 'bob'\n";
+    FileStorage
+        .write(
+            &wal::path(&engine, DATABASE_NAME),
+            &Entry::Append {
+                file: "users.asql".to_string(),
+                offset,
+                contents: row.to_vec(),
+            }
+            .encode(),
+        )
+        .unwrap();
+    FileStorage.append(&table_path, &row[..6]).unwrap();
+
+    let reopened = Engine::open(engine.root()).unwrap();
+    assert_eq!(names(&reopened), vec![name("'alice'"), name("'bob'")]);
+    assert!(!reopened
+        .storage()
+        .exists(&wal::path(&reopened, DATABASE_NAME)));
+
+    // Redoing the row again does not add it twice
+    run(
+        &reopened,
+        &format!(
+            "INSERT STRUCTURED ('carol') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let reopened = Engine::open(engine.root()).unwrap();
+    assert_eq!(
+        names(&reopened),
+        vec![name("'alice'"), name("'bob'"), name("'carol'")]
+    );
+}
+
+#[test]
+pub fn test_incomplete_entry_is_dropped() {
+    let engine = pretest();
+
+    // The engine stopped while logging a row, so the row never reached the table file
+    let entry = Entry::Append {
+        file: "users.asql".to_string(),
+        offset: 0,
+        contents: b"+ 1, 'bob'\n".to_vec(),
+    }
+    .encode();
+    FileStorage
+        .append(
+            &wal::path(&engine, DATABASE_NAME),
+            &entry[..entry.len() - 3],
+        )
+        .unwrap();
+
+    let reopened = Engine::open(engine.root()).unwrap();
+    assert_eq!(names(&reopened), vec![name("'alice'")]);
+}
+
+#[test]
+pub fn test_checkpoint() {
+    let engine = pretest();
+    let log = wal::path(&engine, DATABASE_NAME);
+
+    run(
+        &engine,
+        &format!("DELETE ON users ON {} WHERE (id = 0);", DATABASE_NAME),
+    )
+    .unwrap();
+    let entries = Entry::decode_all(&engine.storage().read(&log).unwrap());
+    assert!(matches!(entries.last(), Some(Entry::Patch { .. })));
+
+    run(&engine, &format!("CHECKPOINT ON {};", DATABASE_NAME)).unwrap();
+    assert!(!engine.storage().exists(&log));
+    assert!(names(&engine).is_empty());
+
+    let err = run(&engine, "CHECKPOINT ON missing_database;").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+pub fn test_rename_is_one_entry() {
+    let engine = pretest();
+    let log = wal::path(&engine, DATABASE_NAME);
+    run(&engine, &format!("CHECKPOINT ON {};", DATABASE_NAME)).unwrap();
+    run(
+        &engine,
+        &format!("RENAME TABLE users ON {} INTO customers;", DATABASE_NAME),
+    )
+    .unwrap();
+    let entries = Entry::decode_all(&engine.storage().read(&log).unwrap());
+    assert_eq!(entries.len(), 1);
+    assert!(matches!(entries[0], Entry::Commit { .. }));
+
+    // The engine stopped right after logging the rename, before moving any file
+    let stopped = pretest();
+    run(&stopped, &format!("CHECKPOINT ON {};", DATABASE_NAME)).unwrap();
+    FileStorage
+        .write(&wal::path(&stopped, DATABASE_NAME), &entries[0].encode())
+        .unwrap();
+
+    let reopened = Engine::open(stopped.root()).unwrap();
+    assert!(!table_file::exists(&reopened, DATABASE_NAME, "users"));
+    assert_eq!(
+        run(
+            &reopened,
+            &format!("READ STRUCTURED (name) ON customers ON {};", DATABASE_NAME),
+        )
+        .unwrap(),
+        Output::ResultSet(vec!["name".to_string()], vec![name("'alice'")])
+    );
+}
+
+#[test]
+pub fn test_checkpoint_syncs_directories() {
+    let recorder = Recorder::default();
+    let events = recorder.events.clone();
+    let engine = Engine::new("/", recorder).unwrap();
+    run(&engine, &format!("DATABASE {};", DATABASE_NAME)).unwrap();
+    run(
+        &engine,
+        &format!(
+            "TABLE users ON {} STRUCTURED (UINT(1) id) CONSTRAINED (ON id PKEY INC) MODE FADD;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(
+        &engine,
+        &format!("RENAME TABLE users ON {} INTO customers;", DATABASE_NAME),
+    )
+    .unwrap();
+
+    // The moves made by the log reach the disk before the log is dropped
+    events.lock().unwrap().clear();
+    run(&engine, &format!("CHECKPOINT ON {};", DATABASE_NAME)).unwrap();
+    let db_path = engine.path(DATABASE_NAME);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            format!("sync {}", db_path.display()),
+            format!("remove {}", db_path.join(wal::WAL).display())
+        ]
+    );
+}
+
+#[test]
+pub fn test_new_log_synced_before_change() {
+    let recorder = Recorder::default();
+    let events = recorder.events.clone();
+    let engine = Engine::new("/", recorder).unwrap();
+    run(&engine, &format!("DATABASE {};", DATABASE_NAME)).unwrap();
+    run(
+        &engine,
+        &format!(
+            "TABLE users ON {} STRUCTURED (UINT(1) id, STRING(64) name) CONSTRAINED (ON id PKEY INC) MODE FADD;",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(&engine, &format!("CHECKPOINT ON {};", DATABASE_NAME)).unwrap();
+
+    // The first change after a checkpoint creates the log, which is in place before the change
+    events.lock().unwrap().clear();
+    run(
+        &engine,
+        &format!(
+            "INSERT STRUCTURED ('alice') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    let db_path = engine.path(DATABASE_NAME);
+    assert_eq!(
+        events.lock().unwrap()[..3],
+        [
+            format!("write {}", db_path.join(wal::WAL).display()),
+            format!("sync {}", db_path.display()),
+            format!(
+                "patch {}",
+                table_file::path(&engine, DATABASE_NAME, "users").display()
+            ),
+        ]
+    );
+}
+
+/// Snapshots the users, the whole database or, with `on` set to `users ON `, only the table,
+/// then adds one more user.
+fn snapshot_then_insert(engine: &Engine, on: &str) {
+    run(
+        engine,
+        &format!("SNAPSHOT before ON {}{};", on, DATABASE_NAME),
+    )
+    .unwrap();
+    run(
+        engine,
+        &format!(
+            "INSERT STRUCTURED ('bob') ON users STRUCTURED (name) ON {};",
+            DATABASE_NAME
+        ),
+    )
+    .unwrap();
+    run(engine, &format!("CHECKPOINT ON {};", DATABASE_NAME)).unwrap();
+}
+
+#[test]
+pub fn test_restore_is_one_entry() {
+    for on in ["", "users ON "] {
+        let engine = pretest();
+        snapshot_then_insert(&engine, on);
+        run(
+            &engine,
+            &format!("RESTORE before ON {}{};", on, DATABASE_NAME),
+        )
+        .unwrap();
+        let entries = Entry::decode_all(
+            &engine
+                .storage()
+                .read(&wal::path(&engine, DATABASE_NAME))
+                .unwrap(),
+        );
+        assert_eq!(entries.len(), 1, "{}", on);
+        assert!(matches!(entries[0], Entry::Commit { .. }));
+
+        // The engine stopped right after logging the restore, before replacing any file
+        let stopped = pretest();
+        snapshot_then_insert(&stopped, on);
+        FileStorage
+            .write(&wal::path(&stopped, DATABASE_NAME), &entries[0].encode())
+            .unwrap();
+
+        let reopened = Engine::open(stopped.root()).unwrap();
+        assert_eq!(names(&reopened), vec![name("'alice'")], "{}", on);
+    }
+}
@@ -0,0 +1,134 @@
+use arraysql::parser::{
+    self,
+    query::{Alteration, Constraint, ConstraintType, Expression, Identifier, Mode, QueryType},
+};
+
+#[test]
+fn test_alter_mode() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database MODE FREAD FDELETE;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Alter);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert_eq!(p.query_data.alteration, Alteration::Mode);
+    assert_eq!(p.query_data.modes, vec![Mode::Fread, Mode::Fdelete]);
+}
+
+#[test]
+#[should_panic]
+fn test_alter_without_mode() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database MODE;".to_string())
+        .parse();
+}
+
+#[test]
+fn test_alter_add_field() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database ADD UINT(1) age EXISTS DEFAULT (18);".to_string())
+        .parse();
+
+    let age = Identifier::Field("age".to_string());
+    assert_eq!(p.query_data.alteration, Alteration::AddField);
+    assert_eq!(p.query_data.altered_field, age);
+    assert_eq!(
+        p.query_data.fields,
+        vec![(
+            Identifier::Datatype("UINT".to_string()),
+            vec![Identifier::IntLiteral(1)],
+            age.clone()
+        )]
+    );
+    assert_eq!(
+        p.query_data.constraints[&age],
+        vec![
+            Constraint {
+                constraint_type: ConstraintType::Exists,
+                options: Vec::new(),
+            },
+            Constraint {
+                constraint_type: ConstraintType::Default,
+                options: vec![Expression::Identifier(Identifier::IntLiteral(18))],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_alter_drop_field() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database DROP age;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data.alteration, Alteration::DropField);
+    assert_eq!(
+        p.query_data.altered_field,
+        Identifier::Field("age".to_string())
+    );
+}
+
+#[test]
+fn test_alter_retype_field() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database RETYPE name STRING(255);".to_string())
+        .parse();
+
+    assert_eq!(p.query_data.alteration, Alteration::RetypeField);
+    assert_eq!(
+        p.query_data.fields,
+        vec![(
+            Identifier::Datatype("STRING".to_string()),
+            vec![Identifier::IntLiteral(255)],
+            Identifier::Field("name".to_string())
+        )]
+    );
+}
+
+#[test]
+fn test_alter_constraints() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database ADD ON name UNIQUE;".to_string())
+        .parse();
+
+    let name = Identifier::Field("name".to_string());
+    assert_eq!(p.query_data.alteration, Alteration::AddConstraints);
+    assert_eq!(p.query_data.altered_field, name);
+    assert_eq!(
+        p.query_data.constraints[&name],
+        vec![Constraint {
+            constraint_type: ConstraintType::Unique,
+            options: Vec::new(),
+        }]
+    );
+
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database DROP ON name UNIQUE EXISTS;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data.alteration, Alteration::DropConstraints);
+    assert_eq!(p.query_data.constraints[&name].len(), 2);
+}
+
+#[test]
+#[should_panic]
+fn test_alter_add_constraints_without_constraint() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database ADD ON name;".to_string())
+        .parse();
+}
+
+#[test]
+#[should_panic]
+fn test_alter_add_field_constraining_other_field() {
+    let mut p = parser::Parser::new();
+    p.set_query("ALTER users ON my_database ADD UINT(1) age EXISTS, ON name UNIQUE;".to_string())
+        .parse();
+}
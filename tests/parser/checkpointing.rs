@@ -0,0 +1,24 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_checkpoint() {
+    let mut p = parser::Parser::new();
+    p.set_query("CHECKPOINT ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Checkpoint);
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_checkpoint_without_database() {
+    let mut p = parser::Parser::new();
+    p.set_query("CHECKPOINT ON;".to_string()).parse();
+}
@@ -0,0 +1,83 @@
+use arraysql::parser::{
+    self,
+    query::{BinaryOperation, Expression, Identifier, QueryType},
+};
+
+#[test]
+fn test_delete() {
+    let mut p = parser::Parser::new();
+    p.set_query("DELETE ON users ON my_database WHERE (age < 13);".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Delete);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert_eq!(
+        p.query_data.conditions,
+        Expression::Binary(
+            BinaryOperation::LesserThan,
+            Box::new((
+                Expression::Identifier(Identifier::Field("age".to_string())),
+                Expression::Identifier(Identifier::IntLiteral(13))
+            ))
+        )
+    );
+}
+
+#[test]
+fn test_delete_everything() {
+    let mut p = parser::Parser::new();
+    p.set_query("DELETE ON users ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Delete);
+    assert_eq!(p.query_data.conditions, Expression::None);
+}
+
+#[test]
+#[should_panic]
+fn test_delete_without_table() {
+    let mut p = parser::Parser::new();
+    p.set_query("DELETE users ON my_database;".to_string())
+        .parse();
+}
+
+#[test]
+fn test_delete_unparenthesized() {
+    let mut p = parser::Parser::new();
+    p.set_query("DELETE ON users ON my_database WHERE id = 1;".to_string())
+        .parse();
+
+    assert_eq!(
+        p.query_data.conditions,
+        Expression::Binary(
+            BinaryOperation::Equals,
+            Box::new((
+                Expression::Identifier(Identifier::Field("id".to_string())),
+                Expression::Identifier(Identifier::IntLiteral(1))
+            ))
+        )
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_delete_empty_condition() {
+    let mut p = parser::Parser::new();
+    p.set_query("DELETE ON users ON my_database WHERE;".to_string())
+        .parse();
+}
+
+#[test]
+#[should_panic]
+fn test_delete_empty_parentheses() {
+    let mut p = parser::Parser::new();
+    p.set_query("DELETE ON users ON my_database WHERE ();".to_string())
+        .parse();
+}
@@ -0,0 +1,28 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_describe() {
+    let mut p = parser::Parser::new();
+    p.set_query("DESCRIBE users ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Describe);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_describe_without_database() {
+    let mut p = parser::Parser::new();
+    p.set_query("DESCRIBE users;".to_string()).parse();
+}
@@ -0,0 +1,66 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_drop_database() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP DATABASE my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::DatabaseDrop);
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert!(!p.query_data.if_exists);
+}
+
+#[test]
+fn test_drop_database_if_exists() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP DATABASE IF EXISTS my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::DatabaseDrop);
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert!(p.query_data.if_exists);
+}
+
+#[test]
+fn test_drop_table() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP TABLE IF EXISTS users ON my_database CASCADE;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::TableDrop);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert!(p.query_data.if_exists);
+    assert!(p.query_data.cascade);
+}
+
+#[test]
+#[should_panic]
+fn test_drop_database_cascade() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP DATABASE my_database CASCADE;".to_string())
+        .parse();
+}
+
+#[test]
+#[should_panic]
+fn test_drop_table_without_database() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP TABLE users;".to_string()).parse();
+}
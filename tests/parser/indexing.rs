@@ -0,0 +1,129 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, Index, IndexKind, QueryType},
+};
+
+#[test]
+fn test_index() {
+    let mut p = parser::Parser::new();
+    p.set_query("INDEX by_name ON users ON my_database STRUCTURED (name, tier);".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::IndexCreation);
+    assert_eq!(
+        p.query_data.index_name,
+        Identifier::StringLiteral("by_name".to_string())
+    );
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert_eq!(
+        p.query_data.index_fields,
+        vec![
+            Identifier::Field("name".to_string()),
+            Identifier::Field("tier".to_string())
+        ]
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_index_without_fields() {
+    let mut p = parser::Parser::new();
+    p.set_query("INDEX by_name ON users ON my_database;".to_string())
+        .parse();
+}
+
+#[test]
+fn test_drop_index() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP INDEX IF EXISTS by_name ON users ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::IndexDrop);
+    assert_eq!(
+        p.query_data.index_name,
+        Identifier::StringLiteral("by_name".to_string())
+    );
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert!(p.query_data.if_exists);
+}
+
+#[test]
+#[should_panic]
+fn test_drop_index_without_table() {
+    let mut p = parser::Parser::new();
+    p.set_query("DROP INDEX by_name ON my_database;".to_string())
+        .parse();
+}
+
+#[test]
+fn test_indexed_table() {
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "TABLE users ON my_database STRUCTURED (UINT(1) id, STRING(64) name) MODE FADD INDEXED (by_name (name), by_both (name, id));"
+            .to_string(),
+    )
+    .parse();
+
+    assert_eq!(p.query_data._type, QueryType::TableCreation);
+    assert_eq!(
+        p.query_data.indexes,
+        vec![
+            Index {
+                name: Identifier::StringLiteral("by_name".to_string()),
+                fields: vec![Identifier::Field("name".to_string())],
+                kind: IndexKind::BTree,
+            },
+            Index {
+                name: Identifier::StringLiteral("by_both".to_string()),
+                fields: vec![
+                    Identifier::Field("name".to_string()),
+                    Identifier::Field("id".to_string())
+                ],
+                kind: IndexKind::BTree,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_hash_index() {
+    let mut p = parser::Parser::new();
+    p.set_query("INDEX by_id ON orders ON my_database STRUCTURED (id) HASH;".to_string())
+        .parse();
+    assert_eq!(p.query_data._type, QueryType::IndexCreation);
+    assert_eq!(p.query_data.index_kind, IndexKind::Hash);
+
+    let mut p = parser::Parser::new();
+    p.set_query("INDEX by_id ON orders ON my_database STRUCTURED (id);".to_string())
+        .parse();
+    assert_eq!(p.query_data.index_kind, IndexKind::BTree);
+
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "TABLE orders ON my_database STRUCTURED (UINT(1) id) MODE FADD INDEXED (by_id (id) HASH, by_id_ordered (id) BTREE);"
+            .to_string(),
+    )
+    .parse();
+    assert_eq!(
+        p.query_data
+            .indexes
+            .iter()
+            .map(|index| index.kind)
+            .collect::<Vec<IndexKind>>(),
+        vec![IndexKind::Hash, IndexKind::BTree]
+    );
+}
@@ -9,3 +9,39 @@ pub mod inserting;
 
 // #[cfg(test)]
 pub mod reading;
+
+// #[cfg(test)]
+pub mod altering;
+
+// #[cfg(test)]
+pub mod snapshots;
+
+// #[cfg(test)]
+pub mod deleting;
+
+// #[cfg(test)]
+pub mod updating;
+
+// #[cfg(test)]
+pub mod dropping;
+
+// #[cfg(test)]
+pub mod renaming;
+
+// #[cfg(test)]
+pub mod showing;
+
+// #[cfg(test)]
+pub mod describing;
+
+// #[cfg(test)]
+pub mod checkpointing;
+
+// #[cfg(test)]
+pub mod transactions;
+
+// #[cfg(test)]
+pub mod indexing;
+
+// #[cfg(test)]
+pub mod analyzing;
@@ -135,3 +135,77 @@ fn test_where_advanced() {
         )
     );
 }
+
+#[test]
+fn test_where_keyword_prefixed_field() {
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "READ STRUCTURED (order_id) ON orders ON my_database WHERE (order_id >= 13);".to_string(),
+    )
+    .parse();
+
+    assert_eq!(
+        p.query_data.conditions,
+        Expression::Binary(
+            BinaryOperation::GreaterThanEqualTo,
+            Box::new((
+                Expression::Identifier(Identifier::Field("order_id".to_string())),
+                Expression::Identifier(Identifier::IntLiteral(13))
+            ))
+        )
+    );
+}
+
+#[test]
+fn test_read_at_snapshot() {
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "READ STRUCTURED (name) ON users ON my_database AT before_migration WHERE (age >= 13);"
+            .to_string(),
+    )
+    .parse();
+
+    assert_eq!(
+        p.query_data.snapshot_name,
+        Identifier::StringLiteral("before_migration".to_string())
+    );
+    assert_eq!(
+        p.query_data.conditions,
+        Expression::Binary(
+            BinaryOperation::GreaterThanEqualTo,
+            Box::new((
+                Expression::Identifier(Identifier::Field("age".to_string())),
+                Expression::Identifier(Identifier::IntLiteral(13))
+            ))
+        )
+    );
+}
+
+#[test]
+fn test_explain_read() {
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "EXPLAIN READ STRUCTURED (name) ON users ON my_database WHERE (age >= 13);".to_string(),
+    )
+    .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Read);
+    assert!(p.query_data.explain);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+
+    let mut p = parser::Parser::new();
+    p.set_query("READ STRUCTURED (name) ON users ON my_database;".to_string())
+        .parse();
+    assert!(!p.query_data.explain);
+}
+
+#[test]
+#[should_panic]
+fn test_explain_other_statement() {
+    let mut p = parser::Parser::new();
+    p.set_query("EXPLAIN DELETE ON users ON my_database WHERE (age >= 13);".to_string())
+        .parse();
+}
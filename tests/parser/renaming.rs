@@ -0,0 +1,50 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_rename_database() {
+    let mut p = parser::Parser::new();
+    p.set_query("RENAME DATABASE my_database INTO shop;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::DatabaseRename);
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert_eq!(
+        p.query_data.new_name,
+        Identifier::StringLiteral("shop".to_string())
+    );
+}
+
+#[test]
+fn test_rename_table() {
+    let mut p = parser::Parser::new();
+    p.set_query("RENAME TABLE users ON my_database INTO customers;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::TableRename);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert_eq!(
+        p.query_data.new_name,
+        Identifier::StringLiteral("customers".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_rename_without_new_name() {
+    let mut p = parser::Parser::new();
+    p.set_query("RENAME TABLE users ON my_database;".to_string())
+        .parse();
+}
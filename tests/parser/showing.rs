@@ -0,0 +1,32 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_show_databases() {
+    let mut p = parser::Parser::new();
+    p.set_query("SHOW DATABASES;".to_string()).parse();
+
+    assert_eq!(p.query_data._type, QueryType::ShowDatabases);
+}
+
+#[test]
+fn test_show_tables() {
+    let mut p = parser::Parser::new();
+    p.set_query("SHOW TABLES ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::ShowTables);
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_show_tables_without_database() {
+    let mut p = parser::Parser::new();
+    p.set_query("SHOW TABLES;".to_string()).parse();
+}
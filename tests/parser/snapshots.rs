@@ -0,0 +1,138 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_snapshot() {
+    let mut p = parser::Parser::new();
+    p.set_query("SNAPSHOT before_migration ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Snapshot);
+    assert_eq!(
+        p.query_data.snapshot_name,
+        Identifier::StringLiteral("before_migration".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+fn test_snapshot_list() {
+    let mut p = parser::Parser::new();
+    p.set_query("SNAPSHOTS ON my_database;".to_string()).parse();
+
+    assert_eq!(p.query_data._type, QueryType::SnapshotList);
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+fn test_restore() {
+    let mut p = parser::Parser::new();
+    p.set_query("RESTORE before_migration ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Restore);
+    assert_eq!(
+        p.query_data.snapshot_name,
+        Identifier::StringLiteral("before_migration".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_snapshot_without_name() {
+    let mut p = parser::Parser::new();
+    p.set_query("SNAPSHOT ON my_database;".to_string()).parse();
+}
+
+#[test]
+fn test_table_snapshot() {
+    let mut p = parser::Parser::new();
+    p.set_query("SNAPSHOT users_backup ON users ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Snapshot);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+fn test_table_restore_into() {
+    let mut p = parser::Parser::new();
+    p.set_query("RESTORE users_backup ON users ON my_database INTO old_users;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Restore);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.new_name,
+        Identifier::StringLiteral("old_users".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_snapshot_into() {
+    let mut p = parser::Parser::new();
+    p.set_query("SNAPSHOT users_backup ON users ON my_database INTO old_users;".to_string())
+        .parse();
+}
+
+#[test]
+fn test_snapshot_message() {
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "SNAPSHOT nightly ON users ON my_database MESSAGE 'Before the import';".to_string(),
+    )
+    .parse();
+
+    assert_eq!(
+        p.query_data.snapshot_message,
+        Identifier::StringLiteral("'Before the import'".to_string())
+    );
+}
+
+#[test]
+fn test_diff() {
+    let mut p = parser::Parser::new();
+    p.set_query("DIFF first, second ON my_database;".to_string())
+        .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Diff);
+    assert_eq!(
+        p.query_data.snapshot_name,
+        Identifier::StringLiteral("first".to_string())
+    );
+    assert_eq!(
+        p.query_data.other_snapshot_name,
+        Identifier::StringLiteral("second".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_restore_message() {
+    let mut p = parser::Parser::new();
+    p.set_query("RESTORE nightly ON my_database MESSAGE 'Nope';".to_string())
+        .parse();
+}
@@ -0,0 +1,50 @@
+use arraysql::parser::{
+    self,
+    query::{Identifier, QueryType},
+};
+
+#[test]
+fn test_transaction_statements() {
+    for (query, query_type) in [
+        ("BEGIN;", QueryType::Begin),
+        ("COMMIT;", QueryType::Commit),
+        ("ROLLBACK;", QueryType::Rollback),
+    ] {
+        let mut p = parser::Parser::new();
+        p.set_query(query.to_string()).parse();
+
+        assert_eq!(p.query_data._type, query_type);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_commit_with_trailing_tokens() {
+    let mut p = parser::Parser::new();
+    p.set_query("COMMIT my_database;".to_string()).parse();
+}
+
+#[test]
+fn test_savepoint_statements() {
+    for (query, query_type) in [
+        ("SAVEPOINT batch_1;", QueryType::Savepoint),
+        ("ROLLBACK TO batch_1;", QueryType::RollbackTo),
+        ("RELEASE batch_1;", QueryType::Release),
+    ] {
+        let mut p = parser::Parser::new();
+        p.set_query(query.to_string()).parse();
+
+        assert_eq!(p.query_data._type, query_type);
+        assert_eq!(
+            p.query_data.savepoint_name,
+            Identifier::StringLiteral("batch_1".to_string())
+        );
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_savepoint_without_name() {
+    let mut p = parser::Parser::new();
+    p.set_query("ROLLBACK TO;".to_string()).parse();
+}
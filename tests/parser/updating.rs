@@ -0,0 +1,97 @@
+use arraysql::parser::{
+    self,
+    query::{BinaryOperation, Expression, Identifier, QueryType},
+};
+
+#[test]
+fn test_update() {
+    let mut p = parser::Parser::new();
+    p.set_query(
+        "UPDATE ON users ON my_database SET (age = age + 1, name = 'bob') WHERE (age < 13);"
+            .to_string(),
+    )
+    .parse();
+
+    assert_eq!(p.query_data._type, QueryType::Update);
+    assert_eq!(
+        p.query_data.table_name,
+        Identifier::StringLiteral("users".to_string())
+    );
+    assert_eq!(
+        p.query_data.db_name,
+        Identifier::StringLiteral("my_database".to_string())
+    );
+    assert_eq!(
+        p.query_data.assignments,
+        vec![
+            (
+                Identifier::Field("age".to_string()),
+                Expression::Binary(
+                    BinaryOperation::Addition,
+                    Box::new((
+                        Expression::Identifier(Identifier::Field("age".to_string())),
+                        Expression::Identifier(Identifier::IntLiteral(1))
+                    ))
+                )
+            ),
+            (
+                Identifier::Field("name".to_string()),
+                Expression::Identifier(Identifier::StringLiteral("'bob'".to_string()))
+            )
+        ]
+    );
+    assert_eq!(
+        p.query_data.conditions,
+        Expression::Binary(
+            BinaryOperation::LesserThan,
+            Box::new((
+                Expression::Identifier(Identifier::Field("age".to_string())),
+                Expression::Identifier(Identifier::IntLiteral(13))
+            ))
+        )
+    );
+}
+
+#[test]
+fn test_update_everything() {
+    let mut p = parser::Parser::new();
+    p.set_query("UPDATE ON users ON my_database SET (age = 0);".to_string())
+        .parse();
+
+    assert_eq!(p.query_data.conditions, Expression::None);
+    assert_eq!(p.query_data.assignments.len(), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_update_without_value() {
+    let mut p = parser::Parser::new();
+    p.set_query("UPDATE ON users ON my_database SET (age = );".to_string())
+        .parse();
+}
+
+#[test]
+fn test_update_unparenthesized() {
+    let mut p = parser::Parser::new();
+    p.set_query("UPDATE ON users ON my_database SET (age = 13) WHERE id = 1;".to_string())
+        .parse();
+
+    assert_eq!(
+        p.query_data.conditions,
+        Expression::Binary(
+            BinaryOperation::Equals,
+            Box::new((
+                Expression::Identifier(Identifier::Field("id".to_string())),
+                Expression::Identifier(Identifier::IntLiteral(1))
+            ))
+        )
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_update_empty_condition() {
+    let mut p = parser::Parser::new();
+    p.set_query("UPDATE ON users ON my_database SET (age = 13) WHERE;".to_string())
+        .parse();
+}